@@ -1,9 +1,17 @@
 use std::env;
 
 use anyhow::Result;
-use swarms_rs::{llm::provider::openai::OpenAI, structs::agent::Agent};
+use serde::Deserialize;
+use swarms_rs::llm::provider::openai::OpenAI;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+#[derive(Debug, Deserialize)]
+struct Article {
+    title: String,
+    url: String,
+    metadata: Option<String>,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv::dotenv().ok();
@@ -44,8 +52,8 @@ async fn main() -> Result<()> {
         .save_state_dir("./temp/fetch-agent")
         .build();
 
-    let response = agent
-        .run(
+    let articles: Vec<Article> = agent
+        .run_json(
             r#"Fetch Hacker News, after that, fetch each news page, and get the title and url of the top 5 articles.
              Finally, return a json array of the top 5 articles.
              
@@ -58,12 +66,18 @@ async fn main() -> Result<()> {
                 },
                 ...
              ]
-             "#
-            .to_owned(),
+             "#,
         )
-        .await
-        .unwrap();
+        .await?;
+
+    for article in &articles {
+        println!("{} - {}", article.title, article.url);
+        if let Some(metadata) = &article.metadata {
+            println!("    {metadata}");
+        }
+    }
 
-    println!("{response}");
+    // Stop the MCP server child process
+    agent.shutdown().await;
     Ok(())
 }
@@ -75,5 +75,8 @@ async fn main() -> Result<()> {
         .unwrap();
 
     println!("{response}");
+
+    // Stop the MCP server child process
+    agent.shutdown().await;
     Ok(())
 }
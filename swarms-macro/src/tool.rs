@@ -20,6 +20,7 @@ struct ArgMeta {
     name: String,
     description: Option<String>,
     required: Option<bool>,
+    allowed_values: Vec<String>,
 }
 
 impl Parse for ToolAttribute {
@@ -81,6 +82,7 @@ impl Parse for ArgMeta {
             name: input.parse::<Ident>()?.to_string().trim().to_owned(),
             description: None,
             required: None,
+            allowed_values: Vec::new(),
         };
 
         if input.peek(Token![,]) {
@@ -117,6 +119,25 @@ impl Parse for ArgMeta {
                                     })?;
                                 arg.required = Some(lit.value);
                             }
+                            "allowed_values" => {
+                                let array = syn::parse2::<syn::ExprArray>(nv.value.into_token_stream())
+                                    .map_err(|e| {
+                                        Error::new_spanned(
+                                            &value,
+                                            format!("Expected an array of string literals for 'allowed_values', got: {}", e),
+                                        )
+                                    })?;
+                                for elem in array.elems {
+                                    let lit = syn::parse2::<LitStr>(elem.into_token_stream())
+                                        .map_err(|e| {
+                                            Error::new_spanned(
+                                                &value,
+                                                format!("Expected string literal in 'allowed_values', error: {}", e),
+                                            )
+                                        })?;
+                                    arg.allowed_values.push(lit.value());
+                                }
+                            }
                             _ => {
                                 return Err(Error::new_spanned(
                                     ident,
@@ -235,6 +256,27 @@ fn is_custom_struct(ty: &Type) -> bool {
     }
 }
 
+/// Check if the given type is `String` or `Option<String>`, returning whether it is optional
+fn string_arg_kind(ty: &Type) -> Option<bool> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = &type_path.path.segments[0];
+    match segment.ident.to_string().as_str() {
+        "String" => Some(false),
+        "Option" => match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => match &args.args[0] {
+                syn::GenericArgument::Type(inner_type) => string_arg_kind(inner_type)
+                    .filter(|optional| !optional)
+                    .map(|_| true),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 pub fn tool_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
     let tool_attr = parse_macro_input!(attr as ToolAttribute);
     let input_fn = parse_macro_input!(item as ItemFn);
@@ -374,6 +416,98 @@ pub fn tool_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
         })
         .collect();
 
+    // `"enum"` keyword of each argument's schema
+    let arg_enums: Vec<_> = arg_names
+        .iter()
+        .map(|pat| {
+            let ident = match &***pat {
+                syn::Pat::Ident(pat_ident) => &pat_ident.ident,
+                _ => panic!("Only simple identifiers are supported in tool arguments"),
+            };
+            match tool_attr.args.iter().find(|arg| *ident == arg.name) {
+                Some(arg) if !arg.allowed_values.is_empty() => {
+                    let values = &arg.allowed_values;
+                    quote! { , "enum": [#(#values),*] }
+                }
+                _ => quote! {},
+            }
+        })
+        .collect();
+
+    // Check arguments with allowed values before calling the function
+    let validations: Vec<_> = args
+        .clone()
+        .filter_map(|(pat, ty)| {
+            let ident = match &**pat {
+                syn::Pat::Ident(pat_ident) => &pat_ident.ident,
+                _ => panic!("Only simple identifiers are supported in tool arguments"),
+            };
+            let arg = tool_attr
+                .args
+                .iter()
+                .find(|arg| *ident == arg.name && !arg.allowed_values.is_empty())?;
+            let values = &arg.allowed_values;
+            let check = quote! {
+                if ![#(#values),*].contains(&value.as_str()) {
+                    return Err(format!(
+                        "`{}` must be one of {:?}, got {:?}",
+                        stringify!(#ident),
+                        [#(#values),*],
+                        value
+                    ));
+                }
+            };
+            match string_arg_kind(ty) {
+                Some(false) => Some(quote! {
+                    let value = &args.#ident;
+                    #check
+                }),
+                Some(true) => Some(quote! {
+                    if let Some(value) = &args.#ident {
+                        #check
+                    }
+                }),
+                None => panic!(
+                    "Argument '{}' has allowed_values but is not a String or Option<String>",
+                    arg.name
+                ),
+            }
+        })
+        .collect();
+
+    // Rich output is handed to the model as is rather than JSON-encoded
+    let returns_tool_output = match return_type {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "ToolOutput"),
+        _ => false,
+    };
+    let format_output_impl = if returns_tool_output {
+        quote! {
+            fn format_output(
+                &self,
+                output: Self::Output,
+            ) -> Result<String, swarms_rs::structs::tool::ToolError> {
+                Ok(output.to_string())
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let validate_impl = if validations.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            async fn validate(&self, args: &Self::Args) -> Result<(), String> {
+                #(#validations)*
+                Ok(())
+            }
+        }
+    };
+
     // Collect required arguments
     let required_args: Vec<_> = arg_names
         .iter()
@@ -433,11 +567,13 @@ pub fn tool_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
                                 stringify!(#arg_names): {
                                     #json_types,
                                     "description": #arg_descriptions
+                                    #arg_enums
                                 }
                             ),*
                         },
                         #required_field
                     }),
+                    output_schema: None,
                 }
             }
         }
@@ -448,6 +584,7 @@ pub fn tool_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
                     name: Self::NAME.to_string(),
                     description: #description,
                     parameters: serde_json::to_value(schemars::schema_for!(#args_struct_name)).unwrap(),
+                    output_schema: None,
                 }
             }
         }
@@ -473,6 +610,10 @@ pub fn tool_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
 
             #definition_impl
 
+            #validate_impl
+
+            #format_output_impl
+
             #call_impl
         }
 
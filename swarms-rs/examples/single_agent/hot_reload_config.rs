@@ -0,0 +1,81 @@
+//! Answers one question per line of stdin with a long-lived agent, reloading its
+//! settings from `SWARMS_*` environment variables (e.g. from a mounted `.env` file)
+//! whenever the process receives SIGHUP:
+//!
+//! ```sh
+//! cargo run --example hot_reload_config
+//! # in another shell, after editing .env
+//! kill -HUP <pid>
+//! ```
+
+use std::env;
+
+use anyhow::{Context, Result};
+use swarms_rs::llm::provider::openai::OpenAI;
+use swarms_rs::structs::agent::Agent;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::signal::unix::{SignalKind, signal};
+
+// Single-threaded, so that `reload_dotenv` can safely modify the environment
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+    tracing_subscriber::fmt::init();
+
+    let api_key = env::var("OPENAI_API_KEY").context("OPENAI_API_KEY is not set")?;
+    let mut agent = OpenAI::new(api_key)
+        .set_model("gpt-4o-mini")
+        .agent_builder()
+        .system_prompt("You are a helpful assistant.")
+        .agent_name("HotReloadAgent")
+        .max_loops(1)
+        .build();
+    // Apply the environment once at startup as well
+    agent.reload_config()?;
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    println!("Ask a question (pid {}):", std::process::id());
+    loop {
+        tokio::select! {
+            _ = hangups.recv() => {
+                reload_dotenv();
+                match agent.reload_config() {
+                    Ok(changes) if changes.is_empty() => println!("Config unchanged"),
+                    Ok(changes) => {
+                        for change in changes {
+                            println!("Config reloaded: {change}");
+                        }
+                    },
+                    Err(e) => eprintln!("Config not reloaded: {e}"),
+                }
+            },
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    break;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match agent.run(line).await {
+                    Ok(response) => println!("{response}"),
+                    Err(e) => eprintln!("Agent failed: {e}"),
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Re-reads `.env`, overriding the variables it sets.
+fn reload_dotenv() {
+    let Ok(items) = dotenv::dotenv_iter() else {
+        return;
+    };
+    for (key, value) in items.flatten() {
+        // SAFETY: the runtime is single-threaded and its blocking stdin reader doesn't
+        // touch the environment, so nothing reads it concurrently
+        unsafe { env::set_var(key, value) };
+    }
+}
@@ -36,6 +36,7 @@ impl Tool for CalculatorTool {
                 },
                 "required": ["expression"]
             }),
+            output_schema: None,
         }
     }
 
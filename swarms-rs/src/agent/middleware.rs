@@ -0,0 +1,140 @@
+//! Middleware hooks for pre- and post-processing `SwarmsAgent` runs.
+//!
+//! A middleware sees the task before the agent starts its loop and the final output
+//! before it is handed back to the caller. Middlewares are applied in the order they
+//! were registered with [`SwarmsAgentBuilder::add_middleware`](crate::agent::SwarmsAgentBuilder::add_middleware),
+//! for both the `before` and the `after` chain.
+
+use futures::future::BoxFuture;
+
+use crate::structs::agent::AgentError;
+
+/// A hook that can rewrite an agent's task before execution and its output afterwards.
+///
+/// Both methods default to returning their input unchanged, so implementors only need
+/// to override the side they care about. Returning an error aborts the run.
+pub trait Middleware: Send + Sync {
+    /// Transforms the task before the agent starts its execution loop.
+    fn before<'a>(&'a self, task: &'a str) -> BoxFuture<'a, Result<String, AgentError>> {
+        Box::pin(async move { Ok(task.to_owned()) })
+    }
+
+    /// Transforms the final output before it is returned from `run`.
+    fn after<'a>(&'a self, output: &'a str) -> BoxFuture<'a, Result<String, AgentError>> {
+        Box::pin(async move { Ok(output.to_owned()) })
+    }
+}
+
+/// Masks a configurable list of words in both the task and the output.
+///
+/// Matching is ASCII case-insensitive and replaces every character of the match
+/// with the mask character (`*` by default).
+#[derive(Debug, Clone)]
+pub struct ProfanityFilter {
+    words: Vec<String>,
+    mask: char,
+}
+
+impl ProfanityFilter {
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            words: words
+                .into_iter()
+                .map(|w| w.into().to_ascii_lowercase())
+                .filter(|w| !w.is_empty())
+                .collect(),
+            mask: '*',
+        }
+    }
+
+    pub fn mask(mut self, mask: char) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Returns `text` with every filtered word masked out.
+    pub fn filter(&self, text: &str) -> String {
+        let mut result = text.to_owned();
+        for word in &self.words {
+            // ASCII lowercasing keeps byte offsets identical to the original text.
+            let haystack = result.to_ascii_lowercase();
+            let replacement = self.mask.to_string().repeat(word.chars().count());
+            let mut filtered = String::with_capacity(result.len());
+            let mut last = 0;
+            for (start, _) in haystack.match_indices(word.as_str()) {
+                filtered.push_str(&result[last..start]);
+                filtered.push_str(&replacement);
+                last = start + word.len();
+            }
+            filtered.push_str(&result[last..]);
+            result = filtered;
+        }
+        result
+    }
+}
+
+impl Middleware for ProfanityFilter {
+    fn before<'a>(&'a self, task: &'a str) -> BoxFuture<'a, Result<String, AgentError>> {
+        Box::pin(async move { Ok(self.filter(task)) })
+    }
+
+    fn after<'a>(&'a self, output: &'a str) -> BoxFuture<'a, Result<String, AgentError>> {
+        Box::pin(async move { Ok(self.filter(output)) })
+    }
+}
+
+/// Logs the incoming task and the size of the final output without modifying either.
+#[derive(Debug, Clone, Default)]
+pub struct TaskLogger;
+
+impl Middleware for TaskLogger {
+    fn before<'a>(&'a self, task: &'a str) -> BoxFuture<'a, Result<String, AgentError>> {
+        Box::pin(async move {
+            log::info!("📥 Task received: {}", task);
+            Ok(task.to_owned())
+        })
+    }
+
+    fn after<'a>(&'a self, output: &'a str) -> BoxFuture<'a, Result<String, AgentError>> {
+        Box::pin(async move {
+            log::info!("📤 Task finished, output length: {} chars", output.len());
+            Ok(output.to_owned())
+        })
+    }
+}
+
+/// Trims surrounding whitespace from the output and optionally caps its length.
+#[derive(Debug, Clone, Default)]
+pub struct OutputTrimmer {
+    max_chars: Option<usize>,
+}
+
+impl OutputTrimmer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Truncates the trimmed output to at most `max_chars` characters.
+    pub fn max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = Some(max_chars);
+        self
+    }
+
+    pub fn trim(&self, output: &str) -> String {
+        let trimmed = output.trim();
+        match self.max_chars {
+            Some(max) => trimmed.chars().take(max).collect(),
+            None => trimmed.to_owned(),
+        }
+    }
+}
+
+impl Middleware for OutputTrimmer {
+    fn after<'a>(&'a self, output: &'a str) -> BoxFuture<'a, Result<String, AgentError>> {
+        Box::pin(async move { Ok(self.trim(output)) })
+    }
+}
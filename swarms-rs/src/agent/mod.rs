@@ -1,3 +1,5 @@
+pub mod middleware;
 pub mod swarms_agent;
 
+pub use middleware::*;
 pub use swarms_agent::*;
@@ -85,25 +85,35 @@
 //! - **Task Hashing**: Efficient state management using content-based hashing
 
 use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, VecDeque},
     ffi::OsStr,
-    hash::{Hash, Hasher},
+    hash::{BuildHasher, Hasher, RandomState},
     ops::Deref,
     path::Path,
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
 };
 
-use twox_hash::XxHash64;
-
 use dashmap::DashMap;
-use futures::{StreamExt, future::BoxFuture, stream};
+use futures::{
+    StreamExt,
+    future::{self, BoxFuture},
+    stream,
+};
+use regex::Regex;
 use reqwest::IntoUrl;
 use rmcp::{
-    ServiceExt,
+    RoleClient, ServiceExt,
     model::{ClientCapabilities, ClientInfo, Implementation},
+    service::{DynService, RunningService},
     transport::{SseTransport, TokioChildProcess},
 };
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use swarms_macro::tool;
 use tabled::{
     builder::Builder,
@@ -111,25 +121,72 @@ use tabled::{
 };
 use thiserror::Error;
 use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader},
     process::Command,
-    sync::{Mutex, mpsc},
+    sync::mpsc,
 };
+use tokio_util::sync::CancellationToken;
+use ulid::Ulid;
 
 use crate::{
     self as swarms_rs,
+    agent::middleware::Middleware,
     llm::{
         self,
-        request::{CompletionRequest, ToolDefinition},
+        cache::CachingModel,
+        completion::ToolFunction,
+        request::{CompletionRequest, CompletionResponse, ToolDefinition},
     },
-    log_agent, log_error_ctx, log_llm, log_memory, log_perf, log_task,
+    log_agent, log_error_ctx, log_llm, log_memory, log_perf, log_task, prompts,
     structs::{
-        conversation::{AgentShortMemory, Role},
-        persistence,
-        tool::{MCPTool, Tool, ToolDyn},
+        conversation::{AgentConversation, AgentShortMemory, Role, RoleStats, estimate_tokens},
+        persistence::{self, FileSystemBackend, MemoryBackend},
+        prebuilt_tools::ToolPreset,
+        tool::{MCPTool, Tool, ToolDyn, ToolError, Toolkit},
+        utils::extract_json,
     },
 };
 
-use crate::structs::agent::{Agent, AgentConfig, AgentError};
+use crate::structs::agent::{
+    Agent, AgentBuildError, AgentConfig, AgentError, ConfigChange,
+    DEFAULT_CONTINUATION_PROMPT_TEMPLATE, SYSTEM_PROMPT_REFRESH_PREFIX, StopWordMatching,
+    TOOLS_ONLY_PROMPT, render_loop_prompt,
+};
+#[cfg(feature = "pdf-export")]
+use crate::structs::pdf_export;
+
+/// Wait before retrying a rate-limited request when the provider doesn't say how long
+const DEFAULT_RATE_LIMIT_DELAY: Duration = Duration::from_secs(1);
+/// Upper bound for honoring a provider's `Retry-After`, so a run doesn't stall for hours
+const MAX_RATE_LIMIT_DELAY: Duration = Duration::from_secs(60);
+/// Upper bound for the exponential backoff between failed attempts
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// How an agent decides that a task is done before `max_loops` is reached, see
+/// [`SwarmsAgentBuilder::completion_strategy`].
+#[derive(Clone)]
+pub enum CompletionStrategy {
+    /// The model calls the built-in `task_evaluator` tool to mark the task complete.
+    /// This is the default.
+    TaskEvaluatorTool,
+    /// The run stops once a response contains one of these words.
+    StopWords(Vec<String>),
+    /// Every run takes exactly `max_loops` loops.
+    MaxLoopsOnly,
+    /// The run stops once this returns `true` for a response.
+    Custom(Arc<dyn Fn(&str) -> bool + Send + Sync>),
+}
+
+impl std::fmt::Debug for CompletionStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TaskEvaluatorTool => f.write_str("TaskEvaluatorTool"),
+            Self::StopWords(words) => f.debug_tuple("StopWords").field(words).finish(),
+            Self::MaxLoopsOnly => f.write_str("MaxLoopsOnly"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
 
 /// Builder pattern implementation for creating `SwarmsAgent` instances with customizable configuration.
 ///
@@ -176,6 +233,16 @@ where
     tools: Vec<ToolDefinition>,
     /// Implementation instances of tools, keyed by tool name
     tools_impl: DashMap<String, Arc<dyn ToolDyn>>,
+    /// Middlewares applied to the task before and the output after each run
+    middlewares: Vec<Arc<dyn Middleware>>,
+    /// MCP server connections started by this builder
+    mcp_services: Vec<McpServiceHandle>,
+    /// Model used for the planning step instead of `model`
+    planning_model: Option<Arc<dyn PlanningModel>>,
+    /// Completion check set by `CompletionStrategy::Custom`
+    completion_check: Option<Arc<dyn Fn(&str) -> bool + Send + Sync>>,
+    /// Storage for task state instead of files in `save_state_dir`
+    memory_backend: Option<Arc<dyn MemoryBackend>>,
 }
 
 impl<M> SwarmsAgentBuilder<M>
@@ -211,6 +278,11 @@ where
             system_prompt: None,
             tools: vec![],
             tools_impl: DashMap::new(),
+            middlewares: vec![],
+            mcp_services: vec![],
+            planning_model: None,
+            completion_check: None,
+            memory_backend: None,
         }
     }
 
@@ -330,6 +402,7 @@ where
     ///                 },
     ///                 "required": ["expression"]
     ///             }),
+    ///             output_schema: None,
     ///         }
     ///     }
     ///
@@ -353,9 +426,149 @@ where
     /// # }
     /// ```
     pub fn add_tool<T: Tool + 'static>(mut self, tool: T) -> Self {
-        self.tools.push(tool.definition());
-        self.tools_impl
-            .insert(tool.name().to_string(), Arc::new(tool) as Arc<dyn ToolDyn>);
+        self.insert_tool(Arc::new(tool));
+        self
+    }
+
+    /// Adds a tool, advertising it to the model with a different description.
+    ///
+    /// The tool implementation is unchanged; only the `description` of the `ToolDefinition`
+    /// sent to the model is replaced. This is useful for steering tool selection per agent
+    /// when the same tool is shared across agents. A tool whose name is already registered
+    /// replaces the existing one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::agent::SwarmsAgentBuilder;
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    /// # use swarms_rs::structs::tool::Tool;
+    ///
+    /// # async fn example(search_tool: impl Tool + 'static) -> Result<(), Box<dyn std::error::Error>> {
+    /// let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env())
+    ///     .add_tool_with_description(
+    ///         search_tool,
+    ///         "Search internal engineering docs. Prefer this over guessing.",
+    ///     )
+    ///     .build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_tool_with_description<T: Tool + 'static>(
+        mut self,
+        tool: T,
+        description: impl Into<String>,
+    ) -> Self {
+        let mut definition = ToolDyn::definition(&tool);
+        definition.description = description.into();
+        self.insert_tool_with_definition(definition, Arc::new(tool));
+        self
+    }
+
+    /// Adds every tool from a [`Toolkit`] to the agent.
+    ///
+    /// A tool whose name is already registered replaces the existing one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::agent::SwarmsAgentBuilder;
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    /// use swarms_rs::structs::tool::Toolkit;
+    /// # use swarms_rs::structs::tool::ToolDyn;
+    ///
+    /// # async fn example(read_file: impl ToolDyn + 'static, write_file: impl ToolDyn + 'static) -> Result<(), Box<dyn std::error::Error>> {
+    /// let filesystem = Toolkit::new("filesystem")
+    ///     .add_tool(read_file)
+    ///     .add_tool(write_file);
+    ///
+    /// let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env())
+    ///     .add_toolkit(filesystem)
+    ///     .build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_toolkit(mut self, toolkit: Toolkit) -> Self {
+        for tool in toolkit {
+            self.insert_tool(tool);
+        }
+        self
+    }
+
+    /// Registers a curated set of tools for a common agent archetype.
+    ///
+    /// See [`ToolPreset`] for the tools each preset provides. Presets can be combined
+    /// by calling this method several times; a tool registered twice is only listed once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::agent::SwarmsAgentBuilder;
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    /// use swarms_rs::structs::prebuilt_tools::ToolPreset;
+    ///
+    /// let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env())
+    ///     .with_prebuilt_tools(ToolPreset::CodeInterpreter)
+    ///     .build();
+    /// ```
+    pub fn with_prebuilt_tools(self, preset: ToolPreset) -> Self {
+        self.add_toolkit(preset.toolkit())
+    }
+
+    /// Copies every tool registered on another builder into this one.
+    ///
+    /// Tool implementations are shared, not cloned, so this is cheap even for MCP tools.
+    /// Definitions are copied as registered, keeping descriptions set with
+    /// [`add_tool_with_description`](Self::add_tool_with_description).
+    pub fn add_tools_from<N>(mut self, other: &SwarmsAgentBuilder<N>) -> Self
+    where
+        N: llm::Model + Send + Sync,
+        N::RawCompletionResponse: Send + Sync,
+    {
+        for definition in &other.tools {
+            if let Some(tool) = other.tools_impl.get(&definition.name) {
+                self.insert_tool_with_definition(definition.clone(), Arc::clone(tool.value()));
+            }
+        }
+        self
+    }
+
+    fn insert_tool(&mut self, tool: Arc<dyn ToolDyn>) {
+        self.insert_tool_with_definition(tool.definition(), tool);
+    }
+
+    fn insert_tool_with_definition(&mut self, definition: ToolDefinition, tool: Arc<dyn ToolDyn>) {
+        match self.tools.iter_mut().find(|t| t.name == definition.name) {
+            Some(existing) => *existing = definition,
+            None => self.tools.push(definition),
+        }
+        self.tools_impl.insert(tool.name(), tool);
+    }
+
+    /// Registers a middleware that pre-processes the task and post-processes the final output.
+    ///
+    /// Middlewares run in registration order: every `before` hook is applied to the task
+    /// before the first loop, and every `after` hook is applied to the final output.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::agent::{OutputTrimmer, ProfanityFilter, SwarmsAgentBuilder, TaskLogger};
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let model = OpenAI::from_env();
+    ///
+    /// let agent = SwarmsAgentBuilder::new_with_model(model)
+    ///     .add_middleware(TaskLogger)
+    ///     .add_middleware(ProfanityFilter::new(["darn"]))
+    ///     .add_middleware(OutputTrimmer::new().max_chars(2000))
+    ///     .build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middlewares.push(Arc::new(middleware));
         self
     }
 
@@ -409,18 +622,17 @@ where
             },
         };
 
+        let cancellation = CancellationToken::new();
         let client = Arc::new(
             client_info
                 .into_dyn()
-                .serve(transport)
+                .serve_with_ct(transport, cancellation.clone())
                 .await
                 .expect("Failed to start MCP server"),
         );
 
         let mcp_tools = client.list_all_tools().await.expect("Failed to list tools");
-        mcp_tools.into_iter().fold(self, |acc, tool| {
-            acc.add_tool(MCPTool::from_server(tool, Arc::clone(&client)))
-        })
+        self.add_mcp_tools(name, client, cancellation, mcp_tools)
     }
 
     /// Adds tools from an MCP server via stdio (standard input/output).
@@ -468,9 +680,14 @@ where
         I: IntoIterator<Item = S>,
         S: AsRef<OsStr>,
     {
+        let server = command.as_ref().to_string_lossy().into_owned();
+        let cancellation = CancellationToken::new();
         let service = Arc::new(
             ().into_dyn()
-                .serve(TokioChildProcess::new(Command::new(command).args(args)).unwrap())
+                .serve_with_ct(
+                    TokioChildProcess::new(Command::new(command).args(args)).unwrap(),
+                    cancellation.clone(),
+                )
                 .await
                 .expect("Failed to start MCP server"),
         );
@@ -479,11 +696,81 @@ where
             .list_all_tools()
             .await
             .expect("Failed to list tools");
-        mcp_tools.into_iter().fold(self, |acc, tool| {
-            acc.add_tool(MCPTool::from_server(tool, Arc::clone(&service)))
-        })
+        self.add_mcp_tools(server, service, cancellation, mcp_tools)
+    }
+
+    /// Registers the tools of a connected MCP server, skipping those whose input schema
+    /// is malformed (see [`MCPTool::validate_schema`]).
+    fn add_mcp_tools(
+        self,
+        server: String,
+        service: Arc<McpService>,
+        cancellation: CancellationToken,
+        mcp_tools: Vec<rmcp::model::Tool>,
+    ) -> Self {
+        let mut skipped = Vec::new();
+        let mut tools = Vec::new();
+        for tool in mcp_tools {
+            match MCPTool::validate_schema(&tool) {
+                Ok(()) => tools.push(tool),
+                Err(e) => {
+                    log::warn!(
+                        "Skipping MCP tool `{}` from server `{server}`: {e}",
+                        tool.name
+                    );
+                    skipped.push(SkippedMcpTool {
+                        server: server.clone(),
+                        tool: tool.name.to_string(),
+                        reason: e.to_string(),
+                    });
+                },
+            }
+        }
+
+        let mut builder = tools.iter().fold(self, |acc, tool| {
+            acc.add_tool(MCPTool::from_server(tool.clone(), Arc::clone(&service)))
+        });
+        builder.mcp_services.push(McpServiceHandle {
+            cancellation,
+            service,
+            tools,
+            skipped,
+        });
+        builder
+    }
+
+    /// MCP tools that were not registered because their input schema is malformed.
+    pub fn skipped_mcp_tools(&self) -> Vec<SkippedMcpTool> {
+        self.mcp_services
+            .iter()
+            .flat_map(|handle| handle.skipped.iter().cloned())
+            .collect()
     }
 
+    /// Validates the configuration and builds the agent.
+    ///
+    /// Prefer this over [`build`](Self::build), which accepts any configuration, so that
+    /// mistakes such as zero loops or autosave without a `save_state_dir` surface here
+    /// instead of deep in the agent loop. See [`AgentConfig::validate`] for the checks.
+    pub fn try_build(self) -> Result<SwarmsAgent<M>, AgentBuildError> {
+        if let Err(e) = self.config.validate()
+            // A memory backend stores the state instead of save_state_dir
+            && !(matches!(e, AgentBuildError::AutosaveWithoutSaveStateDir)
+                && self.memory_backend.is_some())
+        {
+            return Err(e);
+        }
+        if self.config.tools_only
+            && self.tools.is_empty()
+            && !self.config.task_evaluator_tool_enabled
+        {
+            return Err(AgentBuildError::ToolsOnlyWithoutTools);
+        }
+        Ok(self.build())
+    }
+
+    /// Builds the agent without validating its configuration, see
+    /// [`try_build`](Self::try_build).
     pub fn build(mut self) -> SwarmsAgent<M> {
         if self.config.verbose && log::log_enabled!(log::Level::Info) {
             log::info!("🏗️  Building SwarmsAgent: {}", self.config.name);
@@ -503,6 +790,12 @@ where
             );
         }
 
+        // try_build rejects invalid patterns up front
+        let stop_word_regexes = self.config.stop_word_regexes().unwrap_or_else(|e| {
+            log::warn!("{e}, ignoring the stop words of {}", self.config.name);
+            Vec::new()
+        });
+
         let agent = SwarmsAgent {
             model: self.model,
             config: self.config.clone(),
@@ -510,6 +803,17 @@ where
             short_memory: AgentShortMemory::new(),
             tools: self.tools.clone(),
             tools_impl: self.tools_impl,
+            middlewares: self.middlewares,
+            persistence_checked: Arc::new(AtomicBool::new(false)),
+            saved_messages: Arc::new(DashMap::new()),
+            mcp_services: Arc::new(McpServices(self.mcp_services)),
+            persona_stack: Vec::new(),
+            timed_out_tool_calls: Arc::new(AtomicUsize::new(0)),
+            metrics: Arc::default(),
+            planning_model: self.planning_model,
+            completion_check: self.completion_check,
+            stop_word_regexes,
+            memory_backend: self.memory_backend,
         };
 
         if agent.config.verbose && log::log_enabled!(log::Level::Info) {
@@ -524,6 +828,38 @@ where
         agent
     }
 
+    /// Caches completion responses for `ttl`, keyed by a hash of the full request.
+    ///
+    /// Identical requests (same system prompt, history, tools and parameters) made within
+    /// the TTL are answered from the cache instead of calling the model again. Useful for
+    /// deterministic prompts such as tests or idempotent planning steps.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use swarms_rs::agent::SwarmsAgentBuilder;
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    ///
+    /// let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env())
+    ///     .cache_completions(Duration::from_secs(300))
+    ///     .build();
+    /// ```
+    pub fn cache_completions(self, ttl: Duration) -> SwarmsAgentBuilder<CachingModel<M>> {
+        SwarmsAgentBuilder {
+            model: CachingModel::new(self.model, ttl),
+            config: self.config,
+            system_prompt: self.system_prompt,
+            tools: self.tools,
+            tools_impl: self.tools_impl,
+            middlewares: self.middlewares,
+            mcp_services: self.mcp_services,
+            planning_model: self.planning_model,
+            completion_check: self.completion_check,
+            memory_backend: self.memory_backend,
+        }
+    }
+
     // Configuration methods
 
     pub fn agent_name(mut self, name: impl Into<String>) -> Self {
@@ -562,6 +898,30 @@ where
         self
     }
 
+    /// Uses `model` for the planning step (see [`enable_plan`](Self::enable_plan)) while
+    /// the task loops keep using the agent's model, e.g. a strong model to plan and a
+    /// cheaper one to execute.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::agent::SwarmsAgentBuilder;
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    ///
+    /// let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env_with_model("gpt-4o-mini"))
+    ///     .planning_model(OpenAI::from_env_with_model("o3"))
+    ///     .enable_plan("Outline the steps needed to: ".to_owned())
+    ///     .build();
+    /// ```
+    pub fn planning_model<P>(mut self, model: P) -> Self
+    where
+        P: llm::Model + Send + Sync + 'static,
+        P::RawCompletionResponse: Send,
+    {
+        self.planning_model = Some(Arc::new(model));
+        self
+    }
+
     pub fn enable_autosave(mut self) -> Self {
         self.config.autosave = true;
         self
@@ -572,6 +932,15 @@ where
         self
     }
 
+    /// Waits between failed attempts, starting with `retry_base_delay` and doubling
+    /// after every failure, up to 30 seconds. Each delay is jittered to a random
+    /// value between half and all of it, so agents failing together don't retry in
+    /// lockstep. Rate-limited requests wait at least as long as the provider asks.
+    pub fn retry_base_delay(mut self, retry_base_delay: Duration) -> Self {
+        self.config.retry_base_delay = Some(retry_base_delay);
+        self
+    }
+
     pub fn enable_rag_every_loop(mut self) -> Self {
         self.config.rag_every_loop = true;
         self
@@ -582,6 +951,25 @@ where
         self
     }
 
+    /// Saves task state to `backend` instead of files in `save_state_dir`, e.g. to keep
+    /// it in Redis or S3 so agents in stateless containers can still autosave. With a
+    /// backend set, autosave no longer requires a `save_state_dir`.
+    pub fn memory_backend(mut self, backend: Arc<dyn MemoryBackend>) -> Self {
+        self.memory_backend = Some(backend);
+        self
+    }
+
+    /// Saves state by appending new messages to an NDJSON file instead of rewriting
+    /// a full JSON snapshot on every loop.
+    ///
+    /// Each autosave then only writes the turns added since the previous save, which
+    /// keeps long runs (hundreds of loops) from rewriting the whole history each time.
+    /// State files use the `.ndjson` extension, one message per line.
+    pub fn incremental_save(mut self, incremental_save: bool) -> Self {
+        self.config.incremental_save = incremental_save;
+        self
+    }
+
     pub fn add_stop_word(mut self, stop_word: impl Into<String>) -> Self {
         self.config.stop_words.insert(stop_word.into());
         self
@@ -593,6 +981,47 @@ where
             .fold(self, |builder, stop_word| builder.add_stop_word(stop_word))
     }
 
+    /// Sets how stop words are found in a response. Defaults to
+    /// [`StopWordMatching::WholeWord`], so a stop word like "done" does not end the run
+    /// on "abandoned".
+    pub fn stop_word_matching(mut self, stop_word_matching: StopWordMatching) -> Self {
+        self.config.stop_word_matching = stop_word_matching;
+        self
+    }
+
+    /// Sets how the agent decides that a task is done, replacing any earlier
+    /// [`add_stop_word`](Self::add_stop_word),
+    /// [`disable_task_complete_tool`](Self::disable_task_complete_tool) or completion
+    /// strategy settings.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::sync::Arc;
+    /// use swarms_rs::agent::{CompletionStrategy, SwarmsAgentBuilder};
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    ///
+    /// let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env())
+    ///     .completion_strategy(CompletionStrategy::Custom(Arc::new(|response| {
+    ///         response.trim_end().ends_with("QED")
+    ///     })))
+    ///     .build();
+    /// ```
+    pub fn completion_strategy(mut self, strategy: CompletionStrategy) -> Self {
+        self.config.task_evaluator_tool_enabled =
+            matches!(strategy, CompletionStrategy::TaskEvaluatorTool);
+        self.config.stop_words.clear();
+        self.completion_check = None;
+        match strategy {
+            CompletionStrategy::StopWords(stop_words) => self.stop_words(stop_words),
+            CompletionStrategy::Custom(check) => {
+                self.completion_check = Some(check);
+                self
+            },
+            CompletionStrategy::TaskEvaluatorTool | CompletionStrategy::MaxLoopsOnly => self,
+        }
+    }
+
     pub fn disable_task_complete_tool(mut self) -> Self {
         self.config.task_evaluator_tool_enabled = false;
         self
@@ -676,6 +1105,117 @@ where
         self.config.pretty_print_on = pretty_print_on;
         self
     }
+
+    /// Controls how empty or whitespace-only tasks are handled by `run`.
+    ///
+    /// By default (`false`) such tasks are rejected with `AgentError::EmptyTask` before any
+    /// LLM call is made. When set to `true`, the task is executed as given. A task set with
+    /// [`default_task`](Self::default_task) always takes precedence over both behaviours.
+    pub fn allow_empty_task(mut self, allow_empty_task: bool) -> Self {
+        self.config.allow_empty_task = allow_empty_task;
+        self
+    }
+
+    /// Sets a task to run in place of an empty or whitespace-only task.
+    pub fn default_task(mut self, default_task: impl Into<String>) -> Self {
+        self.config.default_task = Some(default_task.into());
+        self
+    }
+
+    /// Truncates model responses and tool results longer than `max_response_chars`
+    /// characters before they are added to memory, guarding against runaway payloads
+    /// such as a web fetch returning a full page.
+    pub fn max_response_chars(mut self, max_response_chars: usize) -> Self {
+        self.config.max_response_chars = Some(max_response_chars);
+        self
+    }
+
+    /// Truncates each document passed to
+    /// [`run_with_documents`](SwarmsAgent::run_with_documents) to roughly
+    /// `max_document_tokens` estimated tokens, so a large file cannot crowd the task out
+    /// of the context window.
+    pub fn max_document_tokens(mut self, max_document_tokens: usize) -> Self {
+        self.config.max_document_tokens = Some(max_document_tokens);
+        self
+    }
+
+    /// Sends at most the `max_history_messages` most recent messages of the chat history
+    /// with each request, applied to the task's short-term memory as well as to history
+    /// passed to [`chat`](SwarmsAgent::chat).
+    ///
+    /// The history is cut with [`truncate_history`](llm::completion::truncate_history),
+    /// so a tool call and its results are dropped together and providers never receive
+    /// a tool result without its call. The user messages before the first reply, which
+    /// hold the task, are always kept, as is the system prompt, which is sent apart from
+    /// the history.
+    pub fn max_history_messages(mut self, max_history_messages: usize) -> Self {
+        self.config.max_history_messages = Some(max_history_messages);
+        self
+    }
+
+    /// Lets the agent use tools with models that don't support native tool calling
+    /// (see [`Model::supports_tools`](llm::Model::supports_tools)).
+    ///
+    /// The tools are described in the system prompt and a JSON tool call in the model's
+    /// text response is executed like a native one. Without this, running such a model
+    /// with tools registered fails with [`AgentError::ToolsUnsupported`].
+    pub fn prompt_tool_fallback(mut self, prompt_tool_fallback: bool) -> Self {
+        self.config.prompt_tool_fallback = prompt_tool_fallback;
+        self
+    }
+
+    /// Repeats the system prompt as a `Role::System` reminder in short-term memory every
+    /// `interval` loops, so it doesn't lose influence as the history grows in long runs.
+    /// Only the latest reminder is kept in memory.
+    pub fn system_prompt_refresh_interval(mut self, interval: u32) -> Self {
+        self.config.system_prompt_refresh_interval = Some(interval);
+        self
+    }
+
+    /// The message that ends [`SwarmsAgent::run_interactive`], `"exit"` by default.
+    pub fn exit_command(mut self, exit_command: impl Into<String>) -> Self {
+        self.config.exit_command = exit_command.into();
+        self
+    }
+
+    /// Ends the run early once the last `stagnation_window` responses are identical,
+    /// ignoring case and whitespace, instead of repeating the same answer until
+    /// `max_loops` is reached. A window of 1 is treated as 2; 0 (the default) disables
+    /// the check.
+    pub fn stagnation_window(mut self, stagnation_window: usize) -> Self {
+        self.config.stagnation_window = stagnation_window;
+        self
+    }
+
+    /// Fails tool calls that take longer than `timeout` with [`ToolError::Timeout`].
+    ///
+    /// Instead of retrying, the agent tells the model in the next loop that the tool
+    /// timed out and asks it to try an alternative approach.
+    pub fn tool_timeout(mut self, timeout: Duration) -> Self {
+        self.config.tool_timeout = Some(timeout);
+        self
+    }
+
+    /// Sends this prompt in the first loop instead of an empty one. Supports the
+    /// `{loop}`, `{max_loops}` and `{task}` placeholders.
+    pub fn first_loop_prompt(mut self, template: impl Into<String>) -> Self {
+        self.config.first_loop_prompt = Some(template.into());
+        self
+    }
+
+    /// Replaces the prompt sent at the start of every loop after the first. Supports
+    /// the `{loop}`, `{max_loops}` and `{task}` placeholders.
+    pub fn continuation_prompt_template(mut self, template: impl Into<String>) -> Self {
+        self.config.continuation_prompt_template = Some(template.into());
+        self
+    }
+
+    /// Requires the model to answer with tool calls. Whenever it returns text instead,
+    /// the next loop re-prompts it with [`TOOLS_ONLY_PROMPT`].
+    pub fn tools_only(mut self, tools_only: bool) -> Self {
+        self.config.tools_only = tools_only;
+        self
+    }
 }
 
 /// The main Swarms Agent implementation providing autonomous task execution capabilities.
@@ -783,6 +1323,39 @@ where
     /// Tool implementation instances (not serialized)
     #[serde(skip)]
     tools_impl: DashMap<String, Arc<dyn ToolDyn>>,
+    /// Middlewares applied around each run (not serialized)
+    #[serde(skip)]
+    middlewares: Vec<Arc<dyn Middleware>>,
+    /// Whether `save_state_dir` has already been verified as writable (not serialized)
+    #[serde(skip)]
+    persistence_checked: Arc<AtomicBool>,
+    /// Number of messages per task already written by incremental saves (not serialized)
+    #[serde(skip)]
+    saved_messages: Arc<DashMap<String, usize>>,
+    /// MCP server connections, shared by clones and shut down with the last one (not serialized)
+    #[serde(skip)]
+    mcp_services: Arc<McpServices>,
+    /// System prompts saved by `push_persona`, restored by `pop_persona` (not serialized)
+    #[serde(skip)]
+    persona_stack: Vec<Option<String>>,
+    /// Number of tool calls that timed out, shared by clones (not serialized)
+    #[serde(skip)]
+    timed_out_tool_calls: Arc<AtomicUsize>,
+    /// Run, loop, request and tool call counters, shared by clones (not serialized)
+    #[serde(skip)]
+    metrics: Arc<Metrics>,
+    /// Model used for the planning step instead of `model` (not serialized)
+    #[serde(skip)]
+    planning_model: Option<Arc<dyn PlanningModel>>,
+    /// Completion check set by `CompletionStrategy::Custom` (not serialized)
+    #[serde(skip)]
+    completion_check: Option<Arc<dyn Fn(&str) -> bool + Send + Sync>>,
+    /// Stop words compiled for `StopWordMatching::Regex` (not serialized)
+    #[serde(skip)]
+    stop_word_regexes: Vec<Regex>,
+    /// Storage for task state instead of files in `save_state_dir` (not serialized)
+    #[serde(skip)]
+    memory_backend: Option<Arc<dyn MemoryBackend>>,
 }
 
 impl<M> SwarmsAgent<M>
@@ -885,6 +1458,17 @@ where
             short_memory: AgentShortMemory::new(),
             tools: vec![],
             tools_impl: DashMap::new(),
+            middlewares: vec![],
+            persistence_checked: Arc::new(AtomicBool::new(false)),
+            saved_messages: Arc::new(DashMap::new()),
+            mcp_services: Arc::default(),
+            persona_stack: Vec::new(),
+            timed_out_tool_calls: Arc::new(AtomicUsize::new(0)),
+            metrics: Arc::default(),
+            planning_model: None,
+            completion_check: None,
+            stop_word_regexes: Vec::new(),
+            memory_backend: None,
         }
     }
 
@@ -939,118 +1523,113 @@ where
     ///
     /// Returns an `AgentError` if:
     /// - The LLM request fails
-    /// - Tool execution fails
     /// - No response choice is available
+    ///
+    /// Failed tool calls, including calls to unknown tools, don't fail the chat: the
+    /// error is returned as the tool's result so the model can react to it.
     pub async fn chat(
         &self,
         prompt: impl Into<String>,
         chat_history: impl Into<Vec<llm::completion::Message>>,
     ) -> Result<ChatResponse, AgentError> {
-        let chat_history = chat_history.into();
+        let mut chat_history = chat_history.into();
+        if let Some(max_messages) = self.config.max_history_messages {
+            chat_history = truncate_history_keeping_task(chat_history, max_messages);
+        }
 
-        let request = CompletionRequest {
-            prompt: llm::completion::Message::user(prompt),
-            system_prompt: self.system_prompt.clone(),
-            chat_history,
-            tools: self.tools.clone(),
-            temperature: Some(self.config.temperature),
-            max_tokens: Some(self.config.max_tokens),
+        // Sort tools by name so the request doesn't depend on registration order, which
+        // varies for tools discovered from MCP servers
+        let mut tools = self.tools.clone();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // Models without native tool calling get the tools described in the system prompt
+        let prompt_tools = self.uses_prompt_tools();
+        if prompt_tools && !self.config.prompt_tool_fallback {
+            return Err(AgentError::ToolsUnsupported(tools.len()));
+        }
+        let system_prompt = if prompt_tools {
+            let protocol = prompts::tool_protocol(&tools);
+            tools.clear();
+            Some(match &self.system_prompt {
+                Some(system_prompt) => format!("{system_prompt}\n\n{protocol}"),
+                None => protocol,
+            })
+        } else {
+            self.system_prompt.clone()
         };
 
-        let response = self.model.completion(request).await?;
+        let request = CompletionRequest::builder()
+            .prompt(llm::completion::Message::user(prompt))
+            .system_prompt(system_prompt)
+            .chat_history(chat_history)
+            .tools(tools)
+            .temperature(self.config.temperature)
+            .max_tokens(self.config.max_tokens)
+            .build();
+
+        self.metrics.llm_calls.fetch_add(1, Ordering::Relaxed);
+        let mut response = self.model.completion(request).await?;
+        self.log_reasoning(&response);
+        if prompt_tools {
+            response.choice = self.parse_prompt_tool_calls(response.choice);
+        }
 
-        let choice = response.choice.first().ok_or(AgentError::NoChoiceFound)?;
+        let mut choices = response
+            .choice
+            .iter()
+            .filter(|choice| !matches!(choice, llm::completion::AssistantContent::Thinking(_)));
+        let choice = choices.next().ok_or(AgentError::NoChoiceFound)?;
         match ToOwned::to_owned(choice) {
-            llm::completion::AssistantContent::Text(text) => Ok(ChatResponse::Text(text.text)), // <--- return Text
+            llm::completion::AssistantContent::Text(text) => Ok(ChatResponse::Text(
+                self.limit_response(text.text, "Model response"),
+            )),
             llm::completion::AssistantContent::ToolCall(tool_call) => {
                 let mut all_tool_calls = vec![tool_call.function];
-                all_tool_calls.extend(response.choice.iter().skip(1).filter_map(|choice| {
+                all_tool_calls.extend(choices.filter_map(|choice| {
                     match ToOwned::to_owned(choice) {
-                        llm::completion::AssistantContent::Text(_) => None,
                         llm::completion::AssistantContent::ToolCall(tool_call) => {
                             Some(tool_call.function)
                         },
+                        llm::completion::AssistantContent::Text(_)
+                        | llm::completion::AssistantContent::Thinking(_) => None,
                     }
                 }));
 
-                // Call tools concurrently
-                let results = Arc::new(Mutex::new(Vec::new()));
-                if self.config.concurrent_tool_call_enabled {
-                    stream::iter(all_tool_calls)
-                        .for_each_concurrent(None, |tool_call| {
-                            let results = Arc::clone(&results);
-                            async move {
-                                let tool = Arc::clone(
-                                    match self.tools_impl.get(&tool_call.name) {
-                                        Some(tool) => tool,
-                                        None => {
-                                            tracing::error!("Tool not found: {}", tool_call.name);
-                                            results.lock().await.push(ToolCallOutput {
-                                                name: tool_call.name,
-                                                args: tool_call.arguments.to_string(),
-                                                result: "Tool not found".to_owned(),
-                                            });
-                                            return;
-                                        },
-                                    }
-                                    .deref(),
-                                );
-                                let args = tool_call.arguments.to_string();
-                                // execute tool
-                                let result = match tool.call(args.clone()).await {
-                                    Ok(result) => result,
-                                    Err(e) => {
-                                        tracing::error!(
-                                            "Failed to call tool<{}>, args: {}, error: {}",
-                                            tool.name(),
-                                            args,
-                                            e
-                                        );
-                                        results.lock().await.push(ToolCallOutput {
-                                            name: tool_call.name,
-                                            args,
-                                            result: e.to_string(),
-                                        });
-                                        return;
-                                    },
-                                };
-                                results.lock().await.push(ToolCallOutput {
-                                    name: tool_call.name,
-                                    args,
-                                    result,
-                                });
-                            }
-                        })
-                        .await;
+                // Call tools concurrently, keeping the results in call order
+                let results = if self.config.concurrent_tool_call_enabled {
+                    future::join_all(
+                        all_tool_calls
+                            .into_iter()
+                            .map(|tool_call| self.execute_tool_call(tool_call)),
+                    )
+                    .await
                 } else {
+                    let mut results = Vec::with_capacity(all_tool_calls.len());
                     for tool_call in all_tool_calls {
-                        let tool = Arc::clone(
-                            self.tools_impl
-                                .get(&tool_call.name)
-                                .ok_or(AgentError::ToolNotFound(tool_call.name.clone()))?
-                                .deref(),
-                        );
-                        let args = tool_call.arguments.to_string();
-                        // execute tool
-                        let result_str = tool.call(args.clone()).await?;
-                        // collect results
-                        results.lock().await.push(ToolCallOutput {
-                            name: tool_call.name.clone(),
-                            args,
-                            result: result_str,
-                        });
+                        results.push(self.execute_tool_call(tool_call).await);
                     }
-                }
+                    results
+                };
 
-                Ok(ChatResponse::ToolCalls(
-                    Arc::clone(&results).lock().await.clone(),
-                ))
+                Ok(ChatResponse::ToolCalls(results))
+            },
+            llm::completion::AssistantContent::Thinking(_) => {
+                unreachable!("Thinking content was filtered out")
             },
         }
     }
 
     pub async fn prompt(&self, prompt: impl Into<String>) -> Result<String, AgentError> {
-        let prompt = prompt.into();
+        self.prompt_with(prompt.into(), None).await
+    }
+
+    /// Like [`prompt`](Self::prompt), but sends the request to `model` if given instead
+    /// of the agent's model.
+    async fn prompt_with(
+        &self,
+        prompt: String,
+        model: Option<&dyn PlanningModel>,
+    ) -> Result<String, AgentError> {
         let start_time = std::time::Instant::now();
 
         if self.config.verbose {
@@ -1064,23 +1643,38 @@ where
             );
         }
 
-        let request = CompletionRequest {
-            prompt: llm::completion::Message::user(prompt.clone()),
-            system_prompt: self.system_prompt.clone(),
-            chat_history: vec![],
-            tools: vec![],
-            temperature: Some(self.config.temperature),
-            max_tokens: Some(self.config.max_tokens),
-        };
-
-        let response = self.model.completion(request).await.map_err(|e| {
+        let request = CompletionRequest::builder()
+            .prompt(llm::completion::Message::user(prompt.clone()))
+            .system_prompt(self.system_prompt.clone())
+            .temperature(self.config.temperature)
+            .max_tokens(self.config.max_tokens)
+            .build();
+
+        self.metrics.llm_calls.fetch_add(1, Ordering::Relaxed);
+        let response = match model {
+            Some(model) => model.complete(request).await,
+            None => self
+                .model
+                .completion(request)
+                .await
+                .map(|response| CompletionResponse {
+                    choice: response.choice,
+                    raw_response: (),
+                }),
+        }
+        .map_err(|e| {
             if self.config.verbose {
                 log_error_ctx!(&self.config.name, &self.config.id, &e, "LLM completion");
             }
             e
         })?;
 
-        let choice = response.choice.first().ok_or(AgentError::NoChoiceFound)?;
+        self.log_reasoning(&response);
+        let choice = response
+            .choice
+            .iter()
+            .find(|choice| !matches!(choice, llm::completion::AssistantContent::Thinking(_)))
+            .ok_or(AgentError::NoChoiceFound)?;
         let result = match ToOwned::to_owned(choice) {
             llm::completion::AssistantContent::Text(text) => {
                 let duration = start_time.elapsed().as_millis() as u64;
@@ -1096,11 +1690,14 @@ where
                         text.text.chars().take(100).collect::<String>()
                     );
                 }
-                Ok(text.text)
+                Ok(self.limit_response(text.text, "Model response"))
             },
             llm::completion::AssistantContent::ToolCall(_) => {
                 unreachable!("We don't provide tools")
             },
+            llm::completion::AssistantContent::Thinking(_) => {
+                unreachable!("Thinking content was filtered out")
+            },
         };
 
         result
@@ -1123,375 +1720,1329 @@ where
         self.system_prompt.as_deref()
     }
 
-    /// Handle error in attempts
-    async fn handle_error_in_attempts(&self, task: &str, error: AgentError, attempt: u32) {
-        let err_msg = format!("Attempt {}, task: {}, failed: {}", attempt + 1, task, error);
-        tracing::error!(err_msg);
+    /// Reloads the settings that can change between runs from `SWARMS_*` environment
+    /// variables, see [`AgentConfig::reload_from_env`]. Runs already in progress keep
+    /// their settings.
+    pub fn reload_config(&mut self) -> Result<Vec<ConfigChange>, AgentError> {
+        let changes = self.config.reload_from_env()?;
+        self.stop_word_regexes = self.config.stop_word_regexes()?;
+        for change in &changes {
+            log_agent!(
+                info,
+                &self.config.name,
+                &self.config.id,
+                "Config reloaded: {}",
+                change
+            );
+        }
+        Ok(changes)
+    }
 
-        if self.config.autosave {
-            let _ = self.save_task_state(task.to_owned()).await.map_err(|e| {
-                tracing::error!(
-                    "Failed to save agent<{}> task<{}>,  state: {}",
-                    self.config.name,
-                    task,
-                    e
-                )
-            });
+    /// Number of tool calls that timed out so far, see
+    /// [`SwarmsAgentBuilder::tool_timeout`].
+    pub fn timed_out_tool_calls(&self) -> usize {
+        self.timed_out_tool_calls.load(Ordering::Relaxed)
+    }
+
+    /// Counts of the runs, loops, model requests, retries and tool calls of this agent
+    /// and its clones so far, with the total duration of its completed runs.
+    pub fn metrics(&self) -> MetricsSnapshot {
+        let metrics = &self.metrics;
+        MetricsSnapshot {
+            runs: metrics.runs.load(Ordering::Relaxed),
+            loops: metrics.loops.load(Ordering::Relaxed),
+            llm_calls: metrics.llm_calls.load(Ordering::Relaxed),
+            retries: metrics.retries.load(Ordering::Relaxed),
+            total_latency: Duration::from_millis(metrics.total_latency_ms.load(Ordering::Relaxed)),
+            tool_calls: metrics
+                .tool_calls
+                .iter()
+                .map(|entry| (entry.key().clone(), *entry.value()))
+                .collect(),
         }
     }
-}
 
-impl<M> Agent for SwarmsAgent<M>
-where
-    M: llm::Model + Clone + Send + Sync + 'static,
-    M::RawCompletionResponse: Clone + Send + Sync,
-{
-    fn run(&self, task: String) -> BoxFuture<Result<String, AgentError>> {
+    /// Runs one tool call requested by the model.
+    ///
+    /// Failures, including unknown tools, become the result reported back to the model,
+    /// which can correct its arguments or try another approach.
+    async fn execute_tool_call(&self, tool_call: ToolFunction) -> ToolCallOutput {
+        let args = tool_call.arguments.to_string();
+        let Some(tool) = self
+            .tools_impl
+            .get(&tool_call.name)
+            .map(|tool| Arc::clone(tool.deref()))
+        else {
+            tracing::error!("Tool not found: {}", tool_call.name);
+            let error = ToolError::NotFound(tool_call.name.clone());
+            return ToolCallOutput {
+                name: tool_call.name,
+                args,
+                result: format!("{error}\n{}", error.suggestion()),
+                timed_out: false,
+            };
+        };
+
+        match self.call_tool(tool.as_ref(), args.clone()).await {
+            Ok(result) => ToolCallOutput {
+                name: tool_call.name,
+                args,
+                result: self.limit_response(result, "Tool result"),
+                timed_out: false,
+            },
+            Err(e) => {
+                tracing::error!(
+                    "Failed to call tool<{}>, args: {}, error: {}",
+                    tool.name(),
+                    args,
+                    e
+                );
+                ToolCallOutput {
+                    name: tool_call.name,
+                    args,
+                    result: tool.describe_failure(&e),
+                    timed_out: matches!(e, ToolError::Timeout { .. }),
+                }
+            },
+        }
+    }
+
+    /// Calls `tool`, failing with [`ToolError::Timeout`] if it takes longer than the
+    /// configured tool timeout.
+    async fn call_tool(&self, tool: &dyn ToolDyn, args: String) -> Result<String, ToolError> {
+        *self.metrics.tool_calls.entry(tool.name()).or_default() += 1;
+        let result = match self.config.tool_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, tool.call(args))
+                .await
+                .unwrap_or_else(|_| {
+                    Err(ToolError::Timeout {
+                        name: tool.name(),
+                        timeout,
+                    })
+                }),
+            None => tool.call(args).await,
+        };
+        if matches!(result, Err(ToolError::Timeout { .. })) {
+            self.timed_out_tool_calls.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Replaces the system prompt, e.g. to continue a task "as a journalist" after
+    /// drafting it "as a lawyer".
+    ///
+    /// A `Role::System` message marking the change is added to every task in short-term
+    /// memory, so the model sees where one persona ends and the next begins when a task
+    /// is continued.
+    pub fn switch_persona(&mut self, new_system_prompt: &str) {
+        self.system_prompt = Some(new_system_prompt.to_owned());
+        self.record_persona_change(&format!("Persona switched to: {new_system_prompt}"));
+    }
+
+    /// Like [`switch_persona`](Self::switch_persona), but remembers the current system
+    /// prompt so that [`pop_persona`](Self::pop_persona) can restore it.
+    pub fn push_persona(&mut self, new_system_prompt: &str) {
+        self.persona_stack.push(self.system_prompt.take());
+        self.switch_persona(new_system_prompt);
+    }
+
+    /// Restores the system prompt that was active before the last
+    /// [`push_persona`](Self::push_persona), returning the persona that was left.
+    ///
+    /// Returns `None` and leaves the system prompt unchanged if no persona was pushed.
+    pub fn pop_persona(&mut self) -> Option<String> {
+        let previous = self.persona_stack.pop()?;
+        let left = std::mem::replace(&mut self.system_prompt, previous);
+        let note = match &self.system_prompt {
+            Some(prompt) => format!("Persona restored to: {prompt}"),
+            None => "Persona restored to the default (no system prompt)".to_owned(),
+        };
+        self.record_persona_change(&note);
+        left
+    }
+
+    fn record_persona_change(&self, note: &str) {
+        for mut conversation in self.short_memory.0.iter_mut() {
+            conversation.add(Role::System(self.config.name.clone()), note.to_owned());
+        }
+        if self.config.verbose {
+            log_agent!(info, &self.config.name, &self.config.id, "{}", note);
+        }
+    }
+
+    /// Rejects or replaces an empty task according to the agent configuration.
+    fn resolve_empty_task(&self, task: String) -> Result<String, AgentError> {
+        if !task.trim().is_empty() {
+            return Ok(task);
+        }
+
+        match &self.config.default_task {
+            Some(default_task) => {
+                if self.config.verbose {
+                    log_agent!(
+                        info,
+                        &self.config.name,
+                        &self.config.id,
+                        "Empty task received, using default task"
+                    );
+                }
+                Ok(default_task.clone())
+            },
+            None if self.config.allow_empty_task => Ok(task),
+            None => Err(AgentError::EmptyTask),
+        }
+    }
+
+    /// Logs the model's reasoning, if any, instead of adding it to the conversation.
+    fn log_reasoning<T>(&self, response: &llm::request::CompletionResponse<T>) {
+        if let Some(reasoning) = response.reasoning() {
+            log_llm!(
+                debug,
+                &self.config.name,
+                &self.config.id,
+                "Reasoning",
+                "{}",
+                reasoning
+            );
+        }
+    }
+
+    /// Truncates `text` to `max_response_chars` characters, if configured, appending a
+    /// marker with the number of characters dropped.
+    fn limit_response(&self, text: String, source: &str) -> String {
+        let Some(max_chars) = self.config.max_response_chars else {
+            return text;
+        };
+        let Some((cut, _)) = text.char_indices().nth(max_chars) else {
+            return text;
+        };
+
+        let dropped = text[cut..].chars().count();
+        log_agent!(
+            warn,
+            &self.config.name,
+            &self.config.id,
+            "{} exceeded max_response_chars ({}), truncated {} characters",
+            source,
+            max_chars,
+            dropped
+        );
+        let mut truncated = text;
+        truncated.truncate(cut);
+        truncated.push_str(&format!("… [truncated {dropped} characters]"));
+        truncated
+    }
+
+    /// Whether tools are registered but the model can't be sent them natively.
+    fn uses_prompt_tools(&self) -> bool {
+        !self.tools.is_empty() && !self.model.supports_tools()
+    }
+
+    /// Replaces a text response holding JSON tool calls, as requested by
+    /// [`prompts::tool_protocol`], with native tool call content. Responses that don't
+    /// name registered tools are kept as text.
+    fn parse_prompt_tool_calls(
+        &self,
+        choice: Vec<llm::completion::AssistantContent>,
+    ) -> Vec<llm::completion::AssistantContent> {
+        let text = choice
+            .iter()
+            .filter_map(|content| match content {
+                llm::completion::AssistantContent::Text(text) => Some(text.text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let calls = extract_json::<Vec<ToolFunction>>(&text)
+            .or_else(|| extract_json::<ToolFunction>(&text).map(|call| vec![call]));
+        match calls {
+            Some(calls)
+                if !calls.is_empty()
+                    && calls
+                        .iter()
+                        .all(|call| self.tools_impl.contains_key(&call.name)) =>
+            {
+                calls
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, call)| {
+                        llm::completion::AssistantContent::tool_call(
+                            format!("prompt_tool_call_{i}"),
+                            call.name,
+                            call.arguments,
+                        )
+                    })
+                    .collect()
+            },
+            _ => choice,
+        }
+    }
+
+    /// Truncates a document's content to about `max_document_tokens` estimated tokens,
+    /// if configured.
+    fn limit_document<'a>(&self, name: &str, content: &'a str) -> Cow<'a, str> {
+        let Some(max_tokens) = self.config.max_document_tokens else {
+            return Cow::Borrowed(content);
+        };
+        let max_chars = max_tokens.saturating_mul(4);
+        let Some((cut, _)) = content.char_indices().nth(max_chars) else {
+            return Cow::Borrowed(content);
+        };
+
+        let dropped = estimate_tokens(&content[cut..]);
+        log_agent!(
+            warn,
+            &self.config.name,
+            &self.config.id,
+            "Document {} exceeded max_document_tokens ({}), truncated about {} tokens",
+            name,
+            max_tokens,
+            dropped
+        );
+        Cow::Owned(format!(
+            "{}… [truncated about {dropped} tokens]",
+            &content[..cut]
+        ))
+    }
+
+    async fn apply_before_middlewares(&self, task: String) -> Result<String, AgentError> {
+        let mut task = task;
+        for middleware in &self.middlewares {
+            task = middleware.before(&task).await?;
+        }
+        Ok(task)
+    }
+
+    async fn apply_after_middlewares(&self, output: String) -> Result<String, AgentError> {
+        let mut output = output;
+        for middleware in &self.middlewares {
+            output = middleware.after(&output).await?;
+        }
+        Ok(output)
+    }
+
+    /// Branch this agent into an independent copy, e.g. to try two approaches to the
+    /// same conversation.
+    ///
+    /// The fork starts with a copy of the short-term memory and persistence progress, after
+    /// which the two agents diverge independently. Tools and MCP connections are shared.
+    pub fn fork(&self) -> Self {
+        Self {
+            short_memory: self.short_memory.fork(),
+            saved_messages: Arc::new((*self.saved_messages).clone()),
+            ..self.clone()
+        }
+    }
+
+    /// Copy this agent with a fresh ULID as its id, e.g. to process several tasks with the
+    /// same agent template concurrently.
+    ///
+    /// Like a [`fork`](Self::fork), the copy has its own short-term memory and persistence
+    /// progress, and it also counts its own metrics and timed out tool calls. Its autosaved
+    /// state is keyed by the new id, so copies running the same task don't overwrite each
+    /// other's state. Tools and MCP connections are shared.
+    pub fn clone_with_new_id(&self) -> Self {
+        Self {
+            config: AgentConfig {
+                id: Ulid::new().to_string(),
+                ..self.config.clone()
+            },
+            timed_out_tool_calls: Arc::default(),
+            metrics: Arc::default(),
+            ..self.fork()
+        }
+    }
+
+    /// MCP tools that were not registered because their input schema is malformed.
+    ///
+    /// Useful to diagnose third-party servers whose tools are missing from the agent.
+    pub fn skipped_mcp_tools(&self) -> Vec<SkippedMcpTool> {
+        self.mcp_services
+            .0
+            .iter()
+            .flat_map(|handle| handle.skipped.iter().cloned())
+            .collect()
+    }
+
+    /// Message statistics per role across every task this agent has run.
+    ///
+    /// See [`AgentConversation::role_statistics`](crate::structs::conversation::AgentConversation::role_statistics).
+    pub fn conversation_stats(&self) -> HashMap<String, RoleStats> {
+        let mut stats = HashMap::new();
+        for conversation in self.short_memory.0.iter() {
+            conversation.accumulate_role_statistics(&mut stats);
+        }
+        stats
+    }
+
+    /// Writes a PDF report of the conversation for `task` to `path`.
+    ///
+    /// The report lists the agent name, ID, model and export time, followed by every
+    /// message with its speaker and timestamp. Tool calls are shown as code blocks with
+    /// highlighted JSON arguments and results.
+    #[cfg(feature = "pdf-export")]
+    pub async fn export_pdf(&self, task: &str, path: &Path) -> Result<(), AgentError> {
+        let report = pdf_export::PdfReport {
+            agent_name: self.config.name.clone(),
+            agent_id: self.config.id.clone(),
+            model: short_type_name::<M>(),
+            task: task.to_owned(),
+            exported_at: chrono::Utc::now(),
+        };
+        let pdf = pdf_export::conversation_to_pdf(&report, &self.short_memory.entries_for(task))?;
+        persistence::save_to_file(pdf, path).await?;
+        Ok(())
+    }
+
+    /// Shuts down all MCP servers started by this agent and waits for them to exit.
+    ///
+    /// Stdio servers (e.g. `npx`/`uvx` processes) are killed once their connection
+    /// ends. Connections are cancelled even if clones of this agent still use them;
+    /// termination is only awaited for connections that no clone holds anymore.
+    /// Dropping the last clone of an agent cancels the connections without waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::agent::SwarmsAgentBuilder;
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    /// use swarms_rs::structs::agent::Agent;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env())
+    ///     .add_stdio_mcp_server("uvx", ["mcp-server-fetch"])
+    ///     .await
+    ///     .build();
+    ///
+    /// agent.run("Fetch https://example.com".to_string()).await?;
+    /// agent.shutdown().await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn shutdown(self) -> BoxFuture<'static, ()> {
+        let SwarmsAgent {
+            mcp_services,
+            tools_impl,
+            ..
+        } = self;
+        // Release the MCP tools' references so the services can be reclaimed below
+        drop(tools_impl);
+
         Box::pin(async move {
-            let start_time = std::time::Instant::now();
+            let handles = match Arc::try_unwrap(mcp_services) {
+                Ok(mut services) => std::mem::take(&mut services.0),
+                Err(shared) => {
+                    for handle in &shared.0 {
+                        handle.cancellation.cancel();
+                    }
+                    return;
+                },
+            };
+
+            for McpServiceHandle {
+                cancellation,
+                service,
+                ..
+            } in handles
+            {
+                cancellation.cancel();
+                if let Ok(service) = Arc::try_unwrap(service)
+                    && let Err(e) = service.waiting().await
+                {
+                    tracing::warn!("MCP service did not shut down cleanly: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Runs [`MCPTool::health_check`] for every tool registered from an MCP server.
+    ///
+    /// Returns whether each tool is healthy, keyed by tool name. Tools whose server
+    /// cannot be reached are reported as unhealthy. Useful to validate MCP servers at
+    /// startup, before the agent relies on them.
+    pub fn mcp_health_check_all(&self) -> BoxFuture<'_, HashMap<String, bool>> {
+        Box::pin(async move {
+            let checks = self.mcp_services.0.iter().flat_map(|handle| {
+                handle.tools.iter().map(move |tool| async move {
+                    let mcp_tool = MCPTool::from_server(tool.clone(), Arc::clone(&handle.service));
+                    let healthy = match mcp_tool.health_check().await {
+                        Ok(healthy) => healthy,
+                        Err(e) => {
+                            tracing::warn!(
+                                "MCP tool '{}' failed its health check: {}",
+                                tool.name,
+                                e
+                            );
+                            false
+                        },
+                    };
+                    (tool.name.to_string(), healthy)
+                })
+            });
+            future::join_all(checks).await.into_iter().collect()
+        })
+    }
+
+    /// Runs all tasks concurrently and returns their results in the same order as `tasks`.
+    ///
+    /// Unlike [`Agent::run_multiple_tasks`], which collects results as they finish and
+    /// drops failures, every task gets an entry here, so `results[i]` always belongs to
+    /// `tasks[i]`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::agent::SwarmsAgentBuilder;
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env()).build();
+    ///
+    /// let tasks = vec!["Summarize A".to_string(), "Summarize B".to_string()];
+    /// let results = agent.run_parallel_tasks_ordered(tasks).await?;
+    /// for result in results {
+    ///     println!("{}", result?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run_parallel_tasks_ordered(
+        &self,
+        tasks: Vec<String>,
+    ) -> BoxFuture<'_, Result<Vec<Result<String, AgentError>>, AgentError>> {
+        Box::pin(async move {
+            // join_all yields results in the order of its input futures
+            let results = future::join_all(tasks.into_iter().map(|task| self.run(task))).await;
+            Ok(results)
+        })
+    }
+
+    /// Runs the task, asking `approve_fn` to approve the output after every loop.
+    ///
+    /// When `approve_fn` returns `true` the run completes. Otherwise the agent is asked to
+    /// revise its previous answer in the next loop. Stop words and the task evaluator no
+    /// longer end the run on their own; only approval does. The run is still bounded by
+    /// `max_loops`, so set it high enough to allow for revisions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::agent::SwarmsAgentBuilder;
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env())
+    ///     .system_prompt("You write short product descriptions.")
+    ///     .max_loops(5)
+    ///     .build();
+    ///
+    /// let result = agent
+    ///     .run_until_approved("Describe our new headphones".to_string(), |output| {
+    ///         !output.to_lowercase().contains("guarantee")
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run_until_approved<'a, F>(
+        &'a self,
+        task: String,
+        approve_fn: F,
+    ) -> BoxFuture<'a, Result<String, AgentError>>
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'a,
+    {
+        Box::pin(async move { self.execute_task(task, None, Some(&approve_fn)).await })
+    }
+
+    /// Runs `task` and deserializes the first valid JSON object or array in the final
+    /// response, ignoring surrounding prose and Markdown code fences. JSON in the task or
+    /// in earlier turns is not considered. Middlewares' `after` hooks are applied to the
+    /// final response before the JSON is extracted.
+    ///
+    /// Returns [`AgentError::JsonExtraction`] with the final response if no JSON in it
+    /// deserializes into `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::agent::SwarmsAgentBuilder;
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env()).build();
+    ///
+    /// let titles: Vec<String> = agent
+    ///     .run_json("List three article titles as a JSON array of strings")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_json<T: DeserializeOwned>(
+        &self,
+        task: impl Into<String>,
+    ) -> Result<T, AgentError> {
+        let response = self
+            .execute_task_with_output(task.into(), None, None, RunOutput::FinalResponse)
+            .await?;
+        extract_json(&response).ok_or(AgentError::JsonExtraction(response))
+    }
+
+    /// Runs `task` with `documents` attached to it as context.
+    ///
+    /// Each document is added to the task as a delimited text block labelled with its
+    /// name and MIME type. Documents larger than `max_document_tokens`, if set, are
+    /// truncated with a marker noting how much was dropped.
+    pub async fn run_with_documents(
+        &self,
+        task: impl Into<String>,
+        documents: Vec<Document>,
+    ) -> Result<String, AgentError> {
+        let mut task = task.into();
+        if !documents.is_empty() {
+            task.push_str("\n\n### Documents ###");
+            for document in &documents {
+                let content = self.limit_document(&document.name, &document.content);
+                task.push_str(&format!(
+                    "\n\n--- {} ({}) ---\n{}\n--- end of {} ---",
+                    document.name, document.mime_type, content, document.name
+                ));
+            }
+        }
+        self.execute_task(task, None, None).await
+    }
+
+    /// Runs `task` with `context_docs` as background material.
+    ///
+    /// Unlike [`run_with_documents`](Self::run_with_documents), the task itself is left
+    /// untouched: the documents are formatted as one block and added to the
+    /// conversation just before it, as a message from `Documents`. Documents larger than
+    /// `max_document_tokens`, if set, are truncated.
+    pub fn run_with_context(
+        &self,
+        task: String,
+        context_docs: Vec<ContextDocument>,
+    ) -> BoxFuture<'_, Result<String, AgentError>> {
+        Box::pin(async move {
+            let context = (!context_docs.is_empty()).then(|| {
+                context_docs
+                    .iter()
+                    .map(|document| {
+                        let content = self.limit_document(&document.title, &document.content);
+                        format!(
+                            "--- {} ---\n{}\n--- end of {} ---",
+                            document.title, content, document.title
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n\n")
+            });
+            let context = context.map(|context| format!("### Documents ###\n\n{context}"));
+            self.execute_task(task, context, None).await
+        })
+    }
+
+    /// Chats with the agent on the terminal: reads a message from stdin, prints the
+    /// agent's reply, and repeats until the exit command (see
+    /// [`SwarmsAgentBuilder::exit_command`]) or the end of input.
+    ///
+    /// Returns the conversation, see [`run_interactive_with`](Self::run_interactive_with).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::agent::SwarmsAgentBuilder;
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env())
+    ///     .system_prompt("You are a helpful assistant.")
+    ///     .exit_command("quit")
+    ///     .build();
+    ///
+    /// agent.run_interactive().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_interactive(&self) -> Result<AgentConversation, AgentError> {
+        self.run_interactive_with(BufReader::new(tokio::io::stdin()), tokio::io::stdout())
+            .await
+    }
+
+    /// Like [`run_interactive`](Self::run_interactive), reading messages line by line
+    /// from `input` and writing replies to `output`.
+    ///
+    /// All messages form one continuous conversation, so the agent sees everything said
+    /// before. Tool calls are executed and fed back to the model for up to `max_loops`
+    /// requests per message. A failed request is reported on `output` and the message is
+    /// left out of the conversation.
+    pub async fn run_interactive_with<R, W>(
+        &self,
+        input: R,
+        mut output: W,
+    ) -> Result<AgentConversation, AgentError>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut conversation = AgentConversation::new(self.config.name.clone());
+        let mut lines = input.lines();
+        loop {
+            output.write_all(b"> ").await?;
+            output.flush().await?;
+            let Some(line) = lines.next_line().await? else {
+                break;
+            };
+            let line = line.trim();
+            if line == self.config.exit_command {
+                break;
+            }
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut history: Vec<llm::completion::Message> = (&conversation).into();
+            let mut prompt = format!("{}: {line}", self.config.user_name);
+            let mut replies = Vec::new();
+            let mut answered = false;
+            for loop_count in 0..self.config.max_loops {
+                let reply = match self.chat(&prompt, history.clone()).await {
+                    Ok(ChatResponse::Text(text)) => {
+                        answered = true;
+                        text
+                    },
+                    Ok(ChatResponse::ToolCalls(tool_calls)) => tool_calls
+                        .iter()
+                        .map(|tool_call| {
+                            format!(
+                                "[Tool name]: {}\n[Tool args]: {}\n[Tool result]: {}\n\n",
+                                tool_call.name, tool_call.args, tool_call.result
+                            )
+                        })
+                        .collect(),
+                    Err(e) => {
+                        output.write_all(format!("Error: {e}\n").as_bytes()).await?;
+                        replies.clear();
+                        break;
+                    },
+                };
+                output
+                    .write_all(format!("{}: {reply}\n", self.config.name).as_bytes())
+                    .await?;
+                if answered {
+                    replies.push(reply);
+                    break;
+                }
+
+                // Tool results are sent back with the continuation prompt, like in `run`
+                history.push(llm::completion::Message::user(prompt));
+                history.push(llm::completion::Message::assistant(format!(
+                    "{}: {reply}",
+                    self.config.name
+                )));
+                replies.push(reply);
+                let template = self
+                    .config
+                    .continuation_prompt_template
+                    .as_deref()
+                    .unwrap_or(DEFAULT_CONTINUATION_PROMPT_TEMPLATE);
+                prompt = render_loop_prompt(template, loop_count + 2, self.config.max_loops, line);
+            }
+
+            if !replies.is_empty() {
+                conversation.add(Role::User(self.config.user_name.clone()), line.to_owned());
+                for reply in replies {
+                    conversation.add(Role::Assistant(self.config.name.clone()), reply);
+                }
+            }
+        }
+        Ok(conversation)
+    }
+
+    /// The autonomous execution loop shared by `run` and `run_until_approved`.
+    async fn execute_task(
+        &self,
+        task: String,
+        context: Option<String>,
+        approve_fn: Option<&(dyn Fn(&str) -> bool + Send + Sync)>,
+    ) -> Result<String, AgentError> {
+        self.execute_task_with_output(task, context, approve_fn, RunOutput::Transcript)
+            .await
+    }
+
+    /// Runs the execution loop, returning `output` after the middlewares' `after` hooks.
+    async fn execute_task_with_output(
+        &self,
+        task: String,
+        context: Option<String>,
+        approve_fn: Option<&(dyn Fn(&str) -> bool + Send + Sync)>,
+        output: RunOutput,
+    ) -> Result<String, AgentError> {
+        let start_time = std::time::Instant::now();
+
+        let task = self.resolve_empty_task(task)?;
+        let task = self.apply_before_middlewares(task).await?;
+
+        // Fail fast instead of discovering an unwritable state dir after a long run
+        if self.config.autosave {
+            self.check_persistence_setup().await?;
+        }
+
+        // Fail fast too instead of retrying requests the model can never accept
+        if self.uses_prompt_tools() && !self.config.prompt_tool_fallback {
+            return Err(AgentError::ToolsUnsupported(self.tools.len()));
+        }
+
+        if self.config.verbose {
+            log_task!(
+                info,
+                &self.config.name,
+                &self.config.id,
+                &task,
+                "Task initializing - Agent starting autonomous execution loop"
+            );
+        }
+
+        if let Some(context) = context {
+            self.short_memory.add(
+                &task,
+                &self.config.name,
+                Role::User("Documents".to_owned()),
+                context,
+            );
+        }
+        self.short_memory.add(
+            &task,
+            &self.config.name,
+            Role::User(self.config.user_name.clone()),
+            &task,
+        );
+
+        if self.config.verbose {
+            log_memory!(
+                debug,
+                &self.config.name,
+                &self.config.id,
+                "Save Task",
+                "Added task to short-term memory"
+            );
+        }
 
+        // Plan
+        if self.config.plan_enabled {
             if self.config.verbose {
-                log_task!(
+                log_agent!(
                     info,
                     &self.config.name,
                     &self.config.id,
-                    &task,
-                    "Task initializing - Agent starting autonomous execution loop"
+                    "Planning phase initiated"
                 );
             }
+            self.plan(task.clone()).await?;
+        }
 
-            self.short_memory.add(
-                &task,
-                &self.config.name,
-                Role::User(self.config.user_name.clone()),
-                &task,
-            );
+        // Query long term memory
+        // if self.long_term_memory.is_some() {
+        //     self.query_long_term_memory(task.clone()).await?;
+        // }
 
+        // Save state
+        if self.config.autosave {
             if self.config.verbose {
                 log_memory!(
                     debug,
                     &self.config.name,
                     &self.config.id,
-                    "Save Task",
-                    "Added task to short-term memory"
+                    "Autosave",
+                    "Saving agent state to disk"
                 );
             }
+            self.save_task_state(task.clone()).await?;
+        }
 
-            // Plan
-            if self.config.plan_enabled {
-                if self.config.verbose {
-                    log_agent!(
-                        info,
-                        &self.config.name,
-                        &self.config.id,
-                        "Planning phase initiated"
-                    );
-                }
-                self.plan(task.clone()).await?;
-            }
+        // Run agent loop
+        let mut last_response_text = String::new();
+        let mut task_complete = false;
+        let mut was_prev_call_task_evaluator = false;
+        let mut awaiting_revision = false;
+        let mut awaiting_tool_call = false;
+        let mut timed_out_tools: Vec<String> = Vec::new();
+        let mut recent_responses = VecDeque::new();
 
-            // Query long term memory
-            // if self.long_term_memory.is_some() {
-            //     self.query_long_term_memory(task.clone()).await?;
-            // }
+        if self.config.verbose {
+            log_agent!(
+                info,
+                &self.config.name,
+                &self.config.id,
+                "Starting autonomous execution loop - Max loops: {}",
+                self.config.max_loops
+            );
+        }
 
-            // Save state
-            if self.config.autosave {
+        for loop_count in 0..self.config.max_loops {
+            if task_complete {
                 if self.config.verbose {
-                    log_memory!(
-                        debug,
+                    log_agent!(
+                        info,
                         &self.config.name,
                         &self.config.id,
-                        "Autosave",
-                        "Saving agent state to disk"
+                        "Task completed early at loop {} of {}",
+                        loop_count,
+                        self.config.max_loops
                     );
                 }
-                self.save_task_state(task.clone()).await?;
+                break;
             }
 
-            // Run agent loop
-            let mut last_response_text = String::new();
-            let mut task_complete = false;
-            let mut was_prev_call_task_evaluator = false;
-
+            self.metrics.loops.fetch_add(1, Ordering::Relaxed);
             if self.config.verbose {
                 log_agent!(
-                    info,
+                    debug,
                     &self.config.name,
                     &self.config.id,
-                    "Starting autonomous execution loop - Max loops: {}",
+                    "Starting loop iteration {} of {}",
+                    loop_count + 1,
                     self.config.max_loops
                 );
             }
 
-            for loop_count in 0..self.config.max_loops {
-                if task_complete {
-                    if self.config.verbose {
-                        log_agent!(
-                            info,
-                            &self.config.name,
-                            &self.config.id,
-                            "Task completed early at loop {} of {}",
-                            loop_count,
-                            self.config.max_loops
-                        );
-                    }
-                    break;
-                }
-
-                if self.config.verbose {
-                    log_agent!(
-                        debug,
-                        &self.config.name,
-                        &self.config.id,
-                        "Starting loop iteration {} of {}",
-                        loop_count + 1,
-                        self.config.max_loops
-                    );
-                }
-
-                let current_prompt: String;
+            let refresh_due = self
+                .config
+                .system_prompt_refresh_interval
+                .is_some_and(|interval| loop_count > 0 && loop_count.is_multiple_of(interval));
+            if let Some(system_prompt) = self.system_prompt.as_ref().filter(|_| refresh_due) {
+                self.short_memory.add_system_reminder(
+                    &task,
+                    &self.config.name,
+                    format!("{SYSTEM_PROMPT_REFRESH_PREFIX}\n{system_prompt}"),
+                    loop_count,
+                );
+            }
 
-                if was_prev_call_task_evaluator {
-                    current_prompt = format!(
-                        "You previously called task_evaluator and indicated the task was not complete. The required next step or context provided was: '{}'. \
-                        Focus ONLY on addressing this context. DO NOT call task_evaluator again in this turn. Proceed with the task based on the context.",
-                        last_response_text // last_response is the context provided by task_evaluator
-                    );
+            let current_prompt: String;
+
+            if awaiting_tool_call {
+                current_prompt = TOOLS_ONLY_PROMPT.to_owned();
+                awaiting_tool_call = false;
+            } else if !timed_out_tools.is_empty() {
+                current_prompt = timed_out_tools
+                    .drain(..)
+                    .map(|name| format!("Tool {name} timed out. Consider an alternative approach."))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+            } else if awaiting_revision {
+                current_prompt =
+                    "Please revise your previous answer. It was not approved.".to_owned();
+                awaiting_revision = false;
+            } else if was_prev_call_task_evaluator {
+                current_prompt = format!(
+                    "You previously called task_evaluator and indicated the task was not complete. The required next step or context provided was: '{}'. \
+                    Focus ONLY on addressing this context. DO NOT call task_evaluator again in this turn. Proceed with the task based on the context.",
+                    last_response_text // last_response is the context provided by task_evaluator
+                );
 
-                    was_prev_call_task_evaluator = false;
-                } else if loop_count > 0 {
-                    current_prompt = format!(
-                        "Now, you are in loop {} of {}, The dialogue will terminate upon reaching maximum iteration count. You must:
-                         - Complete the user's task before termination
-                         - Optimize loop efficiency
-                         - Minimize resource consumption through minimal iterations
+                was_prev_call_task_evaluator = false;
+            } else if loop_count > 0 {
+                let template = self
+                    .config
+                    .continuation_prompt_template
+                    .as_deref()
+                    .unwrap_or(DEFAULT_CONTINUATION_PROMPT_TEMPLATE);
+                current_prompt =
+                    render_loop_prompt(template, loop_count + 1, self.config.max_loops, &task);
+            } else if let Some(template) = &self.config.first_loop_prompt {
+                current_prompt = render_loop_prompt(template, 1, self.config.max_loops, &task);
+            } else {
+                // first loop
+                // task is already in short_memory, short_memory will be passed to llm
+                // empty prompt should be ignored by LLM provider
+                current_prompt = "".to_owned();
+            }
 
-                        You should consider to use tools if they can help, but only if they are relevant to the task and are necessary for the task.
-                        origin task:\n{}",
-                        loop_count + 1,
-                        self.config.max_loops,
-                        task
-                    )
-                } else {
-                    // first loop
-                    // task is already in short_memory, short_memory will be passed to llm
-                    // empty prompt should be ignored by LLM provider
-                    current_prompt = "".to_owned();
+            let mut success = false;
+            // let task_prompt = self.short_memory.0.get(&task).unwrap().to_string(); // Safety: task is in short_memory
+            for attempt in 0..self.config.retry_attempts {
+                if success {
+                    break;
                 }
 
-                let mut success = false;
-                // let task_prompt = self.short_memory.0.get(&task).unwrap().to_string(); // Safety: task is in short_memory
-                for attempt in 0..self.config.retry_attempts {
-                    if success {
-                        break;
-                    }
-
-                    // if self.long_term_memory.is_some() && self.config.rag_every_loop {
-                    //     // FIXME: if RAG success, but then LLM fails, then RAG is not removed and maybe causes issues
-                    //     if let Err(e) = self.query_long_term_memory(task_prompt.clone()).await {
-                    //         self.handle_error_in_attempts(&task, e, attempt).await;
-                    //         continue;
-                    //     };
-                    // }
-
-                    // Generate response using LLM
-                    let history = self.short_memory.0.get(&task).unwrap(); // Safety: task is in short_memory
-                    let current_chat_response =
-                        match self.chat(&current_prompt, history.deref()).await {
-                            Ok(response) => response,
-                            Err(e) => {
-                                self.handle_error_in_attempts(&task, e, attempt).await;
+                // if self.long_term_memory.is_some() && self.config.rag_every_loop {
+                //     // FIXME: if RAG success, but then LLM fails, then RAG is not removed and maybe causes issues
+                //     if let Err(e) = self.query_long_term_memory(task_prompt.clone()).await {
+                //         self.handle_error_in_attempts(&task, e, attempt).await;
+                //         continue;
+                //     };
+                // }
+
+                // Generate response using LLM
+                // Convert the history up front so the DashMap guard isn't held across the
+                // await, which would deadlock concurrent runs of other tasks on this agent
+                let history: Vec<llm::completion::Message> =
+                    self.short_memory.0.get(&task).unwrap().deref().into(); // Safety: task is in short_memory
+                let current_chat_response = match self.chat(&current_prompt, history).await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        self.handle_error_in_attempts(&task, e, attempt).await;
+                        continue;
+                    },
+                };
+                // handle ChatResponse
+                let mut assistant_memory_content = String::new();
+                let mut is_task_evaluator_called = false;
+                match current_chat_response {
+                    ChatResponse::Text(text) => {
+                        // Pretty print agent output
+                        self.print_agent_output(&text);
+
+                        last_response_text = text.clone();
+                        assistant_memory_content = text;
+                        awaiting_tool_call = self.config.tools_only;
+                    },
+                    ChatResponse::ToolCalls(tool_calls) => {
+                        timed_out_tools = tool_calls
+                            .iter()
+                            .filter(|tool_call| tool_call.timed_out)
+                            .map(|tool_call| tool_call.name.clone())
+                            .collect();
+                        // Memory records every call of the turn, in order. The response is
+                        // the output of the regular tools, unless the task evaluator asks
+                        // for more work, in which case its context drives the next prompt.
+                        let mut tool_results = String::new();
+                        let mut evaluator_context = None;
+                        for tool_call in tool_calls {
+                            // Pretty print tool execution
+                            self.print_tool_execution(
+                                &tool_call.name,
+                                &tool_call.args,
+                                &tool_call.result,
+                            );
+
+                            let formatted = format!(
+                                "[Tool name]: {}\n[Tool args]: {}\n[Tool result]: {}\n\n",
+                                tool_call.name, tool_call.args, tool_call.result
+                            );
+                            assistant_memory_content.push_str(&formatted);
+                            if tool_call.name != ToolDyn::name(&TaskEvaluator) {
+                                tool_results.push_str(&formatted);
                                 continue;
-                            },
-                        };
-                    // needed to drop the lock
-                    // if use:
-                    // let history = (&(*self.short_memory.0.get(&task).unwrap())).into();
-                    // we don't need to drop the lock, because the lock is owned by temporary variable
-                    drop(history);
-
-                    // handle ChatResponse
-                    let mut assistant_memory_content = String::new();
-                    let mut is_task_evaluator_called = false;
-                    match current_chat_response {
-                        ChatResponse::Text(text) => {
-                            // Pretty print agent output
-                            self.print_agent_output(&text);
-
-                            last_response_text = text.clone();
-                            assistant_memory_content = text;
-                        },
-                        ChatResponse::ToolCalls(tool_calls) => {
-                            let mut formatted_tool_results = String::new();
-                            for tool_call in tool_calls {
-                                // Pretty print tool execution
-                                self.print_tool_execution(
-                                    &tool_call.name,
-                                    &tool_call.args,
-                                    &tool_call.result,
-                                );
-
-                                let formatted = format!(
-                                    "[Tool name]: {}\n[Tool args]: {}\n[Tool result]: {}\n\n",
-                                    tool_call.name, tool_call.args, tool_call.result
-                                );
-                                formatted_tool_results.push_str(&formatted);
-                                if tool_call.name == ToolDyn::name(&TaskEvaluator) {
-                                    is_task_evaluator_called = true;
-                                    match serde_json::from_str::<TaskStatus>(&tool_call.result) {
-                                        Ok(task_status) => {
-                                            tracing::info!(
-                                                "Task evaluator tool called, task status: {:#?}",
-                                                task_status,
-                                            );
-
-                                            match task_status {
-                                                TaskStatus::Complete => {
-                                                    task_complete = true;
-                                                    // Task is complete
-                                                    // This may be a bit redundant, but it's here for clarity
-                                                    // last_response_text = format!(
-                                                    //     "Task marked as complete by task_evaluator. Result: {}",
-                                                    //     tool_call.result
-                                                    // );
-                                                    assistant_memory_content = formatted;
-                                                    // Store the final tool call in memory
-                                                },
-                                                TaskStatus::Incomplete { context } => {
-                                                    task_complete = false;
-                                                    // If not complete, store the context for the next loop's prompt
-                                                    last_response_text = context;
-                                                    // Keep the raw tool result for memory
-                                                    assistant_memory_content = formatted;
-                                                },
-                                            }
-                                        },
-                                        Err(e) => {
-                                            tracing::error!(
-                                                "Failed to parse task status from task_evaluator: {}. Raw result: {}",
-                                                e,
-                                                tool_call.result
-                                            );
+                            }
 
+                            is_task_evaluator_called = true;
+                            match serde_json::from_str::<TaskStatus>(&tool_call.result) {
+                                Ok(task_status) => {
+                                    tracing::info!(
+                                        "Task evaluator tool called, task status: {:#?}",
+                                        task_status,
+                                    );
+
+                                    match task_status {
+                                        TaskStatus::Complete => {
+                                            task_complete = true;
+                                            evaluator_context = None;
+                                        },
+                                        TaskStatus::Incomplete { context } => {
                                             task_complete = false;
-
-                                            last_response_text = format!(
-                                                "Error parsing task_evaluator result. Raw output: {}",
-                                                tool_call.result
-                                            );
-                                            assistant_memory_content = formatted;
-                                            // Store the problematic call
+                                            evaluator_context = Some(context);
                                         },
                                     }
-                                } else {
-                                    // Handle other tool calls if necessary, for now just format them
-                                    // If this is the *only* response part, update last_response_text
-                                    if formatted_tool_results.len() == formatted.len() {
-                                        // Check if it's the first/only tool result string being built
-                                        last_response_text = formatted_tool_results.clone();
-                                    } else {
-                                        // Append to existing text/tool results for the final response string
-                                        last_response_text.push_str(&formatted);
-                                    }
-                                }
-                            }
-                            // If multiple tools were called, or if task_evaluator wasn't the only one,
-                            // ensure assistant_memory_content reflects all calls.
-                            if assistant_memory_content.is_empty() || !is_task_evaluator_called {
-                                assistant_memory_content = formatted_tool_results.clone();
-                                // Update last_response_text if it wasn't set by task_evaluator
-                                if !is_task_evaluator_called {
-                                    last_response_text = formatted_tool_results;
-                                }
+                                },
+                                Err(e) => {
+                                    tracing::error!(
+                                        "Failed to parse task status from task_evaluator: {}. Raw result: {}",
+                                        e,
+                                        tool_call.result
+                                    );
+
+                                    task_complete = false;
+                                    evaluator_context = Some(format!(
+                                        "Error parsing task_evaluator result. Raw output: {}",
+                                        tool_call.result
+                                    ));
+                                },
                             }
-                        },
-                    }
+                        }
+
+                        // A turn with only a `Complete` verdict keeps the previous response
+                        if let Some(context) = evaluator_context {
+                            last_response_text = context;
+                        } else if !tool_results.is_empty() {
+                            last_response_text = tool_results;
+                        }
+                    },
+                }
 
-                    // Update the flag for the *next* iteration based on *this* iteration's call
-                    was_prev_call_task_evaluator = is_task_evaluator_called && !task_complete;
+                // Update the flag for the *next* iteration based on *this* iteration's call
+                was_prev_call_task_evaluator = is_task_evaluator_called && !task_complete;
 
-                    self.short_memory.add(
-                        &task,
-                        &self.config.name,
-                        Role::Assistant(self.config.name.to_owned()),
-                        assistant_memory_content.clone(), // Add the text or formatted tool calls
-                    );
+                self.short_memory.add_in_loop(
+                    &task,
+                    &self.config.name,
+                    Role::Assistant(self.config.name.to_owned()),
+                    assistant_memory_content.clone(), // Add the text or formatted tool calls
+                    loop_count,
+                );
 
-                    success = true;
-                }
+                success = true;
+            }
+
+            if !success {
+                // Exit the loop if all retry failed
+                break;
+            }
+
+            // Save state in each loop
+            if self.config.autosave {
+                self.save_task_state(task.clone()).await?;
+            }
 
-                if !success {
-                    // Exit the loop if all retry failed
+            if self.config.stagnation_window > 0 {
+                let window = self.config.stagnation_window.max(2);
+                recent_responses.push_back(normalize_response(&last_response_text));
+                if recent_responses.len() > window {
+                    recent_responses.pop_front();
+                }
+                if recent_responses.len() == window
+                    && recent_responses.iter().all(|r| *r == recent_responses[0])
+                {
+                    log_agent!(
+                        warn,
+                        &self.config.name,
+                        &self.config.id,
+                        "Stopping at loop {}: the last {} responses were identical",
+                        loop_count + 1,
+                        window
+                    );
                     break;
                 }
+            }
 
-                // Save state in each loop
-                if self.config.autosave {
-                    self.save_task_state(task.clone()).await?;
-                }
+            // A text answer never completes a tools-only run, and a timed out tool call
+            // leaves the model to find another way
+            if awaiting_tool_call || !timed_out_tools.is_empty() {
+                continue;
+            }
 
-                if self.is_response_complete(last_response_text.clone()) {
+            // With an approval gate, only approval ends the run early
+            if let Some(approve_fn) = approve_fn {
+                if approve_fn(&last_response_text) {
                     if self.config.verbose {
                         log_agent!(
                             info,
                             &self.config.name,
                             &self.config.id,
-                            "Response marked as complete by completion checker"
+                            "Response approved at loop {}",
+                            loop_count + 1
                         );
                     }
                     break;
                 }
-
-                // TODO: Loop interval, maybe add a sleep here
+                task_complete = false;
+                awaiting_revision = true;
+                continue;
             }
 
-            // TODO: Apply the cleaning function to the responses
-            // clean and add to short memory. role: Assistant(Output Cleaner)
-
-            // Save state
-            if self.config.autosave {
+            if self.is_response_complete(last_response_text.clone()) {
                 if self.config.verbose {
-                    log_memory!(
-                        debug,
+                    log_agent!(
+                        info,
                         &self.config.name,
                         &self.config.id,
-                        "Final Autosave",
-                        "Saving final agent state after task completion"
+                        "Response marked as complete by completion checker"
                     );
                 }
-                self.save_task_state(task.clone()).await?;
+                break;
             }
 
-            let total_duration = start_time.elapsed().as_millis() as u64;
-            if self.config.verbose {
-                log_perf!(info, "Agent", "total_execution_time", total_duration, "ms");
+            // TODO: Loop interval, maybe add a sleep here
+        }
 
-                log_task!(
-                    info,
+        // TODO: Apply the cleaning function to the responses
+        // clean and add to short memory. role: Assistant(Output Cleaner)
+
+        // Save state
+        if self.config.autosave {
+            if self.config.verbose {
+                log_memory!(
+                    debug,
                     &self.config.name,
                     &self.config.id,
-                    &task,
-                    "Task execution completed successfully in {}ms",
-                    total_duration
+                    "Final Autosave",
+                    "Saving final agent state after task completion"
                 );
             }
+            self.save_task_state(task.clone()).await?;
+        }
+
+        let total_duration = start_time.elapsed().as_millis() as u64;
+        self.metrics.runs.fetch_add(1, Ordering::Relaxed);
+        self.metrics
+            .total_latency_ms
+            .fetch_add(total_duration, Ordering::Relaxed);
+        if self.config.verbose {
+            log_perf!(info, "Agent", "total_execution_time", total_duration, "ms");
+
+            log_task!(
+                info,
+                &self.config.name,
+                &self.config.id,
+                &task,
+                "Task execution completed successfully in {}ms",
+                total_duration
+            );
+        }
 
-            // TODO: Handle artifacts
+        // TODO: Handle artifacts
 
-            // TODO: More flexible output types, e.g. JSON, CSV, etc.
-            let final_result = self
+        // TODO: More flexible output types, e.g. JSON, CSV, etc.
+        let final_result = match output {
+            RunOutput::Transcript => self
                 .short_memory
                 .0
                 .get(&task)
                 .expect("Task should exist in short memory")
-                .to_string();
+                .to_string(),
+            RunOutput::FinalResponse => last_response_text,
+        };
+        let final_result = self.apply_after_middlewares(final_result).await?;
 
-            // Pretty print the final result
-            self.print_task_complete(&task, &final_result);
+        // Pretty print the final result
+        self.print_task_complete(&task, &final_result);
 
-            Ok(final_result)
-        })
+        Ok(final_result)
+    }
+
+    /// Verifies once per agent that `save_state_dir` can be created and written to.
+    ///
+    /// Returns [`AgentError::PersistenceSetup`] with the offending path otherwise.
+    /// Does nothing when no `save_state_dir` is configured or a memory backend is set.
+    async fn check_persistence_setup(&self) -> Result<(), AgentError> {
+        if self.memory_backend.is_some() {
+            return Ok(());
+        }
+        let Some(dir) = self.config.save_state_dir.as_deref() else {
+            return Ok(());
+        };
+        if self.persistence_checked.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        persistence::ensure_writable_dir(dir)
+            .await
+            .map_err(|source| AgentError::PersistenceSetup {
+                path: dir.to_owned(),
+                source,
+            })?;
+        self.persistence_checked.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Appends the messages of `task` that are not yet saved to `key` in `backend`, one
+    /// JSON object per line.
+    ///
+    /// The value is rewritten from scratch on the first save of a task in this agent, and
+    /// whenever the history has shrunk (e.g. after `max_messages` eviction).
+    async fn append_task_state(
+        &self,
+        task: &str,
+        backend: &dyn MemoryBackend,
+        key: &str,
+    ) -> Result<(), AgentError> {
+        let (lines, rewrite, total) = {
+            let Some(conversation) = self.short_memory.0.get(task) else {
+                return Ok(());
+            };
+            let history = &conversation.history;
+            let written = self.saved_messages.get(task).map(|count| *count);
+            let (start, rewrite) = match written {
+                Some(written) if written <= history.len() => (written, false),
+                _ => (0, true),
+            };
+
+            let mut lines = String::new();
+            for message in &history[start..] {
+                lines.push_str(&serde_json::to_string(message)?);
+                lines.push('\n');
+            }
+            (lines, rewrite, history.len())
+        };
+
+        if rewrite {
+            backend.save(key, lines.into_bytes()).await?;
+        } else if !lines.is_empty() {
+            backend.append(key, lines.into_bytes()).await?;
+        }
+        self.saved_messages.insert(task.to_owned(), total);
+        Ok(())
+    }
+
+    /// Handle error in attempts
+    async fn handle_error_in_attempts(&self, task: &str, error: AgentError, attempt: u32) {
+        let rate_limit_delay = match &error {
+            AgentError::CompletionError(llm::CompletionError::RateLimited { retry_after }) => Some(
+                retry_after
+                    .unwrap_or(DEFAULT_RATE_LIMIT_DELAY)
+                    .min(MAX_RATE_LIMIT_DELAY),
+            ),
+            _ => None,
+        };
+        let err_msg = format!("Attempt {}, task: {}, failed: {}", attempt + 1, task, error);
+        tracing::error!(err_msg);
+
+        if self.config.autosave {
+            let _ = self.save_task_state(task.to_owned()).await.map_err(|e| {
+                tracing::error!(
+                    "Failed to save agent<{}> task<{}>,  state: {}",
+                    self.config.name,
+                    task,
+                    e
+                )
+            });
+        }
+
+        if attempt + 1 >= self.config.retry_attempts {
+            return;
+        }
+        self.metrics.retries.fetch_add(1, Ordering::Relaxed);
+
+        // Back off before the next attempt, at least as long as the provider's Retry-After
+        let backoff = self
+            .config
+            .retry_base_delay
+            .map(|base| retry_backoff(base, attempt));
+        match (rate_limit_delay, backoff) {
+            (Some(delay), backoff) => {
+                let delay = backoff.map_or(delay, |backoff| delay.max(backoff));
+                tracing::warn!("Rate limited, retrying in {:?}", delay);
+                tokio::time::sleep(delay).await;
+            },
+            (None, Some(delay)) => {
+                tracing::warn!("Retrying in {:?}", delay);
+                tokio::time::sleep(delay).await;
+            },
+            (None, None) => {},
+        }
+    }
+}
+
+/// Exponential backoff with jitter: a random delay between half and all of
+/// `base * 2^attempt`, capped at [`MAX_RETRY_DELAY`].
+fn retry_backoff(base: Duration, attempt: u32) -> Duration {
+    let delay = base
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_RETRY_DELAY);
+    // RandomState is randomly seeded, which is all the randomness jitter needs
+    let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+    delay.mul_f64(0.5 + random / 2.0)
+}
+
+impl<M> Agent for SwarmsAgent<M>
+where
+    M: llm::Model + Clone + Send + Sync + 'static,
+    M::RawCompletionResponse: Clone + Send + Sync,
+{
+    fn run(&self, task: String) -> BoxFuture<Result<String, AgentError>> {
+        Box::pin(self.execute_task(task, None, None))
     }
 
     fn run_multiple_tasks(
@@ -1535,7 +3086,9 @@ where
         Box::pin(async move {
             if let Some(planning_prompt) = &self.config.planning_prompt {
                 let planning_prompt = format!("{} {}", planning_prompt, task);
-                let plan = self.prompt(planning_prompt).await?;
+                let plan = self
+                    .prompt_with(planning_prompt, self.planning_model.as_deref())
+                    .await?;
                 tracing::debug!("Plan: {}", plan);
                 // Add plan to memory
                 self.short_memory.add(
@@ -1554,35 +3107,46 @@ where
     }
 
     fn save_task_state(&self, task: String) -> BoxFuture<Result<(), AgentError>> {
-        let mut hasher = XxHash64::default();
-        task.hash(&mut hasher);
-        let task_hash = hasher.finish();
-        let task_hash = format!("{:x}", task_hash & 0xFFFFFFFF); // lower 32 bits of the hash
-
         Box::pin(async move {
-            let save_state_dir = self.config.save_state_dir.clone();
-            if let Some(save_state_dir) = save_state_dir {
-                let save_state_dir = Path::new(&save_state_dir);
-                if !save_state_dir.exists() {
-                    tokio::fs::create_dir_all(save_state_dir).await?;
-                }
-
-                let path = save_state_dir
-                    .join(format!("{}_{}", self.name(), task_hash))
-                    .with_extension("json");
+            self.check_persistence_setup().await?;
 
+            let backend: Arc<dyn MemoryBackend> =
+                match (&self.memory_backend, &self.config.save_state_dir) {
+                    (Some(backend), _) => Arc::clone(backend),
+                    (None, Some(save_state_dir)) => {
+                        Arc::new(FileSystemBackend::new(save_state_dir))
+                    },
+                    (None, None) => return Ok(()),
+                };
+
+            let stem = persistence::task_state_file_stem(&self.name(), &self.id(), &task);
+            if self.config.incremental_save {
+                self.append_task_state(&task, backend.as_ref(), &format!("{stem}.ndjson"))
+                    .await?;
+            } else {
                 let json = serde_json::to_string_pretty(&self.short_memory.0.get(&task).unwrap())?; // TODO: Safety?
-                persistence::save_to_file(&json, path).await?;
+                backend
+                    .save(&format!("{stem}.json"), json.into_bytes())
+                    .await?;
             }
             Ok(())
         })
     }
 
     fn is_response_complete(&self, response: String) -> bool {
+        if let Some(check) = &self.completion_check {
+            return check(&response);
+        }
+        if self.config.stop_word_matching == StopWordMatching::Regex {
+            return self
+                .stop_word_regexes
+                .iter()
+                .any(|regex| regex.is_match(&response));
+        }
         self.config
             .stop_words
             .iter()
-            .any(|word| response.contains(word))
+            .any(|word| self.config.stop_word_matching.matches(&response, word))
     }
 
     fn id(&self) -> String {
@@ -1602,6 +3166,218 @@ where
     }
 }
 
+type McpService = RunningService<RoleClient, Box<dyn DynService<RoleClient>>>;
+
+/// A running MCP server connection together with the token that stops it.
+struct McpServiceHandle {
+    cancellation: CancellationToken,
+    service: Arc<McpService>,
+    /// Tools the server offered when it was added
+    tools: Vec<rmcp::model::Tool>,
+    /// Tools the server offered that were rejected because of a malformed input schema
+    skipped: Vec<SkippedMcpTool>,
+}
+
+/// A [`Model`](llm::Model) of any type, with the raw provider response dropped, so an
+/// agent can hold it next to its main model.
+trait PlanningModel: Send + Sync {
+    fn complete(
+        &self,
+        request: CompletionRequest,
+    ) -> BoxFuture<'_, Result<CompletionResponse<()>, llm::CompletionError>>;
+}
+
+impl<P> PlanningModel for P
+where
+    P: llm::Model + Send + Sync,
+    P::RawCompletionResponse: Send,
+{
+    fn complete(
+        &self,
+        request: CompletionRequest,
+    ) -> BoxFuture<'_, Result<CompletionResponse<()>, llm::CompletionError>> {
+        let completion = self.completion(request);
+        Box::pin(async move {
+            completion.await.map(|response| CompletionResponse {
+                choice: response.choice,
+                raw_response: (),
+            })
+        })
+    }
+}
+
+/// Lowercases `response` and collapses its whitespace, so that responses differing only
+/// in formatting compare equal.
+fn normalize_response(response: &str) -> String {
+    response
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Cuts `history` to `max_messages` with
+/// [`truncate_history`](llm::completion::truncate_history), always keeping the user
+/// messages before the first reply. Those hold the task and any documents attached to
+/// it, without which the model would no longer know what it was asked to do.
+fn truncate_history_keeping_task(
+    mut history: Vec<llm::completion::Message>,
+    max_messages: usize,
+) -> Vec<llm::completion::Message> {
+    let task_len = history
+        .iter()
+        .position(|message| match message {
+            llm::completion::Message::User { content } => content
+                .iter()
+                .any(|content| matches!(content, llm::completion::UserContent::ToolResult(_))),
+            llm::completion::Message::Assistant { .. } => true,
+        })
+        .unwrap_or(history.len());
+    let rest = history.split_off(task_len);
+    history.extend(llm::completion::truncate_history(
+        rest,
+        max_messages.saturating_sub(task_len),
+    ));
+    history
+}
+
+/// The name of `T` without module paths, e.g. `CachingModel<OpenAI>`.
+#[cfg(feature = "pdf-export")]
+fn short_type_name<T: ?Sized>() -> String {
+    std::any::type_name::<T>()
+        .split_inclusive(['<', '>', ',', ' '])
+        .map(|part| part.rsplit("::").next().unwrap_or(part))
+        .collect()
+}
+
+/// A titled document given to an agent as background material, see
+/// [`SwarmsAgent::run_with_context`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextDocument {
+    pub title: String,
+    pub content: String,
+}
+
+impl ContextDocument {
+    pub fn new(title: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            content: content.into(),
+        }
+    }
+}
+
+/// A file or other text passed to an agent alongside a task, see
+/// [`SwarmsAgent::run_with_documents`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Document {
+    /// Name shown to the model, usually the file name
+    pub name: String,
+    /// Text content of the document
+    pub content: String,
+    /// MIME type, e.g. `text/markdown`
+    pub mime_type: String,
+}
+
+impl Document {
+    pub fn new(
+        name: impl Into<String>,
+        content: impl Into<String>,
+        mime_type: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            content: content.into(),
+            mime_type: mime_type.into(),
+        }
+    }
+
+    /// Reads a text file, guessing its MIME type from the extension.
+    pub async fn from_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let content = tokio::fs::read_to_string(path).await?;
+        let mime_type = match path.extension().and_then(OsStr::to_str) {
+            Some("md" | "markdown") => "text/markdown",
+            Some("json") => "application/json",
+            Some("csv") => "text/csv",
+            Some("html" | "htm") => "text/html",
+            Some("xml") => "application/xml",
+            Some("yaml" | "yml") => "application/yaml",
+            _ => "text/plain",
+        };
+        let name = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .unwrap_or_default()
+            .to_owned();
+        Ok(Self::new(name, content, mime_type))
+    }
+}
+
+/// Which text a run returns, after the middlewares' `after` hooks.
+#[derive(Clone, Copy)]
+enum RunOutput {
+    /// The transcript of the whole task, as returned by `run`
+    Transcript,
+    /// The final response on its own, as parsed by `run_json`
+    FinalResponse,
+}
+
+/// Counters an agent accumulates across runs, shared by its clones. See
+/// [`SwarmsAgent::metrics`].
+#[derive(Debug, Default)]
+struct Metrics {
+    runs: AtomicU64,
+    loops: AtomicU64,
+    llm_calls: AtomicU64,
+    retries: AtomicU64,
+    total_latency_ms: AtomicU64,
+    tool_calls: DashMap<String, u64>,
+}
+
+/// A copy of an agent's metrics at one point in time, see [`SwarmsAgent::metrics`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    /// Runs that completed successfully
+    pub runs: u64,
+    /// Loops started across all runs
+    pub loops: u64,
+    /// Requests sent to the model, including failed and planning requests
+    pub llm_calls: u64,
+    /// Attempts retried after a failed request
+    pub retries: u64,
+    /// Total duration of the completed runs
+    pub total_latency: Duration,
+    /// Calls per tool name, failed calls included
+    pub tool_calls: BTreeMap<String, u64>,
+}
+
+/// An MCP tool that was not registered because its input schema is malformed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkippedMcpTool {
+    /// Name of the MCP server (the command for stdio servers)
+    pub server: String,
+    /// Name of the rejected tool
+    pub tool: String,
+    /// Why the schema was rejected
+    pub reason: String,
+}
+
+/// The MCP connections owned by an agent and its clones.
+///
+/// Dropping the last reference cancels every connection, which ends the service loop
+/// and kills stdio child processes (they are spawned with `kill_on_drop`).
+#[derive(Default)]
+struct McpServices(Vec<McpServiceHandle>);
+
+impl Drop for McpServices {
+    fn drop(&mut self) {
+        for handle in &self.0 {
+            handle.cancellation.cancel();
+        }
+    }
+}
+
 /// Represents the response from a chat interaction with the agent.
 ///
 /// The agent can respond in two ways: with plain text or by executing tools.
@@ -1666,6 +3442,7 @@ pub enum ChatResponse {
 ///     name: "calculator".to_string(),
 ///     args: r#"{"operation": "add", "a": 5, "b": 3}"#.to_string(),
 ///     result: "8".to_string(),
+///     timed_out: false,
 /// };
 ///
 /// println!("Tool {} with args {} returned: {}",
@@ -1689,6 +3466,10 @@ pub struct ToolCallOutput {
     /// All tool results are converted to strings for consistent handling,
     /// even if the tool internally works with other data types.
     pub result: String,
+
+    /// Whether the tool call timed out, in which case `result` holds the timeout error.
+    #[serde(default)]
+    pub timed_out: bool,
 }
 
 #[tool(
@@ -0,0 +1,152 @@
+//! Completion response caching.
+//!
+//! [`CachingModel`] wraps any [`Model`] and memoizes its responses keyed by a hash of the
+//! full [`CompletionRequest`] (system prompt, prompt, chat history, tools and sampling
+//! parameters). Entries expire after a fixed TTL. Clones of a `CachingModel` share the
+//! same cache, so cloned agents benefit from each other's hits.
+
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+use twox_hash::XxHash64;
+
+use super::request::{CompletionRequest, CompletionResponse};
+use super::{CompletionError, Model};
+
+/// A [`Model`] wrapper that returns cached responses for identical requests.
+pub struct CachingModel<M>
+where
+    M: Model,
+{
+    model: M,
+    ttl: Duration,
+    cache: Arc<DashMap<u64, (Instant, CompletionResponse<M::RawCompletionResponse>)>>,
+}
+
+impl<M> Clone for CachingModel<M>
+where
+    M: Model + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            model: self.model.clone(),
+            ttl: self.ttl,
+            cache: Arc::clone(&self.cache),
+        }
+    }
+}
+
+impl<M> CachingModel<M>
+where
+    M: Model,
+{
+    /// Wraps `model`, caching each response for `ttl`.
+    pub fn new(model: M, ttl: Duration) -> Self {
+        Self {
+            model,
+            ttl,
+            cache: Arc::new(DashMap::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &M {
+        &self.model
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Number of entries currently held, including expired ones not yet evicted.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Drops every cached response.
+    pub fn clear(&self) {
+        self.cache.clear();
+    }
+
+    /// Computes the cache key for a request.
+    ///
+    /// The request is hashed through its JSON representation, since the sampling
+    /// parameters are floats and the tool parameters are arbitrary JSON values. The
+    /// `Timestamp(millis)` prefixes agents put in front of every chat message are ignored,
+    /// otherwise the same conversation would only hit within the same millisecond.
+    pub fn request_key(request: &CompletionRequest) -> Result<u64, CompletionError> {
+        let mut hasher = XxHash64::default();
+        request.system_prompt.hash(&mut hasher);
+        strip_timestamps(&serde_json::to_string(&request.prompt)?).hash(&mut hasher);
+        strip_timestamps(&serde_json::to_string(&request.chat_history)?).hash(&mut hasher);
+        serde_json::to_string(&request.tools)?.hash(&mut hasher);
+        request.temperature.map(f64::to_bits).hash(&mut hasher);
+        request.max_tokens.hash(&mut hasher);
+        Ok(hasher.finish())
+    }
+}
+
+/// Removes every `Timestamp(millis): <ms> \n` prefix from a JSON-encoded message.
+fn strip_timestamps(json: &str) -> String {
+    const PREFIX: &str = "Timestamp(millis): ";
+    // The newline following the timestamp is escaped in JSON
+    const SUFFIX: &str = " \\n";
+
+    let mut stripped = String::with_capacity(json.len());
+    let mut rest = json;
+    while let Some(start) = rest.find(PREFIX) {
+        stripped.push_str(&rest[..start]);
+        let after = &rest[start + PREFIX.len()..];
+        let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        match after[digits..].strip_prefix(SUFFIX) {
+            Some(tail) if digits > 0 => rest = tail,
+            _ => {
+                stripped.push_str(PREFIX);
+                rest = after;
+            },
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+impl<M> Model for CachingModel<M>
+where
+    M: Model + Send + Sync,
+    M::RawCompletionResponse: Clone + Send + Sync,
+{
+    type RawCompletionResponse = M::RawCompletionResponse;
+
+    fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> BoxFuture<'_, Result<CompletionResponse<Self::RawCompletionResponse>, CompletionError>>
+    {
+        Box::pin(async move {
+            let key = Self::request_key(&request)?;
+
+            if let Some(entry) = self.cache.get(&key) {
+                let (created_at, response) = entry.value();
+                if created_at.elapsed() < self.ttl {
+                    log::debug!("💾 Completion cache hit");
+                    return Ok(response.clone());
+                }
+            }
+            self.cache.remove(&key);
+
+            let response = self.model.completion(request).await?;
+            self.cache.insert(key, (Instant::now(), response.clone()));
+            Ok(response)
+        })
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.model.supports_tools()
+    }
+}
@@ -1,4 +1,4 @@
-use std::{convert::Infallible, str::FromStr};
+use std::{collections::HashSet, convert::Infallible, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -28,12 +28,16 @@ pub enum UserContent {
     Document(Document),
 }
 
-/// Describes responses from a provider which is either text or a tool call.
+/// Describes responses from a provider which is either text, a tool call or the
+/// model's reasoning.
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(untagged)]
 pub enum AssistantContent {
     Text(Text),
     ToolCall(ToolCall),
+    /// Reasoning ("thinking") the model produced before answering, e.g. Claude's extended
+    /// thinking. Kept apart from `Text` so it can be logged without being shown to users.
+    Thinking(Thinking),
 }
 
 /// Tool result content containing information about a tool call and it's resulting content.
@@ -74,6 +78,16 @@ pub struct Text {
     pub text: String,
 }
 
+/// Reasoning content, with the signature of providers that sign it.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Thinking {
+    pub thinking: String,
+    /// Opaque signature Anthropic attaches to thinking blocks. It has to be sent back
+    /// unchanged when the block is replayed in a later request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
 /// Image content containing image data and metadata about it.
 #[derive(Default, Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Image {
@@ -215,6 +229,77 @@ impl Message {
             content: vec![AssistantContent::text(text)],
         }
     }
+
+    /// Ids of the tool calls made in this message.
+    fn tool_call_ids(&self) -> Vec<&str> {
+        match self {
+            Message::Assistant { content } => content
+                .iter()
+                .filter_map(|content| match content {
+                    AssistantContent::ToolCall(tool_call) => Some(tool_call.id.as_str()),
+                    AssistantContent::Text(_) | AssistantContent::Thinking(_) => None,
+                })
+                .collect(),
+            Message::User { .. } => Vec::new(),
+        }
+    }
+
+    /// Ids of the tool calls answered by this message.
+    fn tool_result_ids(&self) -> Vec<&str> {
+        match self {
+            Message::User { content } => content
+                .iter()
+                .filter_map(|content| match content {
+                    UserContent::ToolResult(result) => Some(result.id.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            Message::Assistant { .. } => Vec::new(),
+        }
+    }
+}
+
+/// Drop the oldest messages of `history` until at most `max_messages` remain, never
+/// separating a tool call from its results.
+///
+/// An assistant message with tool calls and the user messages that immediately follow it
+/// carrying the matching tool results are kept or evicted as one unit. Tool results whose
+/// call is not part of the history are removed as well, since strict providers (e.g.
+/// Anthropic) reject unmatched tool results. The returned history may therefore hold fewer
+/// than `max_messages` messages.
+pub fn truncate_history(history: Vec<Message>, max_messages: usize) -> Vec<Message> {
+    // Group the history into units that must be kept or evicted together
+    let mut units: Vec<Vec<Message>> = Vec::new();
+    let mut open_calls: HashSet<String> = HashSet::new();
+    for message in history {
+        let result_ids = message.tool_result_ids();
+        if !result_ids.is_empty() {
+            let answers_open_calls = result_ids.iter().all(|id| open_calls.contains(*id));
+            match units.last_mut() {
+                Some(unit) if answers_open_calls => unit.push(message),
+                // Orphaned tool result, its call is not part of the history
+                _ => {},
+            }
+            continue;
+        }
+
+        open_calls = message
+            .tool_call_ids()
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+        units.push(vec![message]);
+    }
+
+    let mut kept = 0;
+    let first_kept = units
+        .iter()
+        .rposition(|unit| {
+            kept += unit.len();
+            kept > max_messages
+        })
+        .map_or(0, |index| index + 1);
+    units.into_iter().skip(first_kept).flatten().collect()
 }
 
 impl UserContent {
@@ -279,6 +364,14 @@ impl AssistantContent {
         AssistantContent::Text(text.into().into())
     }
 
+    /// Helper constructor to make creating assistant reasoning content easier.
+    pub fn thinking(thinking: impl Into<String>) -> Self {
+        AssistantContent::Thinking(Thinking {
+            thinking: thinking.into(),
+            signature: None,
+        })
+    }
+
     /// Helper constructor to make creating assistant tool call content easier.
     pub fn tool_call(
         id: impl Into<String>,
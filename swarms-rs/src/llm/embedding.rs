@@ -0,0 +1,121 @@
+//! Text embeddings.
+//!
+//! [`EmbeddingModel`] is deliberately separate from [`Model`](super::Model): not every chat
+//! model can produce embeddings, and embedding models cannot chat. [`VectorStore`] is a
+//! small in-memory store that uses an `EmbeddingModel` to vectorize documents and retrieve
+//! the ones most similar to a query.
+
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use tokio::sync::RwLock;
+
+use super::CompletionError;
+
+pub trait EmbeddingModel {
+    /// Embeds every text, returning one vector per input in the same order.
+    fn embed(&self, texts: Vec<String>) -> BoxFuture<'_, Result<Vec<Vec<f32>>, CompletionError>>;
+}
+
+/// A document retrieved from a [`VectorStore`] together with its similarity to the query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoredDocument {
+    pub text: String,
+    /// Cosine similarity in `[-1, 1]`, higher is more similar
+    pub score: f32,
+}
+
+/// An in-memory vector store backed by an [`EmbeddingModel`].
+///
+/// Clones share the same documents.
+pub struct VectorStore<E> {
+    model: E,
+    documents: Arc<RwLock<Vec<(String, Vec<f32>)>>>,
+}
+
+impl<E: Clone> Clone for VectorStore<E> {
+    fn clone(&self) -> Self {
+        Self {
+            model: self.model.clone(),
+            documents: Arc::clone(&self.documents),
+        }
+    }
+}
+
+impl<E> VectorStore<E>
+where
+    E: EmbeddingModel + Send + Sync,
+{
+    pub fn new(model: E) -> Self {
+        Self {
+            model,
+            documents: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Embeds and stores the given documents.
+    pub async fn add_documents(&self, texts: Vec<String>) -> Result<(), CompletionError> {
+        if texts.is_empty() {
+            return Ok(());
+        }
+        let embeddings = self.model.embed(texts.clone()).await?;
+        if embeddings.len() != texts.len() {
+            return Err(CompletionError::Response(format!(
+                "Expected {} embeddings, got {}",
+                texts.len(),
+                embeddings.len()
+            )));
+        }
+        self.documents
+            .write()
+            .await
+            .extend(texts.into_iter().zip(embeddings));
+        Ok(())
+    }
+
+    /// Returns up to `top_k` stored documents most similar to `query`, best match first.
+    pub async fn search(
+        &self,
+        query: impl Into<String>,
+        top_k: usize,
+    ) -> Result<Vec<ScoredDocument>, CompletionError> {
+        let query = self
+            .model
+            .embed(vec![query.into()])
+            .await?
+            .pop()
+            .ok_or_else(|| CompletionError::Response("No embedding returned".to_owned()))?;
+
+        let documents = self.documents.read().await;
+        let mut scored: Vec<_> = documents
+            .iter()
+            .map(|(text, embedding)| ScoredDocument {
+                text: text.clone(),
+                score: cosine_similarity(&query, embedding),
+            })
+            .collect();
+        scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scored.truncate(top_k);
+        Ok(scored)
+    }
+
+    pub async fn len(&self) -> usize {
+        self.documents.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.documents.read().await.is_empty()
+    }
+}
+
+/// Cosine similarity of two vectors, `0.0` if either has zero length.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
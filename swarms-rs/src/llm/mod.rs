@@ -1,10 +1,16 @@
+use std::time::Duration;
+
 use futures::future::BoxFuture;
 use request::{CompletionRequest, CompletionResponse};
 use thiserror::Error;
 
+pub mod cache;
 pub mod completion;
+pub mod embedding;
 pub mod provider;
 pub mod request;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 pub trait Model {
     type RawCompletionResponse;
@@ -13,6 +19,14 @@ pub trait Model {
         &self,
         request: CompletionRequest,
     ) -> BoxFuture<Result<CompletionResponse<Self::RawCompletionResponse>, CompletionError>>;
+
+    /// Whether the model accepts tool definitions and answers with native tool calls.
+    ///
+    /// Agents check this before sending tools, see
+    /// [`SwarmsAgentBuilder::prompt_tool_fallback`](crate::agent::SwarmsAgentBuilder::prompt_tool_fallback).
+    fn supports_tools(&self) -> bool {
+        true
+    }
 }
 
 // Errors
@@ -38,7 +52,27 @@ pub enum CompletionError {
     #[error("ProviderError: {0}")]
     Provider(String),
 
+    /// The provider rejected the request because of rate limiting
+    #[error("RateLimited: retry after {retry_after:?}")]
+    RateLimited {
+        /// How long the provider asked to wait before retrying, if it said so
+        retry_after: Option<Duration>,
+    },
+
     /// Other error
     #[error("OtherError: {0}")]
     Other(String),
 }
+
+/// Parse a `Retry-After` header value, given either in (possibly fractional) seconds or
+/// as an HTTP date.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(seconds).ok();
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
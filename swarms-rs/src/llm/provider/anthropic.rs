@@ -132,6 +132,7 @@
 //!                 },
 //!                 "required": ["location"]
 //!             }),
+//!             output_schema: None,
 //!         }
 //!     }
 //!
@@ -153,6 +154,42 @@
 //! # }
 //! ```
 //!
+//! ## Computer Use
+//!
+//! [`ComputerUseTool`] exposes Claude's built-in `computer` tool. Add it to an agent like
+//! any other tool and supply a [`ComputerController`] that performs the requested actions
+//! (screenshots, key presses, clicks) on your display:
+//!
+//! ```rust,no_run
+//! use futures::future::BoxFuture;
+//! use swarms_rs::agent::SwarmsAgentBuilder;
+//! use swarms_rs::llm::provider::anthropic::{
+//!     Anthropic, ComputerAction, ComputerActionOutput, ComputerController, ComputerUseTool,
+//! };
+//! use swarms_rs::structs::tool::ToolError;
+//!
+//! struct MyDisplay;
+//!
+//! impl ComputerController for MyDisplay {
+//!     fn execute(
+//!         &self,
+//!         action: ComputerAction,
+//!     ) -> BoxFuture<'_, Result<ComputerActionOutput, ToolError>> {
+//!         Box::pin(async move { Ok(ComputerActionOutput::Text(format!("done: {action:?}"))) })
+//!     }
+//! }
+//!
+//! let agent = SwarmsAgentBuilder::new_with_model(Anthropic::from_env())
+//!     .add_tool(ComputerUseTool::new(MyDisplay, 1024, 768))
+//!     .build();
+//! ```
+//!
+//! The agent keeps tool results as text in its memory, which can't carry images, so
+//! screenshot actions fail with [`ToolError::ExecutionFailed`] in the agent loop and the
+//! model is told to continue without them. For anything that depends on screenshots, use
+//! [`ComputerUseTool::tool_result`] with [`Model::completion`] directly; it sends them as
+//! image blocks.
+//!
 //! ## Available Models
 //!
 //! | Model | Description | Use Case |
@@ -185,7 +222,7 @@ use bytes::Bytes;
 use futures::future::BoxFuture;
 use http_body_util::{BodyExt, Full};
 use hyper::{
-    Method, Request, Uri,
+    Method, Request, StatusCode, Uri,
     body::Buf,
     header::{CONTENT_TYPE, HeaderValue},
 };
@@ -195,12 +232,20 @@ use hyper_util::{
     rt::TokioExecutor,
 };
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-use crate::llm::{
-    self, CompletionError, Model,
-    request::{CompletionRequest, CompletionResponse},
+use crate::{
+    llm::{
+        self, CompletionError, Model,
+        completion::MimeType,
+        request::{CompletionRequest, CompletionResponse, ToolDefinition},
+    },
+    structs::tool::{ToolDyn, ToolError},
 };
 
+/// `max_tokens` sent when the request does not set one
+const DEFAULT_MAX_TOKENS: u64 = 4096;
+
 /// Anthropic API client for Claude models
 ///
 /// This struct provides a high-performance interface to Anthropic's Claude models.
@@ -265,6 +310,11 @@ pub struct Anthropic {
     api_key_header: HeaderValue,
     /// Pre-parsed messages endpoint URI (performance optimization)
     messages_uri: Uri,
+    /// Extended thinking configuration, see [`Anthropic::with_thinking`]
+    thinking: Option<AnthropicThinking>,
+    /// Whether to mark the system prompt and tools as cacheable, see
+    /// [`Anthropic::enable_prompt_caching`]
+    prompt_caching: bool,
 }
 
 impl Anthropic {
@@ -324,6 +374,55 @@ impl Anthropic {
         self
     }
 
+    /// Enable extended thinking with a budget of `budget_tokens` reasoning tokens
+    ///
+    /// The thinking text is returned as
+    /// [`AssistantContent::Thinking`](llm::completion::AssistantContent::Thinking), see
+    /// [`CompletionResponse::reasoning`]. It keeps the block's signature, so a response
+    /// replayed in the chat history, e.g. with
+    /// [`CompletionResponse::to_assistant_message`], sends its thinking back before the
+    /// tool calls it led to, as the API requires.
+    ///
+    /// Anthropic requires a budget of at least 1024 tokens and below `max_tokens`;
+    /// `max_tokens` is raised above the budget when needed. Temperature is not sent while
+    /// thinking is enabled, since the API only accepts the default.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::llm::provider::anthropic::Anthropic;
+    ///
+    /// let client = Anthropic::from_env_with_model("claude-3-7-sonnet-20250219").with_thinking(4096);
+    /// ```
+    pub fn with_thinking(mut self, budget_tokens: u64) -> Self {
+        self.thinking = Some(AnthropicThinking {
+            r#type: "enabled".to_string(),
+            budget_tokens,
+        });
+        self
+    }
+
+    /// Mark the system prompt and the tool definitions as cacheable
+    ///
+    /// Adds `cache_control: {"type": "ephemeral"}` to the system prompt and the last tool
+    /// definition, so Anthropic caches the prompt prefix they end and later calls within
+    /// a few minutes read it from the cache instead. This cuts cost and latency for
+    /// agents running many loops with a long system prompt or many tools. Prefixes
+    /// shorter than the model's minimum cacheable length (1024 tokens for most models)
+    /// are not cached, but are otherwise unaffected.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::llm::provider::anthropic::Anthropic;
+    ///
+    /// let client = Anthropic::from_env().enable_prompt_caching(true);
+    /// ```
+    pub fn enable_prompt_caching(mut self, enabled: bool) -> Self {
+        self.prompt_caching = enabled;
+        self
+    }
+
     /// Get a reference to the current model name
     pub fn model(&self) -> &str {
         &self.model
@@ -366,6 +465,8 @@ impl Anthropic {
             base_url,
             api_key_header,
             messages_uri,
+            thinking: None,
+            prompt_caching: false,
         }
     }
 
@@ -382,6 +483,7 @@ impl Anthropic {
     /// * `messages` - Vector of Anthropic messages
     /// * `temperature` - Optional temperature parameter
     /// * `tools` - Vector of available tools
+    /// * `thinking` - Optional extended thinking configuration
     ///
     /// # Performance Benefits
     ///
@@ -394,14 +496,26 @@ impl Anthropic {
         messages: Vec<AnthropicMessage>,
         temperature: Option<f64>,
         tools: Vec<AnthropicTool>,
+        thinking: Option<AnthropicThinking>,
     ) -> AnthropicRequest {
+        // The thinking budget counts towards max_tokens and must stay below it, and
+        // thinking cannot be combined with a custom temperature
+        let (max_tokens, temperature) = match &thinking {
+            Some(thinking) if max_tokens <= thinking.budget_tokens => {
+                (thinking.budget_tokens + DEFAULT_MAX_TOKENS, None)
+            },
+            Some(_) => (max_tokens, None),
+            None => (max_tokens, temperature),
+        };
+
         AnthropicRequest {
             model,
             max_tokens,
-            system: system_prompt,
+            system: system_prompt.map(AnthropicSystem::Text),
             messages,
             temperature,
             tools,
+            thinking,
         }
     }
 
@@ -548,17 +662,252 @@ impl Anthropic {
     }
 }
 
+/// Tool type of the Anthropic computer-use tool
+pub const COMPUTER_USE_TOOL_TYPE: &str = "computer_20241022";
+
+/// Beta header value required when the computer-use tool is sent
+const COMPUTER_USE_BETA: &str = "computer-use-2024-10-22";
+
+/// An action Claude requests through the computer-use tool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ComputerAction {
+    /// Press a key or key combination, e.g. `"Return"` or `"ctrl+s"`
+    Key {
+        text: String,
+    },
+    /// Type a string of text
+    Type {
+        text: String,
+    },
+    /// Move the cursor to `[x, y]`
+    MouseMove {
+        coordinate: [u32; 2],
+    },
+    LeftClick,
+    /// Press the left button, drag to `[x, y]` and release
+    LeftClickDrag {
+        coordinate: [u32; 2],
+    },
+    RightClick,
+    MiddleClick,
+    DoubleClick,
+    /// Capture the current screen
+    Screenshot,
+    /// Report the current cursor position
+    CursorPosition,
+}
+
+/// The result of executing a [`ComputerAction`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComputerActionOutput {
+    /// Textual output, e.g. the cursor position or a confirmation
+    Text(String),
+    /// A base64 encoded screenshot
+    Screenshot { media_type: String, data: String },
+}
+
+/// Performs computer-use actions on an actual (or virtual) display.
+pub trait ComputerController: Send + Sync {
+    fn execute(
+        &self,
+        action: ComputerAction,
+    ) -> BoxFuture<'_, Result<ComputerActionOutput, ToolError>>;
+}
+
+/// Claude's built-in `computer` tool, backed by a [`ComputerController`].
+///
+/// The definition uses the special `computer_20241022` tool type, which the [`Anthropic`]
+/// provider sends as-is (with the required beta header) instead of as a custom tool.
+/// Tool calls are parsed into [`ComputerAction`]s and their output becomes the tool
+/// result. [`tool_result`](Self::tool_result) returns screenshots as images, while
+/// [`ToolDyn::call`], which the agent loop uses, rejects them because its text results
+/// can't carry images, see the [module documentation](self#computer-use).
+pub struct ComputerUseTool {
+    controller: Arc<dyn ComputerController>,
+    display_width_px: u32,
+    display_height_px: u32,
+    display_number: Option<u32>,
+}
+
+impl ComputerUseTool {
+    pub const NAME: &'static str = "computer";
+
+    pub fn new(
+        controller: impl ComputerController + 'static,
+        display_width_px: u32,
+        display_height_px: u32,
+    ) -> Self {
+        Self {
+            controller: Arc::new(controller),
+            display_width_px,
+            display_height_px,
+            display_number: None,
+        }
+    }
+
+    /// Sets the X11 display number, for environments with several displays
+    pub fn display_number(mut self, display_number: u32) -> Self {
+        self.display_number = Some(display_number);
+        self
+    }
+
+    /// Executes the tool call `id` with the JSON `args` and builds its tool result.
+    ///
+    /// Screenshots become [`ToolResultContent::Image`](llm::completion::ToolResultContent::Image),
+    /// which the [`Anthropic`] provider sends back as an image block.
+    pub async fn tool_result(
+        &self,
+        id: impl Into<String>,
+        args: &str,
+    ) -> Result<llm::completion::ToolResult, ToolError> {
+        let content = match self.execute(args).await? {
+            ComputerActionOutput::Text(text) => llm::completion::ToolResultContent::text(text),
+            ComputerActionOutput::Screenshot { media_type, data } => {
+                let media_type = llm::completion::ImageMediaType::from_mime_type(&media_type)
+                    .ok_or_else(|| {
+                        ToolError::ExecutionFailed(format!(
+                            "Unsupported screenshot media type: {media_type}"
+                        ))
+                    })?;
+                llm::completion::ToolResultContent::image(
+                    data,
+                    Some(llm::completion::ContentFormat::Base64),
+                    Some(media_type),
+                    None,
+                )
+            },
+        };
+        Ok(llm::completion::ToolResult {
+            id: id.into(),
+            content: vec![content],
+        })
+    }
+
+    async fn execute(&self, args: &str) -> Result<ComputerActionOutput, ToolError> {
+        let action: ComputerAction =
+            serde_json::from_str(args).map_err(|e| ToolError::InvalidArguments(e.to_string()))?;
+        self.controller.execute(action).await
+    }
+}
+
+impl ToolDyn for ComputerUseTool {
+    fn name(&self) -> String {
+        Self::NAME.to_owned()
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        let mut parameters = serde_json::json!({
+            "type": COMPUTER_USE_TOOL_TYPE,
+            "name": Self::NAME,
+            "display_width_px": self.display_width_px,
+            "display_height_px": self.display_height_px,
+        });
+        if let Some(display_number) = self.display_number {
+            parameters["display_number"] = display_number.into();
+        }
+
+        ToolDefinition {
+            name: Self::NAME.to_owned(),
+            description: "Control the computer's mouse, keyboard and screen".to_owned(),
+            parameters,
+            output_schema: None,
+        }
+    }
+
+    fn call(&self, args: String) -> BoxFuture<'_, Result<String, ToolError>> {
+        Box::pin(async move {
+            // Checked before executing, so no screenshot is taken just to be dropped
+            if matches!(
+                serde_json::from_str::<ComputerAction>(&args),
+                Ok(ComputerAction::Screenshot)
+            ) {
+                return Err(screenshots_unsupported());
+            }
+            match self.execute(&args).await? {
+                ComputerActionOutput::Text(text) => Ok(text),
+                ComputerActionOutput::Screenshot { .. } => Err(screenshots_unsupported()),
+            }
+        })
+    }
+}
+
+fn screenshots_unsupported() -> ToolError {
+    ToolError::ExecutionFailed(
+        "Screenshots are not available here, because tool results are kept as text. \
+         Continue without them."
+            .to_owned(),
+    )
+}
+
 /// Anthropic API request structure
 #[derive(Serialize, Debug)]
 struct AnthropicRequest {
     model: String,
     max_tokens: u64,
-    system: Option<String>,
+    system: Option<AnthropicSystem>,
     messages: Vec<AnthropicMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f64>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tools: Vec<AnthropicTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<AnthropicThinking>,
+}
+
+impl AnthropicRequest {
+    /// Marks the system prompt and the last tool as the ends of cached prompt prefixes
+    fn with_prompt_caching(mut self) -> Self {
+        if let Some(AnthropicSystem::Text(text)) = self.system.take() {
+            self.system = Some(AnthropicSystem::Blocks(vec![AnthropicSystemBlock {
+                r#type: "text".to_string(),
+                text,
+                cache_control: Some(AnthropicCacheControl::ephemeral()),
+            }]));
+        }
+        if let Some(tool) = self.tools.last_mut() {
+            tool.set_cache_control(AnthropicCacheControl::ephemeral());
+        }
+        self
+    }
+}
+
+/// System prompt of a request, as plain text or as blocks carrying cache control
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+enum AnthropicSystem {
+    Text(String),
+    Blocks(Vec<AnthropicSystemBlock>),
+}
+
+/// Text block of a system prompt
+#[derive(Serialize, Debug)]
+struct AnthropicSystemBlock {
+    r#type: String,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<AnthropicCacheControl>,
+}
+
+/// Marks the end of a prompt prefix to cache, see [`Anthropic::enable_prompt_caching`]
+#[derive(Serialize, Debug, Clone)]
+struct AnthropicCacheControl {
+    r#type: String,
+}
+
+impl AnthropicCacheControl {
+    fn ephemeral() -> Self {
+        Self {
+            r#type: "ephemeral".to_string(),
+        }
+    }
+}
+
+/// Extended thinking configuration sent with a request
+#[derive(Serialize, Debug, Clone)]
+struct AnthropicThinking {
+    r#type: String,
+    budget_tokens: u64,
 }
 
 /// Anthropic message structure
@@ -569,6 +918,9 @@ struct AnthropicMessage {
 }
 
 /// Anthropic content structure
+///
+/// Block types without a dedicated variant are kept as raw JSON in `Other`, so new
+/// response block types don't break parsing.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 enum AnthropicContent {
@@ -584,24 +936,85 @@ enum AnthropicContent {
     },
     ToolResult {
         r#type: String,
+        #[serde(rename = "tool_use_id")]
         tool_call_id: String,
         content: Vec<AnthropicToolResultContent>,
     },
+    Thinking {
+        r#type: String,
+        thinking: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+    },
+    Other(serde_json::Value),
 }
 
 /// Anthropic tool structure
 #[derive(Serialize, Debug, Clone)]
-struct AnthropicTool {
-    name: String,
-    description: String,
-    input_schema: serde_json::Value,
+#[serde(untagged)]
+enum AnthropicTool {
+    Custom {
+        name: String,
+        description: String,
+        input_schema: serde_json::Value,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<AnthropicCacheControl>,
+    },
+    /// Anthropic-defined tool such as `computer_20241022`, sent as-is
+    Builtin(serde_json::Value),
+}
+
+impl AnthropicTool {
+    fn is_computer_use(&self) -> bool {
+        matches!(self, AnthropicTool::Builtin(spec) if spec["type"] == COMPUTER_USE_TOOL_TYPE)
+    }
+
+    fn set_cache_control(&mut self, control: AnthropicCacheControl) {
+        match self {
+            AnthropicTool::Custom { cache_control, .. } => *cache_control = Some(control),
+            AnthropicTool::Builtin(spec) => {
+                if let Some(spec) = spec.as_object_mut() {
+                    spec.insert("cache_control".to_string(), serde_json::json!(control));
+                }
+            },
+        }
+    }
+}
+
+impl From<ToolDefinition> for AnthropicTool {
+    fn from(tool: ToolDefinition) -> Self {
+        if tool.parameters["type"] == COMPUTER_USE_TOOL_TYPE {
+            return AnthropicTool::Builtin(tool.parameters);
+        }
+        AnthropicTool::Custom {
+            description: tool.description_with_output_schema(),
+            name: tool.name,
+            input_schema: tool.parameters,
+            cache_control: None,
+        }
+    }
 }
 
 /// Anthropic tool result content
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 enum AnthropicToolResultContent {
-    Text { r#type: String, text: String },
+    Text {
+        r#type: String,
+        text: String,
+    },
+    Image {
+        r#type: String,
+        source: AnthropicImageSource,
+    },
+}
+
+/// Base64 image source, used for screenshots in tool results
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct AnthropicImageSource {
+    r#type: String,
+    media_type: String,
+    data: String,
 }
 
 /// Anthropic API response structure
@@ -700,34 +1113,39 @@ impl Model for Anthropic {
             let tools = request
                 .tools
                 .into_iter()
-                .map(|tool| AnthropicTool {
-                    name: tool.name,
-                    description: tool.description,
-                    input_schema: tool.parameters,
-                })
+                .map(AnthropicTool::from)
                 .collect::<Vec<_>>();
+            let computer_use = tools.iter().any(AnthropicTool::is_computer_use);
 
             // Build Anthropic request using optimized helper function
-            let anthropic_request = Self::build_optimized_request(
+            let mut anthropic_request = Self::build_optimized_request(
                 self.model.clone(),
-                request.max_tokens.unwrap_or(4096),
+                request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
                 system_prompt,
                 messages,
                 request.temperature,
                 tools,
+                self.thinking.clone(),
             );
+            if self.prompt_caching {
+                anthropic_request = anthropic_request.with_prompt_caching();
+            }
 
             // Serialize request with optimized JSON handling
             let request_body = serde_json::to_string(&anthropic_request)
                 .map_err(|e| CompletionError::Request(e.into()))?;
 
             // Build HTTP request using cached values (performance optimization)
-            let req = Request::builder()
+            let mut req = Request::builder()
                 .method(Method::POST)
                 .uri(self.messages_uri.clone()) // Use cached URI
                 .header(CONTENT_TYPE, "application/json")
                 .header("x-api-key", self.api_key_header.clone()) // Use cached header
-                .header("anthropic-version", "2023-06-01")
+                .header("anthropic-version", "2023-06-01");
+            if computer_use {
+                req = req.header("anthropic-beta", COMPUTER_USE_BETA);
+            }
+            let req = req
                 .body(Full::new(Bytes::from(request_body)))
                 .map_err(|e| CompletionError::Request(e.into()))?;
 
@@ -739,6 +1157,11 @@ impl Model for Anthropic {
                 .map_err(|e| CompletionError::Other(format!("HTTP request failed: {}", e)))?;
 
             let status = response.status();
+            let retry_after = response
+                .headers()
+                .get("retry-after")
+                .and_then(|value| value.to_str().ok())
+                .and_then(llm::parse_retry_after);
 
             // Read response body completely using stream reading
             let mut response_bytes = Vec::new();
@@ -774,6 +1197,9 @@ impl Model for Anthropic {
             }
 
             // Handle non-success status codes
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                return Err(CompletionError::RateLimited { retry_after });
+            }
             if !status.is_success() {
                 if let Ok(error_response) = serde_json::from_str::<AnthropicError>(&response_text) {
                     return Err(CompletionError::Provider(format!(
@@ -828,10 +1254,20 @@ fn convert_user_content_to_anthropic(
                                 text: text.text,
                             })
                         },
-                        llm::completion::ToolResultContent::Image(_) => {
-                            Err(CompletionError::Request(
-                                "Image content in tool results not supported by Anthropic".into(),
-                            ))
+                        llm::completion::ToolResultContent::Image(image) => {
+                            let media_type = image.media_type.ok_or_else(|| {
+                                CompletionError::Request(
+                                    "Image tool results need a media type for Anthropic".into(),
+                                )
+                            })?;
+                            Ok(AnthropicToolResultContent::Image {
+                                r#type: "image".to_string(),
+                                source: AnthropicImageSource {
+                                    r#type: "base64".to_string(),
+                                    media_type: media_type.to_mime_type().to_string(),
+                                    data: image.data,
+                                },
+                            })
                         },
                     })
                     .collect();
@@ -879,6 +1315,20 @@ fn convert_assistant_content_to_anthropic(
                     input: tool_call.function.arguments,
                 });
             },
+            // Anthropic requires signed thinking blocks to be sent back unchanged before the
+            // tool calls they led to. Unsigned thinking, e.g. from another provider, can't
+            // be verified and is dropped.
+            llm::completion::AssistantContent::Thinking(llm::completion::Thinking {
+                thinking,
+                signature: Some(signature),
+            }) => {
+                result.push(AnthropicContent::Thinking {
+                    r#type: "thinking".to_string(),
+                    thinking,
+                    signature: Some(signature),
+                });
+            },
+            llm::completion::AssistantContent::Thinking(_) => continue,
         }
     }
 
@@ -903,10 +1353,26 @@ fn convert_anthropic_response_to_internal(
                     id, name, input,
                 ));
             },
+            AnthropicContent::Thinking {
+                thinking,
+                signature,
+                ..
+            } => {
+                result.push(llm::completion::AssistantContent::Thinking(
+                    llm::completion::Thinking {
+                        thinking,
+                        signature,
+                    },
+                ));
+            },
             AnthropicContent::ToolResult { .. } => {
                 // Tool results are handled in user messages, not assistant responses
                 continue;
             },
+            AnthropicContent::Other(block) => {
+                log::debug!("Skipping unsupported Anthropic content block: {}", block);
+                continue;
+            },
         }
     }
 
@@ -935,4 +1401,222 @@ mod tests {
         let anthropic = Anthropic::new("test-key").set_model("claude-3-haiku-20240307");
         assert_eq!(anthropic.model, "claude-3-haiku-20240307");
     }
+
+    struct FakeDisplay;
+
+    impl ComputerController for FakeDisplay {
+        fn execute(
+            &self,
+            action: ComputerAction,
+        ) -> BoxFuture<'_, Result<ComputerActionOutput, ToolError>> {
+            Box::pin(async move {
+                Ok(match action {
+                    ComputerAction::Screenshot => ComputerActionOutput::Screenshot {
+                        media_type: "image/png".to_string(),
+                        data: "aGVsbG8=".to_string(),
+                    },
+                    other => ComputerActionOutput::Text(format!("{other:?}")),
+                })
+            })
+        }
+    }
+
+    #[test]
+    fn test_computer_use_tool_is_sent_as_builtin() {
+        let tool = ComputerUseTool::new(FakeDisplay, 1024, 768).display_number(1);
+        let anthropic_tool = AnthropicTool::from(ToolDyn::definition(&tool));
+        assert!(anthropic_tool.is_computer_use());
+
+        let json = serde_json::to_value(&anthropic_tool).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "computer_20241022",
+                "name": "computer",
+                "display_width_px": 1024,
+                "display_height_px": 768,
+                "display_number": 1,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_computer_use_tool_executes_actions() {
+        let tool = ComputerUseTool::new(FakeDisplay, 1024, 768);
+
+        // Text tool results can't carry screenshots
+        let screenshot = ToolDyn::call(&tool, r#"{"action":"screenshot"}"#.to_string()).await;
+        assert!(matches!(screenshot, Err(ToolError::ExecutionFailed(_))));
+
+        let key = ToolDyn::call(&tool, r#"{"action":"key","text":"Return"}"#.to_string())
+            .await
+            .unwrap();
+        assert!(key.contains("Return"));
+
+        let invalid = ToolDyn::call(&tool, r#"{"action":"teleport"}"#.to_string()).await;
+        assert!(invalid.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_computer_use_screenshot_is_sent_as_image() {
+        let tool = ComputerUseTool::new(FakeDisplay, 1024, 768);
+        let result = tool
+            .tool_result("toolu_1", r#"{"action":"screenshot"}"#)
+            .await
+            .unwrap();
+        assert!(matches!(
+            result.content[..],
+            [llm::completion::ToolResultContent::Image(_)]
+        ));
+
+        let content =
+            convert_user_content_to_anthropic(vec![llm::completion::UserContent::ToolResult(
+                result,
+            )])
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&content).unwrap()[0],
+            serde_json::json!({
+                "type": "tool_result",
+                "tool_use_id": "toolu_1",
+                "content": [{
+                    "type": "image",
+                    "source": {"type": "base64", "media_type": "image/png", "data": "aGVsbG8="}
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_response_blocks_are_skipped() {
+        let content: Vec<AnthropicContent> = serde_json::from_value(serde_json::json!([
+            {"type": "redacted_thinking", "data": "opaque"},
+            {"type": "text", "text": "hello"},
+            {"type": "tool_use", "id": "toolu_1", "name": "computer", "input": {"action": "screenshot"}},
+        ]))
+        .unwrap();
+
+        let choice = convert_anthropic_response_to_internal(content).unwrap();
+        assert_eq!(choice.len(), 2);
+        assert_eq!(choice[0], llm::completion::AssistantContent::text("hello"));
+    }
+
+    #[test]
+    fn test_thinking_config_is_sent_with_request() {
+        let anthropic = Anthropic::new("test-key").with_thinking(2048);
+        let request = Anthropic::build_optimized_request(
+            anthropic.model.clone(),
+            1024,
+            None,
+            Vec::new(),
+            Some(0.3),
+            Vec::new(),
+            anthropic.thinking.clone(),
+        );
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            json["thinking"],
+            serde_json::json!({"type": "enabled", "budget_tokens": 2048})
+        );
+        assert_eq!(json["max_tokens"], 2048 + DEFAULT_MAX_TOKENS);
+        assert!(json.get("temperature").is_none());
+
+        let request = Anthropic::build_optimized_request(
+            anthropic.model,
+            1024,
+            None,
+            Vec::new(),
+            Some(0.3),
+            Vec::new(),
+            None,
+        );
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("thinking").is_none());
+        assert_eq!(json["temperature"], 0.3);
+    }
+
+    #[test]
+    fn test_prompt_caching_marks_system_prompt_and_last_tool() {
+        let tool = |name: &str| {
+            AnthropicTool::from(ToolDefinition {
+                name: name.to_string(),
+                description: format!("The {name} tool"),
+                parameters: serde_json::json!({"type": "object"}),
+                output_schema: None,
+            })
+        };
+        let request = || {
+            Anthropic::build_optimized_request(
+                "claude-3-5-sonnet-20241022".to_string(),
+                1024,
+                Some("You are a long system prompt.".to_string()),
+                Vec::new(),
+                None,
+                vec![tool("first"), tool("second")],
+                None,
+            )
+        };
+
+        let json = serde_json::to_value(request()).unwrap();
+        assert_eq!(json["system"], "You are a long system prompt.");
+        assert!(json["tools"][1].get("cache_control").is_none());
+
+        let json = serde_json::to_value(request().with_prompt_caching()).unwrap();
+        assert_eq!(
+            json["system"],
+            serde_json::json!([{
+                "type": "text",
+                "text": "You are a long system prompt.",
+                "cache_control": {"type": "ephemeral"},
+            }])
+        );
+        assert!(json["tools"][0].get("cache_control").is_none());
+        assert_eq!(
+            json["tools"][1]["cache_control"],
+            serde_json::json!({"type": "ephemeral"})
+        );
+    }
+
+    #[test]
+    fn test_thinking_blocks_are_kept_apart_from_text() {
+        let content: Vec<AnthropicContent> = serde_json::from_value(serde_json::json!([
+            {"type": "thinking", "thinking": "hmm", "signature": "sig"},
+            {"type": "text", "text": "hello"},
+        ]))
+        .unwrap();
+
+        let choice = convert_anthropic_response_to_internal(content).unwrap();
+        assert_eq!(
+            choice,
+            [
+                llm::completion::AssistantContent::Thinking(llm::completion::Thinking {
+                    thinking: "hmm".to_string(),
+                    signature: Some("sig".to_string()),
+                }),
+                llm::completion::AssistantContent::text("hello"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_signed_thinking_round_trips() {
+        let blocks = serde_json::json!([
+            {"type": "thinking", "thinking": "hmm", "signature": "sig"},
+            {"type": "tool_use", "id": "toolu_1", "name": "search", "input": {"q": "rust"}},
+        ]);
+        let content: Vec<AnthropicContent> = serde_json::from_value(blocks.clone()).unwrap();
+
+        let choice = convert_anthropic_response_to_internal(content).unwrap();
+        let replayed = convert_assistant_content_to_anthropic(choice).unwrap();
+        assert_eq!(serde_json::to_value(&replayed).unwrap(), blocks);
+
+        // Thinking without a signature can't be replayed
+        let unsigned = convert_assistant_content_to_anthropic(vec![
+            llm::completion::AssistantContent::thinking("hmm"),
+            llm::completion::AssistantContent::text("hello"),
+        ])
+        .unwrap();
+        assert_eq!(unsigned.len(), 1);
+    }
 }
@@ -1,19 +1,22 @@
-use std::{cmp::Ordering, env};
+use std::{cmp::Ordering, env, time::Duration};
 
 use async_openai::{
     Client,
     config::OpenAIConfig,
     types::{
-        ChatCompletionMessageToolCall, ChatCompletionRequestAssistantMessageArgs,
-        ChatCompletionRequestAssistantMessageContent,
-        ChatCompletionRequestAssistantMessageContentPart, ChatCompletionRequestMessage,
+        Batch, BatchCompletionWindow, BatchEndpoint, BatchRequestArgs, BatchRequestInput,
+        BatchRequestInputMethod, BatchRequestOutput, BatchStatus, ChatCompletionMessageToolCall,
+        ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestAssistantMessageContent,
+        ChatCompletionRequestAssistantMessageContentPart,
+        ChatCompletionRequestDeveloperMessageArgs, ChatCompletionRequestMessage,
         ChatCompletionRequestMessageContentPartAudio, ChatCompletionRequestMessageContentPartImage,
         ChatCompletionRequestMessageContentPartText, ChatCompletionRequestSystemMessageArgs,
         ChatCompletionRequestToolMessage, ChatCompletionRequestToolMessageContent,
         ChatCompletionRequestToolMessageContentPart, ChatCompletionRequestUserMessageArgs,
         ChatCompletionRequestUserMessageContentPart, ChatCompletionToolArgs,
-        ChatCompletionToolType, CreateChatCompletionRequestArgs, FunctionCall, FunctionObjectArgs,
-        ImageUrl, InputAudio, InputAudioFormat,
+        ChatCompletionToolType, CreateChatCompletionRequest, CreateChatCompletionRequestArgs,
+        CreateChatCompletionResponse, CreateEmbeddingRequestArgs, CreateFileRequestArgs, FileInput,
+        FilePurpose, FunctionCall, FunctionObjectArgs, ImageUrl, InputAudio, InputAudioFormat,
     },
 };
 use futures::future::BoxFuture;
@@ -22,15 +25,40 @@ use crate::{
     agent::SwarmsAgentBuilder, // Updated import path - now from crate::agent instead of crate::structs::agent
     llm::{
         self, CompletionError, Model,
+        embedding::EmbeddingModel,
         request::{CompletionRequest, CompletionResponse},
     },
 };
 
+/// How often [`OpenAI::collect_batch_results`] checks whether a batch job has finished.
+pub const BATCH_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Prefix of the `custom_id` of each batch request, followed by its index.
+const BATCH_CUSTOM_ID_PREFIX: &str = "request-";
+
+/// Embedding model used by [`OpenAI::embed`](EmbeddingModel::embed) unless overridden
+/// with [`OpenAI::set_embedding_model`].
+pub const DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+
+/// The message role used to send the system prompt to the model.
+///
+/// Reasoning models (o1, o3, ...) expect instructions under the `developer` role and
+/// ignore `system` messages, while most chat models still use `system`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SystemRole {
+    #[default]
+    System,
+    Developer,
+}
+
 #[derive(Clone)]
 pub struct OpenAI {
     client: Client<OpenAIConfig>,
     model: String,
     system_prompt: Option<String>,
+    system_role: SystemRole,
+    embedding_model: String,
+    supports_tools: bool,
 }
 
 impl OpenAI {
@@ -45,6 +73,9 @@ impl OpenAI {
             client,
             model: "gpt-4o-mini".to_owned(),
             system_prompt: None,
+            system_role: SystemRole::default(),
+            embedding_model: DEFAULT_EMBEDDING_MODEL.to_owned(),
+            supports_tools: true,
         }
     }
 
@@ -61,6 +92,9 @@ impl OpenAI {
             client,
             model: "gpt-4o-mini".to_owned(),
             system_prompt: None,
+            system_role: SystemRole::default(),
+            embedding_model: DEFAULT_EMBEDDING_MODEL.to_owned(),
+            supports_tools: true,
         }
     }
 
@@ -81,6 +115,25 @@ impl OpenAI {
         self
     }
 
+    /// Selects the role used for the system prompt, see [`SystemRole`].
+    pub fn set_system_role(mut self, system_role: SystemRole) -> Self {
+        self.system_role = system_role;
+        self
+    }
+
+    /// Selects the model used for embeddings, see [`EmbeddingModel`].
+    pub fn set_embedding_model<S: Into<String>>(mut self, model: S) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+
+    /// Declares whether the model supports native tool calling, for OpenAI-compatible
+    /// endpoints serving models that don't, see [`Model::supports_tools`].
+    pub fn set_supports_tools(mut self, supports_tools: bool) -> Self {
+        self.supports_tools = supports_tools;
+        self
+    }
+
     pub fn set_system_prompt<S: Into<String>>(&mut self, prompt: S) {
         self.system_prompt = Some(prompt.into());
     }
@@ -98,77 +151,15 @@ impl Model for OpenAI {
         request: CompletionRequest,
     ) -> BoxFuture<Result<CompletionResponse<Self::RawCompletionResponse>, CompletionError>> {
         Box::pin(async move {
-            let mut msgs = Vec::new();
-
-            if let Some(system_prompt) = request.system_prompt {
-                msgs.push(
-                    ChatCompletionRequestSystemMessageArgs::default()
-                        .content(system_prompt)
-                        .build()?
-                        .into(),
-                );
-            }
-
-            let chat_history = request
-                .chat_history
-                .into_iter()
-                .map(|msg| {
-                    let msgs: Vec<ChatCompletionRequestMessage> = msg.try_into()?;
-                    Ok::<_, CompletionError>(msgs)
-                })
-                .collect::<Result<Vec<_>, _>>()?
-                .into_iter()
-                .flatten()
-                .collect::<Vec<_>>();
-
-            msgs.extend(chat_history);
-
-            if request.prompt.rag_text().is_some() {
-                let prompt: Vec<ChatCompletionRequestMessage> = request.prompt.try_into()?;
-                msgs.extend(prompt);
-            }
-
-            let mut create_request_builder = CreateChatCompletionRequestArgs::default();
-            if let Some(max_tokens) = request.max_tokens {
-                create_request_builder.max_tokens(max_tokens as u32);
-            }
-            if let Some(temperature) = request.temperature {
-                create_request_builder.temperature(temperature as f32);
-            }
-            if !request.tools.is_empty() {
-                create_request_builder.tools(
-                    request
-                        .tools
-                        .into_iter()
-                        .map(|tool| {
-                            ChatCompletionToolArgs::default()
-                                .r#type(ChatCompletionToolType::Function)
-                                .function(
-                                    FunctionObjectArgs::default()
-                                        .name(tool.name)
-                                        .description(tool.description)
-                                        .parameters(tool.parameters)
-                                        .build()
-                                        .expect("All field provided"),
-                                )
-                                .build()
-                                .expect("All field provided")
-                        })
-                        .collect::<Vec<_>>(),
-                );
-            }
-            let create_request = create_request_builder
-                .model(self.model.clone())
-                .messages(msgs)
-                .build()?;
+            let create_request = self.create_request(request)?;
 
             tracing::debug!(
                 "OpenAI Create Request: {}",
                 serde_json::to_string_pretty(&create_request).unwrap()
             );
 
-            let response: CompletionResponse<async_openai::types::CreateChatCompletionResponse> =
-                self.client.chat().create(create_request).await?.into();
+            let body: serde_json::Value = self.client.chat().create_byot(create_request).await?;
+            let response = response_from_json(body)?;
 
             tracing::debug!(
                 "OpenAI response: {}",
@@ -178,12 +169,342 @@ impl Model for OpenAI {
             Ok(response)
         })
     }
+
+    fn supports_tools(&self) -> bool {
+        self.supports_tools
+    }
+}
+
+impl OpenAI {
+    /// Converts a request into the chat completions API format.
+    fn create_request(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CreateChatCompletionRequest, CompletionError> {
+        let mut msgs = Vec::new();
+
+        if let Some(system_prompt) = request.system_prompt {
+            let system_message: ChatCompletionRequestMessage = match self.system_role {
+                SystemRole::System => ChatCompletionRequestSystemMessageArgs::default()
+                    .content(system_prompt)
+                    .build()?
+                    .into(),
+                SystemRole::Developer => ChatCompletionRequestDeveloperMessageArgs::default()
+                    .content(system_prompt)
+                    .build()?
+                    .into(),
+            };
+            msgs.push(system_message);
+        }
+
+        let chat_history = request
+            .chat_history
+            .into_iter()
+            .map(|msg| {
+                let msgs: Vec<ChatCompletionRequestMessage> = msg.try_into()?;
+                Ok::<_, CompletionError>(msgs)
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        msgs.extend(chat_history);
+
+        if request.prompt.rag_text().is_some() {
+            let prompt: Vec<ChatCompletionRequestMessage> = request.prompt.try_into()?;
+            msgs.extend(prompt);
+        }
+
+        let mut create_request_builder = CreateChatCompletionRequestArgs::default();
+        if let Some(max_tokens) = request.max_tokens {
+            create_request_builder.max_tokens(max_tokens as u32);
+        }
+        if let Some(temperature) = request.temperature {
+            create_request_builder.temperature(temperature as f32);
+        }
+        if !request.tools.is_empty() {
+            create_request_builder.tools(
+                request
+                    .tools
+                    .into_iter()
+                    .map(|tool| {
+                        ChatCompletionToolArgs::default()
+                            .r#type(ChatCompletionToolType::Function)
+                            .function(
+                                FunctionObjectArgs::default()
+                                    .description(tool.description_with_output_schema())
+                                    .name(tool.name)
+                                    .parameters(tool.parameters)
+                                    .build()
+                                    .expect("All field provided"),
+                            )
+                            .build()
+                            .expect("All field provided")
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Ok(create_request_builder
+            .model(self.model.clone())
+            .messages(msgs)
+            .build()?)
+    }
+}
+
+impl EmbeddingModel for OpenAI {
+    fn embed(&self, texts: Vec<String>) -> BoxFuture<'_, Result<Vec<Vec<f32>>, CompletionError>> {
+        Box::pin(async move {
+            if texts.is_empty() {
+                return Ok(Vec::new());
+            }
+            let request = CreateEmbeddingRequestArgs::default()
+                .model(&self.embedding_model)
+                .input(texts)
+                .build()?;
+            let mut data = self.client.embeddings().create(request).await?.data;
+            // The API documents `index` as the input position; don't rely on response order
+            data.sort_by_key(|embedding| embedding.index);
+            Ok(data
+                .into_iter()
+                .map(|embedding| embedding.embedding)
+                .collect())
+        })
+    }
+}
+
+/// A job submitted with [`OpenAI::batch_completion`] and its progress.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchJobResponse {
+    /// ID to pass to [`OpenAI::poll_batch`] and [`OpenAI::collect_batch_results`]
+    pub job_id: String,
+    pub status: BatchStatus,
+    pub input_file_id: String,
+    /// Set once the job completed with at least one successful request
+    pub output_file_id: Option<String>,
+    /// Set once the job completed with at least one failed request
+    pub error_file_id: Option<String>,
+    /// Number of requests in the job
+    pub total: u32,
+    pub completed: u32,
+    pub failed: u32,
+}
+
+impl BatchJobResponse {
+    /// Whether the job has stopped, successfully or not, so polling it again is pointless.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            BatchStatus::Completed
+                | BatchStatus::Failed
+                | BatchStatus::Expired
+                | BatchStatus::Cancelled
+        )
+    }
+}
+
+impl From<Batch> for BatchJobResponse {
+    fn from(batch: Batch) -> Self {
+        let counts = batch.request_counts.as_ref();
+        Self {
+            total: counts.map_or(0, |counts| counts.total),
+            completed: counts.map_or(0, |counts| counts.completed),
+            failed: counts.map_or(0, |counts| counts.failed),
+            job_id: batch.id,
+            status: batch.status,
+            input_file_id: batch.input_file_id,
+            output_file_id: batch.output_file_id,
+            error_file_id: batch.error_file_id,
+        }
+    }
+}
+
+/// Results of a batch job, one per submitted request in submission order.
+pub type BatchResults =
+    Vec<Result<CompletionResponse<CreateChatCompletionResponse>, CompletionError>>;
+
+impl OpenAI {
+    /// Submits `requests` as one job to the [Batch API](https://platform.openai.com/docs/guides/batch),
+    /// which completes them within 24 hours at half the price of individual requests.
+    ///
+    /// The requests are uploaded as a JSONL file and run with this client's model and
+    /// system role. Use [`collect_batch_results`](Self::collect_batch_results) to wait for
+    /// the answers.
+    pub fn batch_completion(
+        &self,
+        requests: Vec<CompletionRequest>,
+    ) -> BoxFuture<'_, Result<BatchJobResponse, CompletionError>> {
+        Box::pin(async move {
+            if requests.is_empty() {
+                return Err(CompletionError::Request("No requests to batch".into()));
+            }
+
+            let mut jsonl = String::new();
+            for (i, request) in requests.into_iter().enumerate() {
+                let line = BatchRequestInput {
+                    custom_id: format!("{BATCH_CUSTOM_ID_PREFIX}{i}"),
+                    method: BatchRequestInputMethod::POST,
+                    url: BatchEndpoint::V1ChatCompletions,
+                    body: Some(serde_json::to_value(self.create_request(request)?)?),
+                };
+                jsonl.push_str(&serde_json::to_string(&line)?);
+                jsonl.push('\n');
+            }
+
+            let file = CreateFileRequestArgs::default()
+                .file(FileInput::from_vec_u8(
+                    "batch.jsonl".to_owned(),
+                    jsonl.into_bytes(),
+                ))
+                .purpose(FilePurpose::Batch)
+                .build()?;
+            let file = self.client.files().create(file).await?;
+
+            let batch = BatchRequestArgs::default()
+                .input_file_id(file.id)
+                .endpoint(BatchEndpoint::V1ChatCompletions)
+                .completion_window(BatchCompletionWindow::W24H)
+                .build()?;
+            let batch = self.client.batches().create(batch).await?;
+            tracing::info!("Submitted OpenAI batch job {}", batch.id);
+
+            Ok(batch.into())
+        })
+    }
+
+    /// Fetches the current status of a batch job.
+    pub fn poll_batch(
+        &self,
+        job_id: &str,
+    ) -> BoxFuture<'_, Result<BatchJobResponse, CompletionError>> {
+        let job_id = job_id.to_owned();
+        Box::pin(async move { Ok(self.client.batches().retrieve(&job_id).await?.into()) })
+    }
+
+    /// Waits for a batch job to finish, checking every [`BATCH_POLL_INTERVAL`], and
+    /// returns the result of each request.
+    ///
+    /// Fails if the job itself failed, expired or was cancelled; requests that failed
+    /// individually are reported as errors in the results.
+    pub fn collect_batch_results(
+        &self,
+        job_id: &str,
+    ) -> BoxFuture<'_, Result<BatchResults, CompletionError>> {
+        let job_id = job_id.to_owned();
+        Box::pin(async move {
+            let job = loop {
+                let job = self.poll_batch(&job_id).await?;
+                if job.is_finished() {
+                    break job;
+                }
+                tracing::debug!("OpenAI batch job {job_id} is {:?}", job.status);
+                tokio::time::sleep(BATCH_POLL_INTERVAL).await;
+            };
+            if job.status != BatchStatus::Completed {
+                return Err(CompletionError::Provider(format!(
+                    "Batch job {job_id} ended with status {:?}",
+                    job.status
+                )));
+            }
+
+            let mut results = (0..job.total)
+                .map(|i| {
+                    Err(CompletionError::Response(format!(
+                        "No result for batch request {i}"
+                    )))
+                })
+                .collect::<Vec<_>>();
+            for file_id in [&job.output_file_id, &job.error_file_id]
+                .into_iter()
+                .flatten()
+            {
+                let content = self.client.files().content(file_id).await?;
+                for line in String::from_utf8_lossy(&content).lines() {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let output = serde_json::from_str::<BatchRequestOutput>(line)?;
+                    let Some(result) = output
+                        .custom_id
+                        .strip_prefix(BATCH_CUSTOM_ID_PREFIX)
+                        .and_then(|i| i.parse::<usize>().ok())
+                        .and_then(|i| results.get_mut(i))
+                    else {
+                        tracing::warn!("Unexpected batch result id: {}", output.custom_id);
+                        continue;
+                    };
+                    *result = batch_output_to_response(output);
+                }
+            }
+            Ok(results)
+        })
+    }
+}
+
+fn batch_output_to_response(
+    output: BatchRequestOutput,
+) -> Result<CompletionResponse<CreateChatCompletionResponse>, CompletionError> {
+    if let Some(error) = output.error {
+        return Err(CompletionError::Provider(format!(
+            "{}: {}",
+            error.code, error.message
+        )));
+    }
+    let response = output.response.ok_or_else(|| {
+        CompletionError::Response(format!("Empty batch result for {}", output.custom_id))
+    })?;
+    if response.status_code != 200 {
+        return Err(CompletionError::Provider(format!(
+            "Status {}: {}",
+            response.status_code, response.body
+        )));
+    }
+    response_from_json(response.body)
+}
+
+/// Converts a chat completions response body into a [`CompletionResponse`].
+///
+/// `async-openai` has no field for the `reasoning_content` (or `reasoning`) that
+/// OpenAI-compatible servers for reasoning models, such as DeepSeek, vLLM and
+/// OpenRouter, return next to `content`, so it is read from the raw JSON and placed
+/// before the other content as [`AssistantContent::Thinking`](llm::completion::AssistantContent::Thinking).
+/// OpenAI's own reasoning models don't expose their reasoning over this API.
+fn response_from_json(
+    body: serde_json::Value,
+) -> Result<CompletionResponse<CreateChatCompletionResponse>, CompletionError> {
+    let thinking = body["choices"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|choice| {
+            let message = &choice["message"];
+            message["reasoning_content"]
+                .as_str()
+                .or_else(|| message["reasoning"].as_str())
+        })
+        .filter(|reasoning| !reasoning.is_empty())
+        .map(llm::completion::AssistantContent::thinking)
+        .collect::<Vec<_>>();
+
+    let mut response: CompletionResponse<CreateChatCompletionResponse> =
+        serde_json::from_value::<CreateChatCompletionResponse>(body)?.try_into()?;
+    response.choice.splice(0..0, thinking);
+    Ok(response)
 }
 
 impl From<async_openai::error::OpenAIError> for CompletionError {
     fn from(error: async_openai::error::OpenAIError) -> Self {
         match error {
             async_openai::error::OpenAIError::Reqwest(e) => e.into(),
+            async_openai::error::OpenAIError::ApiError(api_error)
+                if api_error.code.as_deref() == Some("rate_limit_exceeded") =>
+            {
+                // async-openai doesn't expose the response headers, but the message says
+                // how long to wait, e.g. "Please try again in 1.5s."
+                CompletionError::RateLimited {
+                    retry_after: retry_after_from_message(&api_error.message),
+                }
+            },
             async_openai::error::OpenAIError::ApiError(api_error) => {
                 CompletionError::Provider(api_error.to_string())
             },
@@ -200,6 +521,36 @@ impl From<async_openai::error::OpenAIError> for CompletionError {
     }
 }
 
+/// Parse the wait time from an OpenAI rate limit message such as
+/// `"... Please try again in 6m0.5s. ..."`.
+fn retry_after_from_message(message: &str) -> Option<Duration> {
+    let (_, rest) = message.split_once("try again in ")?;
+    let duration = rest.split_whitespace().next()?.trim_end_matches('.');
+
+    let mut total = Duration::ZERO;
+    let mut rest = duration;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_len);
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        let seconds = match unit {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => return None,
+        };
+        total += Duration::try_from_secs_f64(number.parse::<f64>().ok()? * seconds).ok()?;
+        rest = tail;
+    }
+    Some(total)
+}
+
 impl TryFrom<llm::completion::Message> for Vec<ChatCompletionRequestMessage> {
     type Error = CompletionError;
 
@@ -353,6 +704,8 @@ impl TryFrom<llm::completion::Message> for Vec<ChatCompletionRequestMessage> {
                             llm::completion::AssistantContent::ToolCall(tool_call) => {
                                 tools.push(tool_call)
                             },
+                            // Reasoning is never sent back to the model
+                            llm::completion::AssistantContent::Thinking(_) => {},
                         }
                         (texts, tools)
                     },
@@ -468,42 +821,123 @@ impl From<llm::completion::Audio>
     }
 }
 
-impl From<async_openai::types::CreateChatCompletionResponse>
+/// Fails with [`CompletionError::Response`] if a choice has neither content nor tool
+/// calls, or a tool call's arguments are not valid JSON.
+impl TryFrom<async_openai::types::CreateChatCompletionResponse>
     for llm::CompletionResponse<async_openai::types::CreateChatCompletionResponse>
 {
-    fn from(response: async_openai::types::CreateChatCompletionResponse) -> Self {
-        let choices = response
-            .choices
-            .iter()
-            .flat_map(|choice| {
-                let content = choice.message.content.to_owned();
-                let tool_calls = choice.message.tool_calls.to_owned();
-                // OpenAI should always return content or tool_calls
-                if tool_calls.is_none() {
-                    let content =
-                        content.expect("OpenAI should always return content or tool_calls");
-                    vec![llm::completion::AssistantContent::text(content)]
-                } else {
-                    let tool_calls = tool_calls.expect("We just checked that it is not None");
-                    let tool_calls = tool_calls
-                        .iter()
-                        .map(|tool_call| {
-                            llm::completion::AssistantContent::tool_call(
-                                tool_call.id.clone(),
-                                tool_call.function.name.clone(),
-                                serde_json::from_str(&tool_call.function.arguments)
-                                    .expect("OpenAI return invalid json"),
-                            )
-                        })
-                        .collect::<Vec<_>>();
-                    tool_calls
-                }
-            })
-            .collect::<Vec<_>>();
+    type Error = CompletionError;
 
-        Self {
+    fn try_from(
+        response: async_openai::types::CreateChatCompletionResponse,
+    ) -> Result<Self, Self::Error> {
+        let mut choices = Vec::new();
+        for choice in &response.choices {
+            match (&choice.message.tool_calls, &choice.message.content) {
+                (Some(tool_calls), _) => {
+                    for tool_call in tool_calls {
+                        let arguments = serde_json::from_str(&tool_call.function.arguments)
+                            .map_err(|e| {
+                                CompletionError::Response(format!(
+                                    "Invalid arguments for tool call {}: {e}",
+                                    tool_call.function.name
+                                ))
+                            })?;
+                        choices.push(llm::completion::AssistantContent::tool_call(
+                            tool_call.id.clone(),
+                            tool_call.function.name.clone(),
+                            arguments,
+                        ));
+                    }
+                },
+                (None, Some(content)) => {
+                    choices.push(llm::completion::AssistantContent::text(content.clone()))
+                },
+                (None, None) => {
+                    return Err(CompletionError::Response(
+                        "OpenAI returned neither content nor tool calls".to_owned(),
+                    ));
+                },
+            }
+        }
+
+        Ok(Self {
             choice: choices,
             raw_response: response,
-        }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::completion::AssistantContent;
+
+    fn chat_response(message: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "deepseek-reasoner",
+            "choices": [{
+                "index": 0,
+                "message": message,
+                "finish_reason": "stop",
+                "logprobs": null
+            }]
+        })
+    }
+
+    #[test]
+    fn test_reasoning_content_becomes_thinking() {
+        let body = chat_response(serde_json::json!({
+            "role": "assistant",
+            "content": "42",
+            "reasoning_content": "6 times 7 is 42"
+        }));
+
+        let response = response_from_json(body).unwrap();
+        assert_eq!(
+            response.choice,
+            vec![
+                AssistantContent::thinking("6 times 7 is 42"),
+                AssistantContent::text("42"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_response_without_reasoning_has_no_thinking() {
+        let body = chat_response(serde_json::json!({
+            "role": "assistant",
+            "content": "42"
+        }));
+
+        let response = response_from_json(body).unwrap();
+        assert_eq!(response.choice, vec![AssistantContent::text("42")]);
+    }
+
+    #[test]
+    fn test_invalid_tool_arguments_are_an_error() {
+        let body = chat_response(serde_json::json!({
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "search", "arguments": "{\"query\": "}
+            }]
+        }));
+
+        let error = response_from_json(body).err().unwrap();
+        assert!(matches!(error, CompletionError::Response(message) if message.contains("search")));
+    }
+
+    #[test]
+    fn test_response_without_content_is_an_error() {
+        let body = chat_response(serde_json::json!({"role": "assistant", "content": null}));
+
+        let error = response_from_json(body).err().unwrap();
+        assert!(matches!(error, CompletionError::Response(_)));
     }
 }
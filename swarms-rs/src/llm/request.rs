@@ -12,15 +12,126 @@ pub struct CompletionRequest {
     pub max_tokens: Option<u64>,
 }
 
+impl CompletionRequest {
+    /// Starts building a request, the preferred way to construct one so that new fields
+    /// don't break existing code.
+    pub fn builder() -> CompletionRequestBuilder {
+        CompletionRequestBuilder::default()
+    }
+}
+
+/// Builder for [`CompletionRequest`].
+///
+/// The prompt defaults to an empty user message, which providers skip, and every other
+/// field to empty or unset.
+#[derive(Debug)]
+pub struct CompletionRequestBuilder {
+    request: CompletionRequest,
+}
+
+impl Default for CompletionRequestBuilder {
+    fn default() -> Self {
+        Self {
+            request: CompletionRequest {
+                prompt: Message::user(""),
+                system_prompt: None,
+                chat_history: Vec::new(),
+                tools: Vec::new(),
+                temperature: None,
+                max_tokens: None,
+            },
+        }
+    }
+}
+
+impl CompletionRequestBuilder {
+    pub fn prompt(mut self, prompt: impl Into<Message>) -> Self {
+        self.request.prompt = prompt.into();
+        self
+    }
+
+    pub fn system_prompt(mut self, system_prompt: impl Into<Option<String>>) -> Self {
+        self.request.system_prompt = system_prompt.into();
+        self
+    }
+
+    pub fn chat_history(mut self, chat_history: impl Into<Vec<Message>>) -> Self {
+        self.request.chat_history = chat_history.into();
+        self
+    }
+
+    pub fn tools(mut self, tools: Vec<ToolDefinition>) -> Self {
+        self.request.tools = tools;
+        self
+    }
+
+    pub fn temperature(mut self, temperature: impl Into<Option<f64>>) -> Self {
+        self.request.temperature = temperature.into();
+        self
+    }
+
+    pub fn max_tokens(mut self, max_tokens: impl Into<Option<u64>>) -> Self {
+        self.request.max_tokens = max_tokens.into();
+        self
+    }
+
+    pub fn build(self) -> CompletionRequest {
+        self.request
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ToolDefinition {
     pub name: String,
     pub description: String,
     pub parameters: serde_json::Value,
+    /// JSON schema of the tool's output, if the tool declares one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
 }
 
-#[derive(Debug)]
+impl ToolDefinition {
+    /// The description to send to providers without a native output schema field.
+    ///
+    /// When an output schema is declared it is appended to the description, so the model
+    /// still knows the shape of the result it will get back.
+    pub fn description_with_output_schema(&self) -> String {
+        match &self.output_schema {
+            Some(schema) => format!(
+                "{}\n\nReturns JSON matching this schema: {}",
+                self.description, schema
+            ),
+            None => self.description.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct CompletionResponse<T> {
     pub choice: Vec<AssistantContent>,
     pub raw_response: T,
 }
+
+impl<T> CompletionResponse<T> {
+    /// The model's reasoning, if the provider returned any, with multiple thinking
+    /// blocks joined by blank lines.
+    pub fn reasoning(&self) -> Option<String> {
+        let thinking = self
+            .choice
+            .iter()
+            .filter_map(|content| match content {
+                AssistantContent::Thinking(thinking) => Some(thinking.thinking.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        (!thinking.is_empty()).then(|| thinking.join("\n\n"))
+    }
+
+    /// The response as a [`Message::Assistant`], ready to append to the chat history of
+    /// the next request. Text, tool calls and thinking are kept in order.
+    pub fn to_assistant_message(&self) -> Message {
+        Message::Assistant {
+            content: self.choice.clone(),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Test doubles for unit-testing agents without calling a real provider.
+//!
+//! ```rust
+//! use swarms_rs::agent::SwarmsAgentBuilder;
+//! use swarms_rs::llm::testing::MockModel;
+//! use swarms_rs::structs::agent::Agent;
+//!
+//! # tokio_test::block_on(async {
+//! let model = MockModel::from_texts(["Paris"]);
+//! let agent = SwarmsAgentBuilder::new_with_model(model.clone())
+//!     .max_loops(1)
+//!     .build();
+//!
+//! let answer = agent.run("What is the capital of France?".to_owned()).await.unwrap();
+//! assert!(answer.contains("Paris"));
+//! assert_eq!(model.call_count(), 1);
+//! # });
+//! ```
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use futures::future::BoxFuture;
+
+use super::{
+    CompletionError, Model,
+    completion::AssistantContent,
+    request::{CompletionRequest, CompletionResponse},
+};
+
+type Handler =
+    dyn Fn(&CompletionRequest) -> Result<CompletionResponse<()>, CompletionError> + Send + Sync;
+
+#[derive(Clone)]
+enum Script {
+    Sequence(Arc<Mutex<VecDeque<CompletionResponse<()>>>>),
+    Handler(Arc<Handler>),
+}
+
+/// A [`Model`] that returns scripted responses.
+///
+/// Either a fixed sequence of responses, one per completion call, or a closure computing
+/// the response (or an error) from the request. Clones share the script and the call count, so a clone
+/// can be handed to an agent while the original is kept for assertions.
+#[derive(Clone)]
+pub struct MockModel {
+    script: Script,
+    calls: Arc<AtomicUsize>,
+    supports_tools: bool,
+}
+
+impl MockModel {
+    /// Returns `responses` in order, then fails with [`CompletionError::Other`] once they
+    /// are used up.
+    pub fn from_responses(responses: impl IntoIterator<Item = CompletionResponse<()>>) -> Self {
+        Self::with_script(Script::Sequence(Arc::new(Mutex::new(
+            responses.into_iter().collect(),
+        ))))
+    }
+
+    /// Like [`from_responses`](Self::from_responses), with a text-only response for each
+    /// of `texts`.
+    pub fn from_texts(texts: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self::from_responses(texts.into_iter().map(|text| CompletionResponse {
+            choice: vec![AssistantContent::text(text)],
+            raw_response: (),
+        }))
+    }
+
+    /// Answers every request with the response computed by `handler`.
+    pub fn from_fn<F>(handler: F) -> Self
+    where
+        F: Fn(&CompletionRequest) -> CompletionResponse<()> + Send + Sync + 'static,
+    {
+        Self::from_fallible_fn(move |request| Ok(handler(request)))
+    }
+
+    /// Like [`from_fn`](Self::from_fn), for handlers that can fail, e.g. to test how an
+    /// agent retries rate-limited or failed requests.
+    pub fn from_fallible_fn<F>(handler: F) -> Self
+    where
+        F: Fn(&CompletionRequest) -> Result<CompletionResponse<()>, CompletionError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self::with_script(Script::Handler(Arc::new(handler)))
+    }
+
+    fn with_script(script: Script) -> Self {
+        Self {
+            script,
+            calls: Arc::new(AtomicUsize::new(0)),
+            supports_tools: true,
+        }
+    }
+
+    /// Sets what [`Model::supports_tools`] reports, `true` by default.
+    pub fn set_supports_tools(mut self, supports_tools: bool) -> Self {
+        self.supports_tools = supports_tools;
+        self
+    }
+
+    /// Number of completion requests received so far.
+    pub fn call_count(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    /// Number of scripted responses not yet returned, `None` for a closure-backed model.
+    pub fn remaining(&self) -> Option<usize> {
+        match &self.script {
+            Script::Sequence(responses) => Some(responses.lock().unwrap().len()),
+            Script::Handler(_) => None,
+        }
+    }
+}
+
+impl Model for MockModel {
+    type RawCompletionResponse = ();
+
+    fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> BoxFuture<'_, Result<CompletionResponse<()>, CompletionError>> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        let response = match &self.script {
+            Script::Sequence(responses) => responses.lock().unwrap().pop_front().ok_or_else(|| {
+                CompletionError::Other(format!(
+                    "MockModel has no scripted response left for call {call}"
+                ))
+            }),
+            Script::Handler(handler) => handler(&request),
+        };
+        Box::pin(async move { response })
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.supports_tools
+    }
+}
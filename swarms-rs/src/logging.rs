@@ -1,7 +1,57 @@
+use dashmap::DashMap;
 use env_logger::{Builder, Target, WriteStyle};
 use log::{Level, LevelFilter};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::io::Write;
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Whether the `json-logging` feature is enabled.
+///
+/// The `log_*` macros check this constant rather than `cfg!`, because `cfg!` inside an
+/// exported macro would be evaluated against the calling crate's features.
+pub const JSON_LOGGING: bool = cfg!(feature = "json-logging");
+
+/// Re-exported so the `log_*` macros work in crates that don't depend on `tracing`.
+#[doc(hidden)]
+pub use tracing;
+
+/// A machine-parseable log event emitted by the `log_*` macros when the `json-logging`
+/// feature is enabled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogRecord {
+    pub level: String,
+    pub agent_name: Option<String>,
+    pub agent_id: Option<String>,
+    pub event_type: String,
+    pub message: String,
+    pub timestamp_ms: i64,
+}
+
+impl LogRecord {
+    pub fn new(
+        level: &str,
+        event_type: impl Into<String>,
+        agent_name: Option<String>,
+        agent_id: Option<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            level: level.to_uppercase(),
+            agent_name,
+            agent_id,
+            event_type: event_type.into(),
+            message: message.into(),
+            timestamp_ms: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("LogRecord always serializes")
+    }
+}
 
 /// Initialize the logging system with environment variable support
 /// Reads SWARMS_LOG_LEVEL environment variable and sets up colorful logging
@@ -57,16 +107,275 @@ pub fn init_logger() {
     log::info!("🚀 Swarms-RS logging initialized with level: {}", log_level);
 }
 
+/// Initialize JSON-lines logging for log aggregation (ELK, Datadog, ...)
+///
+/// Every event is written to stdout as a single JSON object with the timestamp, level,
+/// target, message and any structured fields (e.g. `agent` and `id` from [`log_agent!`]).
+/// Records emitted through the `log` crate are captured as well. The level is read from
+/// the SWARMS_LOG_LEVEL environment variable, as in [`init_logger`].
+pub fn init_json_logging() {
+    let log_level = env::var("SWARMS_LOG_LEVEL").unwrap_or_else(|_| "INFO".to_string());
+
+    let level_filter = log_level
+        .parse::<tracing_subscriber::filter::LevelFilter>()
+        .unwrap_or_else(|_| {
+            eprintln!(
+                "⚠️  Invalid SWARMS_LOG_LEVEL '{}', defaulting to INFO",
+                log_level
+            );
+            tracing_subscriber::filter::LevelFilter::INFO
+        });
+
+    tracing_subscriber::fmt()
+        .json()
+        .flatten_event(true)
+        .with_current_span(false)
+        .with_max_level(level_filter)
+        .init();
+
+    tracing::info!(max_level = %level_filter, "Swarms-RS JSON logging initialized");
+}
+
+static GLOBAL_METRICS: LazyLock<MetricsCollector> = LazyLock::new(MetricsCollector::default);
+
+/// A monotonically increasing count.
+#[derive(Debug, Default)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A value that can go up and down, e.g. the number of running agents.
+#[derive(Debug, Default)]
+pub struct Gauge(AtomicU64);
+
+impl Gauge {
+    pub fn set(&self, value: u64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    pub fn increment(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Decrements the gauge, saturating at zero.
+    pub fn decrement(&self) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+                Some(v.saturating_sub(1))
+            });
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Count, sum, min and max of recorded observations, e.g. latencies in milliseconds.
+#[derive(Debug)]
+pub struct Histogram {
+    count: AtomicU64,
+    sum: AtomicU64,
+    min: AtomicU64,
+    max: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0),
+            min: AtomicU64::new(u64::MAX),
+            max: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Histogram {
+    pub fn record(&self, value: u64) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+        self.min.fetch_min(value, Ordering::Relaxed);
+        self.max.fetch_max(value, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let count = self.count.load(Ordering::Relaxed);
+        let sum = self.sum.load(Ordering::Relaxed);
+        HistogramSnapshot {
+            count,
+            sum,
+            min: if count == 0 {
+                0
+            } else {
+                self.min.load(Ordering::Relaxed)
+            },
+            max: self.max.load(Ordering::Relaxed),
+            mean: if count == 0 {
+                0.0
+            } else {
+                sum as f64 / count as f64
+            },
+        }
+    }
+}
+
+/// Point-in-time values of a [`Histogram`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistogramSnapshot {
+    pub count: u64,
+    pub sum: u64,
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+}
+
+/// Point-in-time values of every metric in a [`MetricsCollector`], keyed by metric name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub counters: BTreeMap<String, u64>,
+    pub gauges: BTreeMap<String, u64>,
+    pub histograms: BTreeMap<String, HistogramSnapshot>,
+}
+
+/// In-process aggregation of named counters, gauges and histograms.
+///
+/// Metrics are recorded explicitly by the caller; the `log_*` macros only log.
+#[derive(Debug, Default)]
+pub struct MetricsCollector {
+    counters: DashMap<String, Counter>,
+    gauges: DashMap<String, Gauge>,
+    histograms: DashMap<String, Histogram>,
+}
+
+impl MetricsCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A process-wide collector for callers that don't want to thread their own.
+    pub fn global() -> &'static MetricsCollector {
+        &GLOBAL_METRICS
+    }
+
+    pub fn increment_counter(&self, name: &str) {
+        self.add_counter(name, 1);
+    }
+
+    pub fn add_counter(&self, name: &str, n: u64) {
+        if let Some(counter) = self.counters.get(name) {
+            counter.add(n);
+            return;
+        }
+        self.counters.entry(name.to_owned()).or_default().add(n);
+    }
+
+    pub fn set_gauge(&self, name: &str, value: u64) {
+        self.gauges.entry(name.to_owned()).or_default().set(value);
+    }
+
+    pub fn increment_gauge(&self, name: &str) {
+        self.gauges.entry(name.to_owned()).or_default().increment();
+    }
+
+    pub fn decrement_gauge(&self, name: &str) {
+        self.gauges.entry(name.to_owned()).or_default().decrement();
+    }
+
+    pub fn record_histogram(&self, name: &str, value: u64) {
+        if let Some(histogram) = self.histograms.get(name) {
+            histogram.record(value);
+            return;
+        }
+        self.histograms
+            .entry(name.to_owned())
+            .or_default()
+            .record(value);
+    }
+
+    /// All current metric values.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            counters: self
+                .counters
+                .iter()
+                .map(|entry| (entry.key().clone(), entry.value().get()))
+                .collect(),
+            gauges: self
+                .gauges
+                .iter()
+                .map(|entry| (entry.key().clone(), entry.value().get()))
+                .collect(),
+            histograms: self
+                .histograms
+                .iter()
+                .map(|entry| (entry.key().clone(), entry.value().snapshot()))
+                .collect(),
+        }
+    }
+
+    /// Removes every metric.
+    pub fn reset(&self) {
+        self.counters.clear();
+        self.gauges.clear();
+        self.histograms.clear();
+    }
+}
+
+/// Emit a [`LogRecord`] as a single `json` field on the `swarms` target.
+///
+/// Used by the other `log_*` macros when the `json-logging` feature is enabled.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log_json {
+    ($level:ident, $event_type:expr, $agent_name:expr, $agent_id:expr, $($arg:tt)*) => {
+        $crate::logging::tracing::$level!(
+            target: "swarms",
+            json = %$crate::logging::LogRecord::new(
+                stringify!($level),
+                $event_type,
+                $agent_name,
+                $agent_id,
+                format!($($arg)*),
+            )
+            .to_json()
+        );
+    };
+}
+
 /// Macro for logging with agent context
+///
+/// The agent name and id are attached as structured `agent` and `id` fields.
 #[macro_export]
 macro_rules! log_agent {
     ($level:ident, $agent_name:expr, $agent_id:expr, $($arg:tt)*) => {
-        log::$level!(
-            "[{}:{}] {}",
-            $agent_name,
-            $agent_id,
-            format!($($arg)*)
-        );
+        if $crate::logging::JSON_LOGGING {
+            $crate::log_json!(
+                $level,
+                "agent",
+                Some($agent_name.to_string()),
+                Some($agent_id.to_string()),
+                $($arg)*
+            );
+        } else {
+            $crate::logging::tracing::$level!(
+                agent = %$agent_name,
+                id = %$agent_id,
+                $($arg)*
+            );
+        }
     };
 }
 
@@ -74,13 +383,25 @@ macro_rules! log_agent {
 #[macro_export]
 macro_rules! log_task {
     ($level:ident, $agent_name:expr, $agent_id:expr, $task:expr, $($arg:tt)*) => {
-        log::$level!(
-            "[{}:{}] 📋 Task: {} - {}",
-            $agent_name,
-            $agent_id,
-            $task,
-            format!($($arg)*)
-        );
+        if $crate::logging::JSON_LOGGING {
+            $crate::log_json!(
+                $level,
+                "task",
+                Some($agent_name.to_string()),
+                Some($agent_id.to_string()),
+                "Task: {} - {}",
+                $task,
+                format_args!($($arg)*)
+            );
+        } else {
+            $crate::logging::tracing::$level!(
+                agent = %$agent_name,
+                id = %$agent_id,
+                task = %$task,
+                "📋 {}",
+                format_args!($($arg)*)
+            );
+        }
     };
 }
 
@@ -88,13 +409,25 @@ macro_rules! log_task {
 #[macro_export]
 macro_rules! log_tool {
     ($level:ident, $agent_name:expr, $agent_id:expr, $tool_name:expr, $($arg:tt)*) => {
-        log::$level!(
-            "[{}:{}] 🔧 Tool: {} - {}",
-            $agent_name,
-            $agent_id,
-            $tool_name,
-            format!($($arg)*)
-        );
+        if $crate::logging::JSON_LOGGING {
+            $crate::log_json!(
+                $level,
+                "tool",
+                Some($agent_name.to_string()),
+                Some($agent_id.to_string()),
+                "Tool: {} - {}",
+                $tool_name,
+                format_args!($($arg)*)
+            );
+        } else {
+            $crate::logging::tracing::$level!(
+                agent = %$agent_name,
+                id = %$agent_id,
+                tool = %$tool_name,
+                "🔧 {}",
+                format_args!($($arg)*)
+            );
+        }
     };
 }
 
@@ -102,13 +435,25 @@ macro_rules! log_tool {
 #[macro_export]
 macro_rules! log_workflow {
     ($level:ident, $agent_name:expr, $agent_id:expr, $workflow_name:expr, $($arg:tt)*) => {
-        log::$level!(
-            "[{}:{}] 🔄 Workflow: {} - {}",
-            $agent_name,
-            $agent_id,
-            $workflow_name,
-            format!($($arg)*)
-        );
+        if $crate::logging::JSON_LOGGING {
+            $crate::log_json!(
+                $level,
+                "workflow",
+                Some($agent_name.to_string()),
+                Some($agent_id.to_string()),
+                "Workflow: {} - {}",
+                $workflow_name,
+                format_args!($($arg)*)
+            );
+        } else {
+            $crate::logging::tracing::$level!(
+                agent = %$agent_name,
+                id = %$agent_id,
+                workflow = %$workflow_name,
+                "🔄 {}",
+                format_args!($($arg)*)
+            );
+        }
     };
 }
 
@@ -116,13 +461,25 @@ macro_rules! log_workflow {
 #[macro_export]
 macro_rules! log_memory {
     ($level:ident, $agent_name:expr, $agent_id:expr, $operation:expr, $($arg:tt)*) => {
-        log::$level!(
-            "[{}:{}] 🧠 Memory: {} - {}",
-            $agent_name,
-            $agent_id,
-            $operation,
-            format!($($arg)*)
-        );
+        if $crate::logging::JSON_LOGGING {
+            $crate::log_json!(
+                $level,
+                "memory",
+                Some($agent_name.to_string()),
+                Some($agent_id.to_string()),
+                "Memory: {} - {}",
+                $operation,
+                format_args!($($arg)*)
+            );
+        } else {
+            $crate::logging::tracing::$level!(
+                agent = %$agent_name,
+                id = %$agent_id,
+                operation = %$operation,
+                "🧠 {}",
+                format_args!($($arg)*)
+            );
+        }
     };
 }
 
@@ -130,13 +487,25 @@ macro_rules! log_memory {
 #[macro_export]
 macro_rules! log_llm {
     ($level:ident, $agent_name:expr, $agent_id:expr, $model:expr, $($arg:tt)*) => {
-        log::$level!(
-            "[{}:{}] 🤖 LLM: {} - {}",
-            $agent_name,
-            $agent_id,
-            $model,
-            format!($($arg)*)
-        );
+        if $crate::logging::JSON_LOGGING {
+            $crate::log_json!(
+                $level,
+                "llm",
+                Some($agent_name.to_string()),
+                Some($agent_id.to_string()),
+                "LLM: {} - {}",
+                $model,
+                format_args!($($arg)*)
+            );
+        } else {
+            $crate::logging::tracing::$level!(
+                agent = %$agent_name,
+                id = %$agent_id,
+                model = %$model,
+                "🤖 {}",
+                format_args!($($arg)*)
+            );
+        }
     };
 }
 
@@ -144,11 +513,23 @@ macro_rules! log_llm {
 #[macro_export]
 macro_rules! log_swarm {
     ($level:ident, $swarm_name:expr, $($arg:tt)*) => {
-        log::$level!(
-            "[🐝 Swarm: {}] {}",
-            $swarm_name,
-            format!($($arg)*)
-        );
+        if $crate::logging::JSON_LOGGING {
+            $crate::log_json!(
+                $level,
+                "swarm",
+                None,
+                None,
+                "Swarm: {} - {}",
+                $swarm_name,
+                format_args!($($arg)*)
+            );
+        } else {
+            $crate::logging::tracing::$level!(
+                swarm = %$swarm_name,
+                "🐝 {}",
+                format_args!($($arg)*)
+            );
+        }
     };
 }
 
@@ -156,13 +537,31 @@ macro_rules! log_swarm {
 #[macro_export]
 macro_rules! log_perf {
     ($level:ident, $component:expr, $metric:expr, $value:expr, $unit:expr) => {
-        log::$level!(
-            "📊 Performance: {} - {}: {} {}",
-            $component,
-            $metric,
-            $value,
-            $unit
-        );
+        if $crate::logging::JSON_LOGGING {
+            $crate::log_json!(
+                $level,
+                "performance",
+                None,
+                None,
+                "{} - {}: {} {}",
+                $component,
+                $metric,
+                $value,
+                $unit
+            );
+        } else {
+            $crate::logging::tracing::$level!(
+                component = %$component,
+                metric = %$metric,
+                value = %$value,
+                unit = %$unit,
+                "📊 Performance: {} - {}: {} {}",
+                $component,
+                $metric,
+                $value,
+                $unit
+            );
+        }
     };
 }
 
@@ -170,13 +569,27 @@ macro_rules! log_perf {
 #[macro_export]
 macro_rules! log_error_ctx {
     ($agent_name:expr, $agent_id:expr, $error:expr, $context:expr) => {
-        log::error!(
-            "[{}:{}] ❌ Error in {}: {}",
-            $agent_name,
-            $agent_id,
-            $context,
-            $error
-        );
+        if $crate::logging::JSON_LOGGING {
+            $crate::log_json!(
+                error,
+                "error",
+                Some($agent_name.to_string()),
+                Some($agent_id.to_string()),
+                "Error in {}: {}",
+                $context,
+                $error
+            );
+        } else {
+            $crate::logging::tracing::error!(
+                agent = %$agent_name,
+                id = %$agent_id,
+                context = %$context,
+                error = %$error,
+                "❌ Error in {}: {}",
+                $context,
+                $error
+            );
+        }
     };
 }
 
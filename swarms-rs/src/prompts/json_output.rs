@@ -0,0 +1,17 @@
+pub const JSON_OUTPUT_PROMPT: &str = r#"
+Respond ONLY with a single JSON value that matches the JSON schema below.
+
+### Rules
+- Do not wrap the JSON in Markdown code fences.
+- Do not add any explanation, comments or text before or after the JSON.
+- Include every required property, and no properties the schema does not allow.
+- Use `null` only where the schema permits it.
+
+### Schema
+"#;
+
+/// System prompt instructing the model to answer with JSON matching `schema`.
+pub fn json_output(schema: &serde_json::Value) -> String {
+    let schema = serde_json::to_string_pretty(schema).unwrap_or_else(|_| schema.to_string());
+    format!("{}\n{}", JSON_OUTPUT_PROMPT.trim(), schema)
+}
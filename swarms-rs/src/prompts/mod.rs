@@ -1,2 +1,13 @@
+pub mod json_output;
 pub mod multi_agent_collab_prompt;
 pub mod multi_agent_collab_prompt_new;
+pub mod react_agent;
+pub mod summarizer;
+pub mod task_evaluator;
+pub mod tool_protocol;
+
+pub use json_output::json_output;
+pub use react_agent::react_agent;
+pub use summarizer::summarizer;
+pub use task_evaluator::TASK_EVALUATOR_PROMPT;
+pub use tool_protocol::tool_protocol;
@@ -0,0 +1,11 @@
+/// Guidance on when and how to call the built-in `task_evaluator` tool.
+///
+/// Append it to a system prompt when the agent runs multiple loops with the task evaluator
+/// enabled, so the model ends the dialogue as soon as the task is done.
+pub const TASK_EVALUATOR_PROMPT: &str = r#"
+### Completing the task
+You have access to a `task_evaluator` tool that controls when the dialogue ends.
+- Call it with status "Complete" as soon as every user requirement is fully satisfied. Do not keep iterating on a finished answer.
+- Call it with status "Incomplete" when more work is needed, and give a clear, actionable context describing the next step. That context becomes your next prompt.
+- Never call `task_evaluator` twice in a row; do the work described in the context first.
+"#;
@@ -0,0 +1,29 @@
+use crate::llm::request::ToolDefinition;
+
+pub const TOOL_PROTOCOL_PROMPT: &str = r#"
+You can call the tools listed below. To call a tool, respond ONLY with a JSON object of the form:
+{"name": "<tool name>", "arguments": {<arguments matching the tool's parameters>}}
+
+To call several tools at once, respond with a JSON array of such objects.
+When you don't need a tool, answer with plain text and no JSON.
+
+### Tools
+"#;
+
+/// Instructions describing `tools` for models without native tool calling.
+///
+/// Appended to the system prompt when the prompt-based tool fallback is enabled, see
+/// [`SwarmsAgentBuilder::prompt_tool_fallback`](crate::agent::SwarmsAgentBuilder::prompt_tool_fallback).
+pub fn tool_protocol(tools: &[ToolDefinition]) -> String {
+    let tools = tools
+        .iter()
+        .map(|tool| {
+            format!(
+                "- {}: {}\n  Parameters: {}",
+                tool.name, tool.description, tool.parameters
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("{}\n{}", TOOL_PROTOCOL_PROMPT.trim(), tools)
+}
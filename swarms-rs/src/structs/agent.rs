@@ -1,15 +1,48 @@
 use crate::structs::persistence;
 use crate::structs::tool::ToolError;
-use futures::future::BoxFuture;
+use chrono::{DateTime, Utc};
+use futures::future::{self, BoxFuture};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::hash_map::DefaultHasher;
+use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 use tokio::sync::broadcast;
 
+/// Prompt sent at the start of every loop after the first, see
+/// [`AgentConfig::continuation_prompt_template`].
+pub const DEFAULT_CONTINUATION_PROMPT_TEMPLATE: &str = "Now, you are in loop {loop} of {max_loops}, The dialogue will terminate upon reaching maximum iteration count. You must:
+- Complete the user's task before termination
+- Optimize loop efficiency
+- Minimize resource consumption through minimal iterations
+
+You should consider to use tools if they can help, but only if they are relevant to the task and are necessary for the task.
+origin task:
+{task}";
+
+/// Heading of the system prompt reminder added every
+/// [`AgentConfig::system_prompt_refresh_interval`] loops.
+pub const SYSTEM_PROMPT_REFRESH_PREFIX: &str = "Reminder of your instructions:";
+
+/// Prompt sent after a text response when [`AgentConfig::tools_only`] is enabled.
+pub const TOOLS_ONLY_PROMPT: &str =
+    "You must use a tool to respond. Please select and call the appropriate tool.";
+
+/// Fill in the `{loop}` (1-based), `{max_loops}` and `{task}` placeholders of a loop prompt.
+pub fn render_loop_prompt(template: &str, loop_number: u32, max_loops: u32, task: &str) -> String {
+    template
+        .replace("{loop}", &loop_number.to_string())
+        .replace("{max_loops}", &max_loops.to_string())
+        .replace("{task}", task)
+}
+
 #[derive(Debug, Error)]
 pub enum AgentError {
     #[error("IO error: {0}")]
@@ -22,6 +55,11 @@ pub enum AgentError {
     PersistenceError(#[from] persistence::PersistenceError),
     #[error("Invalid save state path: {0}")]
     InvalidSaveStatePath(String),
+    #[error("Save state directory {path} is not writable: {source}")]
+    PersistenceSetup {
+        path: String,
+        source: std::io::Error,
+    },
     #[error("Completion error: {0}")]
     CompletionError(#[from] crate::llm::CompletionError),
     #[error("No choice found")]
@@ -30,12 +68,59 @@ pub enum AgentError {
     ToolNotFound(String),
     #[error("Tool error: {0}")]
     ToolError(#[from] ToolError),
+    #[error("Task is empty")]
+    EmptyTask,
+    #[error("No valid JSON found in response: {0}")]
+    JsonExtraction(String),
+    #[error(
+        "The model does not support tool calling, but {0} tools are registered; enable prompt_tool_fallback or remove the tools"
+    )]
+    ToolsUnsupported(usize),
+    #[error("Environment variable {name} has an invalid value: {value:?}")]
+    InvalidEnvVar { name: String, value: String },
+    #[error("Invalid config: {0}")]
+    InvalidConfig(#[from] AgentBuildError),
+    #[cfg(feature = "pdf-export")]
+    #[error("PDF export error: {0}")]
+    PdfExport(#[from] printpdf::Error),
 
     #[cfg(test)]
     #[error("Test error")]
     TestError(String),
 }
 
+/// A configuration mistake found by
+/// [`SwarmsAgentBuilder::try_build`](crate::agent::SwarmsAgentBuilder::try_build).
+#[derive(Debug, Error, PartialEq)]
+pub enum AgentBuildError {
+    #[error("Agent name is empty")]
+    EmptyName,
+    #[error("max_loops must be at least 1")]
+    ZeroMaxLoops,
+    #[error("retry_attempts must be at least 1, otherwise the model is never called")]
+    ZeroRetryAttempts,
+    #[error("max_tokens must be at least 1")]
+    ZeroMaxTokens,
+    #[error("Temperature {0} is outside of 0.0..=2.0")]
+    InvalidTemperature(f64),
+    #[error("max_response_chars must be at least 1")]
+    ZeroMaxResponseChars,
+    #[error("max_document_tokens must be at least 1")]
+    ZeroMaxDocumentTokens,
+    #[error("max_history_messages must be at least 1")]
+    ZeroMaxHistoryMessages,
+    #[error("system_prompt_refresh_interval must be at least 1")]
+    ZeroSystemPromptRefreshInterval,
+    #[error("tool_timeout must be greater than zero")]
+    ZeroToolTimeout,
+    #[error("Autosave is enabled but no save_state_dir is set")]
+    AutosaveWithoutSaveStateDir,
+    #[error("tools_only is enabled but the agent has no tools")]
+    ToolsOnlyWithoutTools,
+    #[error("Stop word {pattern:?} is not a valid regex: {error}")]
+    InvalidStopWordPattern { pattern: String, error: String },
+}
+
 #[derive(Clone)]
 pub struct AgentConfigBuilder {
     config: Arc<AgentConfig>,
@@ -93,6 +178,11 @@ impl AgentConfigBuilder {
         self
     }
 
+    pub fn retry_base_delay(mut self, retry_base_delay: Duration) -> Self {
+        Arc::make_mut(&mut self.config).retry_base_delay = Some(retry_base_delay);
+        self
+    }
+
     pub fn enable_rag_every_loop(mut self) -> Self {
         Arc::make_mut(&mut self.config).rag_every_loop = true;
         self
@@ -116,6 +206,11 @@ impl AgentConfigBuilder {
         self
     }
 
+    pub fn stop_word_matching(mut self, stop_word_matching: StopWordMatching) -> Self {
+        Arc::make_mut(&mut self.config).stop_word_matching = stop_word_matching;
+        self
+    }
+
     pub fn verbose(mut self, verbose: bool) -> Self {
         Arc::make_mut(&mut self.config).verbose = verbose;
         self
@@ -126,6 +221,76 @@ impl AgentConfigBuilder {
         self
     }
 
+    pub fn allow_empty_task(mut self, allow_empty_task: bool) -> Self {
+        Arc::make_mut(&mut self.config).allow_empty_task = allow_empty_task;
+        self
+    }
+
+    pub fn default_task(mut self, default_task: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.config).default_task = Some(default_task.into());
+        self
+    }
+
+    pub fn incremental_save(mut self, incremental_save: bool) -> Self {
+        Arc::make_mut(&mut self.config).incremental_save = incremental_save;
+        self
+    }
+
+    pub fn max_response_chars(mut self, max_response_chars: usize) -> Self {
+        Arc::make_mut(&mut self.config).max_response_chars = Some(max_response_chars);
+        self
+    }
+
+    pub fn max_document_tokens(mut self, max_document_tokens: usize) -> Self {
+        Arc::make_mut(&mut self.config).max_document_tokens = Some(max_document_tokens);
+        self
+    }
+
+    pub fn max_history_messages(mut self, max_history_messages: usize) -> Self {
+        Arc::make_mut(&mut self.config).max_history_messages = Some(max_history_messages);
+        self
+    }
+
+    pub fn prompt_tool_fallback(mut self, prompt_tool_fallback: bool) -> Self {
+        Arc::make_mut(&mut self.config).prompt_tool_fallback = prompt_tool_fallback;
+        self
+    }
+
+    pub fn system_prompt_refresh_interval(mut self, interval: u32) -> Self {
+        Arc::make_mut(&mut self.config).system_prompt_refresh_interval = Some(interval);
+        self
+    }
+
+    pub fn tool_timeout(mut self, timeout: Duration) -> Self {
+        Arc::make_mut(&mut self.config).tool_timeout = Some(timeout);
+        self
+    }
+
+    pub fn exit_command(mut self, exit_command: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.config).exit_command = exit_command.into();
+        self
+    }
+
+    pub fn stagnation_window(mut self, stagnation_window: usize) -> Self {
+        Arc::make_mut(&mut self.config).stagnation_window = stagnation_window;
+        self
+    }
+
+    pub fn first_loop_prompt(mut self, template: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.config).first_loop_prompt = Some(template.into());
+        self
+    }
+
+    pub fn continuation_prompt_template(mut self, template: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.config).continuation_prompt_template = Some(template.into());
+        self
+    }
+
+    pub fn tools_only(mut self, tools_only: bool) -> Self {
+        Arc::make_mut(&mut self.config).tools_only = tools_only;
+        self
+    }
+
     pub fn build(self) -> Arc<AgentConfig> {
         let config = &self.config;
         if config.verbose {
@@ -143,7 +308,11 @@ impl AgentConfigBuilder {
 }
 
 /// Agent configuration
+///
+/// Fields missing when deserializing take their [`Default`] value, so configs and
+/// snapshots saved by older versions still load.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AgentConfig {
     pub id: String,
     pub name: String,
@@ -156,18 +325,100 @@ pub struct AgentConfig {
     pub planning_prompt: Option<String>,
     pub autosave: bool,
     pub retry_attempts: u32,
+    /// Delay before the first retry of a failed attempt, doubled for every later retry
+    /// and jittered. No delay between attempts when unset
+    pub retry_base_delay: Option<Duration>,
     pub rag_every_loop: bool,
     pub save_state_dir: Option<String>,
+    /// Append new messages to an NDJSON state file instead of rewriting a JSON snapshot
+    pub incremental_save: bool,
     #[serde(with = "hashset_serde")]
     pub stop_words: HashSet<String>,
+    /// How `stop_words` are looked for in a response
+    pub stop_word_matching: StopWordMatching,
     pub task_evaluator_tool_enabled: bool,
     pub concurrent_tool_call_enabled: bool,
     pub verbose: bool,
     pub pretty_print_on: bool,
+    /// Run empty or whitespace-only tasks instead of rejecting them with `AgentError::EmptyTask`
+    pub allow_empty_task: bool,
+    /// Task substituted when an empty or whitespace-only task is submitted
+    pub default_task: Option<String>,
+    /// Model responses and tool results longer than this many characters are truncated
+    /// before they are added to memory
+    pub max_response_chars: Option<usize>,
+    /// Documents passed to `run_with_documents` are truncated to roughly this many
+    /// estimated tokens each
+    pub max_document_tokens: Option<usize>,
+    /// Chat history sent to the model is cut down to at most this many of its most
+    /// recent messages, keeping tool calls together with their results
+    pub max_history_messages: Option<usize>,
+    /// Prompt for the first loop, with the same placeholders as
+    /// `continuation_prompt_template`. When unset the first loop sends an empty prompt,
+    /// since the task is already part of the conversation history.
+    pub first_loop_prompt: Option<String>,
+    /// Prompt for every later loop, with `{loop}`, `{max_loops}` and `{task}`
+    /// placeholders. Defaults to [`DEFAULT_CONTINUATION_PROMPT_TEMPLATE`] when unset.
+    pub continuation_prompt_template: Option<String>,
+    /// Re-prompt the model whenever it answers with text instead of calling a tool
+    pub tools_only: bool,
+    /// Describe tools in the system prompt and parse tool calls from the response text
+    /// when the model does not support native tool calling
+    pub prompt_tool_fallback: bool,
+    /// Repeat the system prompt as a `Role::System` reminder every this many loops, to
+    /// counter instruction drift in long runs
+    pub system_prompt_refresh_interval: Option<u32>,
+    /// Tool calls that take longer than this fail with `ToolError::Timeout`
+    pub tool_timeout: Option<Duration>,
+    /// Message that ends `SwarmsAgent::run_interactive`
+    pub exit_command: String,
+    /// Stop the run once this many consecutive responses are identical, ignoring case
+    /// and whitespace. 0 disables the check
+    pub stagnation_window: usize,
     #[serde(skip)]
     pub response_cache: HashMap<String, String>,
 }
 
+/// How [`AgentConfig::stop_words`] are matched against a response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopWordMatching {
+    /// The stop word appears anywhere, so "done" also matches "abandoned"
+    Substring,
+    /// The stop word appears as a whole word. Only edges of the stop word that are
+    /// letters, digits or `_` need a boundary, so "<DONE>" still matches "x<DONE>y"
+    #[default]
+    WholeWord,
+    /// Each stop word is a regular expression that must match somewhere
+    Regex,
+}
+
+impl StopWordMatching {
+    /// Whether `stop_word` occurs in `response`. An invalid regex never matches;
+    /// [`AgentConfig::validate`] rejects those up front.
+    ///
+    /// A regex is compiled on every call, so agents use
+    /// [`AgentConfig::stop_word_regexes`], compiled once when they are built, instead.
+    pub fn matches(self, response: &str, stop_word: &str) -> bool {
+        match self {
+            Self::Substring => response.contains(stop_word),
+            Self::WholeWord => contains_whole_word(response, stop_word),
+            Self::Regex => Regex::new(stop_word).is_ok_and(|regex| regex.is_match(response)),
+        }
+    }
+}
+
+fn contains_whole_word(text: &str, word: &str) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let (Some(first), Some(last)) = (word.chars().next(), word.chars().next_back()) else {
+        return false;
+    };
+    text.match_indices(word).any(|(start, _)| {
+        let end = start + word.len();
+        (!is_word_char(first) || !text[..start].ends_with(is_word_char))
+            && (!is_word_char(last) || !text[end..].starts_with(is_word_char))
+    })
+}
+
 // Helper module for HashSet serialization
 mod hashset_serde {
     use super::*;
@@ -198,6 +449,130 @@ impl AgentConfig {
         }
     }
 
+    /// Preset for coding tasks: low temperature for precise output, a few loops to
+    /// iterate on the solution, and a plan covering the changes and how to verify them.
+    ///
+    /// Like the other presets, this is a starting point; override any field afterwards,
+    /// e.g. through the [`SwarmsAgentBuilder`](crate::agent::SwarmsAgentBuilder) methods:
+    ///
+    /// ```rust,no_run
+    /// use swarms_rs::agent::SwarmsAgentBuilder;
+    /// use swarms_rs::llm::provider::openai::OpenAI;
+    /// use swarms_rs::structs::agent::AgentConfig;
+    ///
+    /// let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env())
+    ///     .config(AgentConfig::coding())
+    ///     .agent_name("RustReviewer")
+    ///     .max_loops(5)
+    ///     .build();
+    /// ```
+    pub fn coding() -> Self {
+        Self {
+            temperature: 0.2,
+            max_loops: 3,
+            task_evaluator_tool_enabled: true,
+            plan_enabled: true,
+            planning_prompt: Some(
+                "Before writing any code, outline the files and functions you will change, \
+                 the edge cases to handle and how you will verify the result. Task:"
+                    .to_owned(),
+            ),
+            ..Self::default()
+        }
+    }
+
+    /// Preset for research tasks: moderate temperature, room for several loops of
+    /// gathering and cross-checking information, and a plan splitting the question up.
+    pub fn research() -> Self {
+        Self {
+            temperature: 0.4,
+            max_loops: 5,
+            task_evaluator_tool_enabled: true,
+            plan_enabled: true,
+            planning_prompt: Some(
+                "Break the question into sub-questions, list the sources or tools you will \
+                 consult for each, and how you will cross-check the findings. Question:"
+                    .to_owned(),
+            ),
+            ..Self::default()
+        }
+    }
+
+    /// Preset for creative writing: high temperature for varied output and a single
+    /// loop, without the task evaluator, after planning audience, tone and structure.
+    pub fn creative() -> Self {
+        Self {
+            temperature: 0.9,
+            max_loops: 1,
+            task_evaluator_tool_enabled: false,
+            plan_enabled: true,
+            planning_prompt: Some(
+                "Before writing, decide on the audience, tone, structure and key ideas of \
+                 the piece. Brief:"
+                    .to_owned(),
+            ),
+            ..Self::default()
+        }
+    }
+
+    /// Checks the settings that would otherwise only fail, or silently do nothing, once
+    /// the agent runs.
+    pub fn validate(&self) -> Result<(), AgentBuildError> {
+        if self.name.trim().is_empty() {
+            return Err(AgentBuildError::EmptyName);
+        }
+        if self.max_loops == 0 {
+            return Err(AgentBuildError::ZeroMaxLoops);
+        }
+        if self.retry_attempts == 0 {
+            return Err(AgentBuildError::ZeroRetryAttempts);
+        }
+        if self.max_tokens == 0 {
+            return Err(AgentBuildError::ZeroMaxTokens);
+        }
+        if !(0.0..=2.0).contains(&self.temperature) {
+            return Err(AgentBuildError::InvalidTemperature(self.temperature));
+        }
+        if self.max_response_chars == Some(0) {
+            return Err(AgentBuildError::ZeroMaxResponseChars);
+        }
+        if self.max_document_tokens == Some(0) {
+            return Err(AgentBuildError::ZeroMaxDocumentTokens);
+        }
+        if self.max_history_messages == Some(0) {
+            return Err(AgentBuildError::ZeroMaxHistoryMessages);
+        }
+        if self.system_prompt_refresh_interval == Some(0) {
+            return Err(AgentBuildError::ZeroSystemPromptRefreshInterval);
+        }
+        if self.tool_timeout == Some(Duration::ZERO) {
+            return Err(AgentBuildError::ZeroToolTimeout);
+        }
+        if self.autosave && self.save_state_dir.is_none() {
+            return Err(AgentBuildError::AutosaveWithoutSaveStateDir);
+        }
+        self.stop_word_regexes()?;
+        Ok(())
+    }
+
+    /// Compiles the stop words with [`StopWordMatching::Regex`], failing with
+    /// [`AgentBuildError::InvalidStopWordPattern`] on the first invalid pattern. Empty
+    /// with the other matching modes.
+    pub fn stop_word_regexes(&self) -> Result<Vec<Regex>, AgentBuildError> {
+        if self.stop_word_matching != StopWordMatching::Regex {
+            return Ok(Vec::new());
+        }
+        self.stop_words
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| AgentBuildError::InvalidStopWordPattern {
+                    pattern: pattern.clone(),
+                    error: e.to_string(),
+                })
+            })
+            .collect()
+    }
+
     // Add a method to compute a hash for caching
     pub fn compute_hash(&self, input: &str) -> u64 {
         let mut hasher = DefaultHasher::new();
@@ -214,6 +589,150 @@ impl AgentConfig {
     pub fn cache_response(&mut self, input: String, response: String) {
         self.response_cache.insert(input, response);
     }
+
+    /// Lists the settings that differ from `self` in `other`.
+    ///
+    /// The most commonly tuned settings get dedicated variants; any other field is
+    /// reported as [`ConfigChange::Other`] with its JSON values. The `id`, which is
+    /// unique per config, and the response cache are not compared.
+    pub fn diff(&self, other: &AgentConfig) -> Vec<ConfigChange> {
+        let mut changes = Vec::new();
+        if self.name != other.name {
+            changes.push(ConfigChange::Name {
+                from: self.name.clone(),
+                to: other.name.clone(),
+            });
+        }
+        if self.temperature != other.temperature {
+            changes.push(ConfigChange::Temperature {
+                from: self.temperature,
+                to: other.temperature,
+            });
+        }
+        if self.max_loops != other.max_loops {
+            changes.push(ConfigChange::MaxLoops {
+                from: self.max_loops,
+                to: other.max_loops,
+            });
+        }
+        if self.max_tokens != other.max_tokens {
+            changes.push(ConfigChange::MaxTokens {
+                from: self.max_tokens,
+                to: other.max_tokens,
+            });
+        }
+        if self.retry_attempts != other.retry_attempts {
+            changes.push(ConfigChange::RetryAttempts {
+                from: self.retry_attempts,
+                to: other.retry_attempts,
+            });
+        }
+        if self.stop_words != other.stop_words {
+            let mut added = other
+                .stop_words
+                .difference(&self.stop_words)
+                .cloned()
+                .collect::<Vec<_>>();
+            let mut removed = self
+                .stop_words
+                .difference(&other.stop_words)
+                .cloned()
+                .collect::<Vec<_>>();
+            added.sort();
+            removed.sort();
+            changes.push(ConfigChange::StopWords { added, removed });
+        }
+
+        const COMPARED: [&str; 7] = [
+            "id",
+            "name",
+            "temperature",
+            "max_loops",
+            "max_tokens",
+            "retry_attempts",
+            "stop_words",
+        ];
+        let to_map = |config: &AgentConfig| match serde_json::to_value(config) {
+            Ok(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        let (from, mut to) = (to_map(self), to_map(other));
+        let mut others = from
+            .into_iter()
+            .filter(|(field, _)| !COMPARED.contains(&field.as_str()))
+            .filter_map(|(field, from)| {
+                let to = to.remove(&field).unwrap_or_default();
+                (from != to).then_some(ConfigChange::Other { field, from, to })
+            })
+            .collect::<Vec<_>>();
+        others.sort_by(|a, b| a.to_string().cmp(&b.to_string()));
+        changes.extend(others);
+        changes
+    }
+
+    /// Re-reads the settings that can safely change between runs from the environment,
+    /// so a long-running agent picks up new values without a restart:
+    ///
+    /// | Variable | Field |
+    /// |---|---|
+    /// | `SWARMS_TEMPERATURE` | `temperature` |
+    /// | `SWARMS_MAX_LOOPS` | `max_loops` |
+    /// | `SWARMS_MAX_TOKENS` | `max_tokens` |
+    /// | `SWARMS_RETRY_ATTEMPTS` | `retry_attempts` |
+    /// | `SWARMS_RETRY_BASE_DELAY_MS` | `retry_base_delay` |
+    /// | `SWARMS_TOOL_TIMEOUT_MS` | `tool_timeout` |
+    /// | `SWARMS_STOP_WORDS` | `stop_words`, comma-separated |
+    /// | `SWARMS_VERBOSE` | `verbose` |
+    ///
+    /// Unset variables leave their field unchanged. If a value doesn't parse
+    /// ([`AgentError::InvalidEnvVar`]) or the result fails [`validate`](Self::validate)
+    /// ([`AgentError::InvalidConfig`]), the config is left untouched.
+    /// Returns the changes that were applied.
+    pub fn reload_from_env(&mut self) -> Result<Vec<ConfigChange>, AgentError> {
+        let mut reloaded = self.clone();
+        if let Some(temperature) = env_var("SWARMS_TEMPERATURE")? {
+            reloaded.temperature = temperature;
+        }
+        if let Some(max_loops) = env_var("SWARMS_MAX_LOOPS")? {
+            reloaded.max_loops = max_loops;
+        }
+        if let Some(max_tokens) = env_var("SWARMS_MAX_TOKENS")? {
+            reloaded.max_tokens = max_tokens;
+        }
+        if let Some(retry_attempts) = env_var("SWARMS_RETRY_ATTEMPTS")? {
+            reloaded.retry_attempts = retry_attempts;
+        }
+        if let Some(delay_ms) = env_var("SWARMS_RETRY_BASE_DELAY_MS")? {
+            reloaded.retry_base_delay = Some(Duration::from_millis(delay_ms));
+        }
+        if let Some(timeout_ms) = env_var("SWARMS_TOOL_TIMEOUT_MS")? {
+            reloaded.tool_timeout = Some(Duration::from_millis(timeout_ms));
+        }
+        if let Some(stop_words) = env_var::<String>("SWARMS_STOP_WORDS")? {
+            reloaded.stop_words = stop_words
+                .split(',')
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .map(str::to_owned)
+                .collect();
+        }
+        if let Some(verbose) = env_var("SWARMS_VERBOSE")? {
+            reloaded.verbose = verbose;
+        }
+
+        reloaded.validate()?;
+        let changes = self.diff(&reloaded);
+        *self = reloaded;
+        Ok(changes)
+    }
+
+    /// Captures this config with the current time, for saving and later comparison.
+    pub fn snapshot(&self) -> ConfigSnapshot {
+        ConfigSnapshot {
+            taken_at: Utc::now(),
+            config: self.clone(),
+        }
+    }
 }
 
 impl Default for AgentConfig {
@@ -232,13 +751,29 @@ impl Default for AgentConfig {
             planning_prompt: None,
             autosave: false,
             retry_attempts: 3,
+            retry_base_delay: None,
             rag_every_loop: false,
             save_state_dir: None,
+            incremental_save: false,
             stop_words: HashSet::with_capacity(16), // Pre-allocate capacity
+            stop_word_matching: StopWordMatching::default(),
             task_evaluator_tool_enabled: true,
             concurrent_tool_call_enabled: true,
-            verbose: false,                              // Default to verbose logging
-            pretty_print_on: false,                      // Default to no pretty printing
+            verbose: false,         // Default to verbose logging
+            pretty_print_on: false, // Default to no pretty printing
+            allow_empty_task: false,
+            default_task: None,
+            max_response_chars: None,
+            max_document_tokens: None,
+            max_history_messages: None,
+            first_loop_prompt: None,
+            continuation_prompt_template: None,
+            tools_only: false,
+            prompt_tool_fallback: false,
+            system_prompt_refresh_interval: None,
+            tool_timeout: None,
+            exit_command: "exit".to_owned(),
+            stagnation_window: 0,
             response_cache: HashMap::with_capacity(100), // Pre-allocate cache capacity
         };
 
@@ -250,6 +785,116 @@ impl Default for AgentConfig {
     }
 }
 
+/// Reads and parses an environment variable, `None` if it is unset.
+fn env_var<T: FromStr>(name: &str) -> Result<Option<T>, AgentError> {
+    let Ok(value) = std::env::var(name) else {
+        return Ok(None);
+    };
+    value
+        .trim()
+        .parse()
+        .map(Some)
+        .map_err(|_| AgentError::InvalidEnvVar {
+            name: name.to_owned(),
+            value,
+        })
+}
+
+/// A setting that differs between two [`AgentConfig`]s, see [`AgentConfig::diff`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConfigChange {
+    Name {
+        from: String,
+        to: String,
+    },
+    Temperature {
+        from: f64,
+        to: f64,
+    },
+    MaxLoops {
+        from: u32,
+        to: u32,
+    },
+    MaxTokens {
+        from: u64,
+        to: u64,
+    },
+    RetryAttempts {
+        from: u32,
+        to: u32,
+    },
+    StopWords {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    /// Any other field, with its JSON values
+    Other {
+        field: String,
+        from: serde_json::Value,
+        to: serde_json::Value,
+    },
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigChange::Name { from, to } => write!(f, "name: {from} -> {to}"),
+            ConfigChange::Temperature { from, to } => write!(f, "temperature: {from} -> {to}"),
+            ConfigChange::MaxLoops { from, to } => write!(f, "max_loops: {from} -> {to}"),
+            ConfigChange::MaxTokens { from, to } => write!(f, "max_tokens: {from} -> {to}"),
+            ConfigChange::RetryAttempts { from, to } => {
+                write!(f, "retry_attempts: {from} -> {to}")
+            },
+            ConfigChange::StopWords { added, removed } => {
+                write!(f, "stop_words: added {added:?}, removed {removed:?}")
+            },
+            ConfigChange::Other { field, from, to } => write!(f, "{field}: {from} -> {to}"),
+        }
+    }
+}
+
+/// An [`AgentConfig`] captured at a point in time, for auditing configuration drift
+/// between deployments.
+///
+/// ```rust,no_run
+/// use swarms_rs::structs::agent::{AgentConfig, ConfigSnapshot};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let previous = ConfigSnapshot::load("./config/agent.json").await?;
+/// let current = AgentConfig::builder().temperature(0.2).build().snapshot();
+/// for change in previous.diff(&current) {
+///     println!("{change}");
+/// }
+/// current.save("./config/agent.json").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub config: AgentConfig,
+}
+
+impl ConfigSnapshot {
+    /// Changes from this snapshot to `other`, see [`AgentConfig::diff`].
+    pub fn diff(&self, other: &ConfigSnapshot) -> Vec<ConfigChange> {
+        self.config.diff(&other.config)
+    }
+
+    /// Writes the snapshot to `path` as pretty-printed JSON.
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<(), AgentError> {
+        let data = serde_json::to_string_pretty(self)?;
+        persistence::save_to_file(data, path).await?;
+        Ok(())
+    }
+
+    /// Reads a snapshot written by [`save`](Self::save).
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, AgentError> {
+        let data = persistence::load_from_file(path).await?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+}
+
 pub trait Agent: Send + Sync {
     /// Runs the autonomous agent loop to complete the given task.
     fn run(&self, task: String) -> BoxFuture<Result<String, AgentError>>;
@@ -289,3 +934,97 @@ impl Clone for Box<dyn Agent> {
         self.clone_box()
     }
 }
+
+/// Chains two agents into one: `first` runs the task and `second` runs on its output.
+///
+/// The result is itself an [`Agent`], so pipes nest and can be used anywhere a single
+/// agent is expected, e.g. as one step of an
+/// [`AgentRearrange`](crate::structs::rearrange::AgentRearrange) flow. Its name is the
+/// two names joined with `|`.
+///
+/// ```rust,no_run
+/// use swarms_rs::agent::SwarmsAgentBuilder;
+/// use swarms_rs::llm::provider::openai::OpenAI;
+/// use swarms_rs::structs::agent::{Agent, pipe};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let drafter = SwarmsAgentBuilder::new_with_model(OpenAI::from_env())
+///     .agent_name("Drafter")
+///     .build();
+/// let editor = SwarmsAgentBuilder::new_with_model(OpenAI::from_env())
+///     .agent_name("Editor")
+///     .build();
+///
+/// let writer = pipe(Box::new(drafter), Box::new(editor));
+/// let article = writer.run("Write a post about Rust".to_owned()).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn pipe(first: Box<dyn Agent>, second: Box<dyn Agent>) -> Box<dyn Agent> {
+    Box::new(Pipe { first, second })
+}
+
+#[derive(Clone)]
+struct Pipe {
+    first: Box<dyn Agent>,
+    second: Box<dyn Agent>,
+}
+
+impl Agent for Pipe {
+    fn run(&self, task: String) -> BoxFuture<Result<String, AgentError>> {
+        Box::pin(async move {
+            let output = self.first.run(task).await?;
+            self.second.run(output).await
+        })
+    }
+
+    fn run_multiple_tasks(
+        &mut self,
+        tasks: Vec<String>,
+    ) -> BoxFuture<Result<Vec<String>, AgentError>> {
+        Box::pin(
+            async move { future::try_join_all(tasks.into_iter().map(|task| self.run(task))).await },
+        )
+    }
+
+    /// Plans with the first agent, which receives the task.
+    fn plan(&self, task: String) -> BoxFuture<Result<(), AgentError>> {
+        self.first.plan(task)
+    }
+
+    fn query_long_term_memory(&self, task: String) -> BoxFuture<Result<(), AgentError>> {
+        self.first.query_long_term_memory(task)
+    }
+
+    fn save_task_state(&self, task: String) -> BoxFuture<Result<(), AgentError>> {
+        Box::pin(async move {
+            self.first.save_task_state(task.clone()).await?;
+            self.second.save_task_state(task).await
+        })
+    }
+
+    /// Whether the second agent, which produces the final output, considers it complete.
+    fn is_response_complete(&self, response: String) -> bool {
+        self.second.is_response_complete(response)
+    }
+
+    fn id(&self) -> String {
+        format!("{}|{}", self.first.id(), self.second.id())
+    }
+
+    fn name(&self) -> String {
+        format!("{}|{}", self.first.name(), self.second.name())
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{}, then {}",
+            self.first.description(),
+            self.second.description()
+        )
+    }
+
+    fn clone_box(&self) -> Box<dyn Agent> {
+        Box::new(self.clone())
+    }
+}
@@ -3,7 +3,7 @@ use std::{
     path::Path,
 };
 
-use chrono::Local;
+use chrono::{Local, Utc};
 use dashmap::{DashMap, DashSet};
 use futures::{StreamExt, future::BoxFuture, stream};
 use thiserror::Error;
@@ -15,7 +15,7 @@ use crate::structs::{
     agent::{Agent, AgentError},
     conversation::{AgentConversation, AgentShortMemory, Role},
     persistence::{self, PersistenceError},
-    swarm::{MetadataSchema, Swarm, SwarmError},
+    swarm::{MetadataSchema, Swarm, SwarmError, SwarmMetadata},
     utils::run_agent_with_output_schema,
 };
 
@@ -101,7 +101,21 @@ impl ConcurrentWorkflow {
         &self,
         task: impl Into<String>,
     ) -> Result<AgentConversation, ConcurrentWorkflowError> {
+        let (conversation, _) = self.run_with_metadata(task).await?;
+        Ok(conversation)
+    }
+
+    /// Runs the workflow like [`run`](Self::run), also returning a [`SwarmMetadata`]
+    /// report of the run, which is saved to `metadata_output_dir` as well.
+    ///
+    /// The output hash covers every agent's output, ordered by agent name so it doesn't
+    /// depend on which agent finished first.
+    pub async fn run_with_metadata(
+        &self,
+        task: impl Into<String>,
+    ) -> Result<(AgentConversation, SwarmMetadata), ConcurrentWorkflowError> {
         let task = task.into();
+        let started_at = Utc::now();
 
         if task.is_empty() || self.agents.is_empty() {
             return Err(ConcurrentWorkflowError::EmptyTasksOrAgents);
@@ -171,7 +185,24 @@ impl ConcurrentWorkflow {
         persistence::save_to_file(metadata_data, &metadata_output_dir).await?;
 
         // Safety: we know that the task exists
-        Ok(self.conversation.0.get(&task).unwrap().clone())
+        let conversation = self.conversation.0.get(&task).unwrap().clone();
+
+        let mut outputs = metadata
+            .agents_output_schema
+            .iter()
+            .map(|output| format!("{}: {}", output.agent_name, output.output))
+            .collect::<Vec<_>>();
+        outputs.sort();
+        let run_metadata = SwarmMetadata::new(
+            started_at,
+            self.agents.iter().map(|agent| agent.name()).collect(),
+            &task,
+            &conversation.entries(),
+            &outputs.join("\n"),
+        );
+        run_metadata.save(metadata_path_dir).await?;
+
+        Ok((conversation, run_metadata))
     }
 
     /// Runs the workflow for a batch of tasks, executes agents concurrently for each task.
@@ -41,6 +41,75 @@ impl AgentShortMemory {
             .or_insert(AgentConversation::new(conversation_owner.into()));
         conversation.add(role, message.into())
     }
+
+    /// Add a message produced during the given (zero-based) loop of the agent.
+    pub fn add_in_loop(
+        &self,
+        task: impl Into<String>,
+        conversation_owner: impl Into<String>,
+        role: Role,
+        message: impl Into<String>,
+        loop_index: u32,
+    ) {
+        let mut conversation = self
+            .0
+            .entry(task.into())
+            .or_insert(AgentConversation::new(conversation_owner.into()));
+        conversation.add_in_loop(role, message.into(), Some(loop_index))
+    }
+
+    /// Copy the current state into a new, independent memory.
+    ///
+    /// Messages added to either memory afterwards are not visible in the other.
+    pub fn fork(&self) -> AgentShortMemory {
+        AgentShortMemory(
+            self.0
+                .iter()
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect(),
+        )
+    }
+
+    /// Add a deduplicated `Role::System` message to the conversation for `task`, see
+    /// [`AgentConversation::add_system_reminder`].
+    pub fn add_system_reminder(
+        &self,
+        task: impl Into<String>,
+        conversation_owner: impl Into<String>,
+        message: impl Into<String>,
+        loop_index: u32,
+    ) {
+        let conversation_owner = conversation_owner.into();
+        let mut conversation = self
+            .0
+            .entry(task.into())
+            .or_insert(AgentConversation::new(conversation_owner.clone()));
+        conversation.add_system_reminder(conversation_owner, message.into(), Some(loop_index))
+    }
+
+    /// The messages recorded for `task` together with their metadata, oldest first.
+    ///
+    /// Returns an empty list if nothing was recorded for the task.
+    pub fn entries_for(&self, task: &str) -> Vec<MemoryEntry> {
+        self.0
+            .get(task)
+            .map(|conversation| conversation.entries())
+            .unwrap_or_default()
+    }
+
+    /// Render the conversation for `task` as a Markdown document.
+    ///
+    /// Each message gets a `## User` / `## Assistant` / `## System` heading with the speaker and
+    /// timestamp, and tool calls are rendered with fenced code blocks for their
+    /// arguments and results.
+    pub fn to_markdown(&self, task: &str) -> String {
+        let mut markdown = format!("# Task\n\n{}\n\n", task.trim());
+        match self.0.get(task) {
+            Some(conversation) => markdown.push_str(&conversation.to_markdown()),
+            None => markdown.push_str("_No messages recorded._\n"),
+        }
+        markdown
+    }
 }
 
 impl Default for AgentShortMemory {
@@ -49,12 +118,15 @@ impl Default for AgentShortMemory {
     }
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AgentConversation {
     agent_name: String,
     save_filepath: Option<PathBuf>,
     pub history: Vec<Message>,
     max_messages: Option<usize>,
+    /// Metadata for each message in `history`, at the same index
+    #[serde(skip)]
+    metadata: Vec<EntryMetadata>,
 }
 
 impl AgentConversation {
@@ -64,7 +136,51 @@ impl AgentConversation {
             save_filepath: None,
             history: Vec::new(),
             max_messages: Some(1_000_000), // Default maximum messages
+            metadata: Vec::new(),
+        }
+    }
+
+    /// Load a conversation from a JSON file written by
+    /// [`Agent::save_task_state`](crate::structs::agent::Agent::save_task_state).
+    ///
+    /// The loaded conversation is detached from the file: adding messages doesn't
+    /// write them back.
+    pub async fn from_json_file(path: impl AsRef<Path>) -> Result<Self, PersistenceError> {
+        let data = persistence::load_from_file(path).await?;
+        let mut conversation: Self = serde_json::from_slice(&data)?;
+        conversation.save_filepath = None;
+        Ok(conversation)
+    }
+
+    /// Load the conversation an agent saved for `task` in its `save_state_dir`.
+    ///
+    /// `agent_name` and `agent_id` are the name and id of the agent that saved it.
+    ///
+    /// Reads the JSON snapshot if there is one, and otherwise the NDJSON file written
+    /// with `incremental_save`.
+    pub async fn from_saved_state(
+        dir: impl AsRef<Path>,
+        agent_name: &str,
+        agent_id: &str,
+        task: &str,
+    ) -> Result<Self, PersistenceError> {
+        let path = dir.as_ref().join(persistence::task_state_file_stem(
+            agent_name, agent_id, task,
+        ));
+
+        let json_path = path.with_extension("json");
+        if tokio::fs::try_exists(&json_path).await? {
+            return Self::from_json_file(json_path).await;
         }
+
+        let data = persistence::load_from_file(path.with_extension("ndjson")).await?;
+        let mut conversation = Self::new(agent_name.to_owned());
+        for line in String::from_utf8_lossy(&data).lines() {
+            if !line.trim().is_empty() {
+                conversation.history.push(serde_json::from_str(line)?);
+            }
+        }
+        Ok(conversation)
     }
 
     /// Create a new AgentConversation with a custom maximum message limit
@@ -74,20 +190,34 @@ impl AgentConversation {
             save_filepath: None,
             history: Vec::new(),
             max_messages,
+            metadata: Vec::new(),
         }
     }
 
     /// Add a message to the conversation history.
     pub fn add(&mut self, role: Role, message: String) {
+        self.add_in_loop(role, message, None)
+    }
+
+    /// Add a message to the conversation history, recording the agent loop that produced it.
+    pub fn add_in_loop(&mut self, role: Role, message: String, loop_index: Option<u32>) {
         // Only check message limit if it's set
         if let Some(max) = self.max_messages {
             if self.history.len() >= max {
                 // Remove oldest messages to make room for new ones
-                self.history.drain(0..(self.history.len() - max + 1));
+                let excess = self.history.len() - max + 1;
+                self.history.drain(0..excess);
+                self.metadata.drain(0..excess.min(self.metadata.len()));
             }
         }
 
         let timestamp = Local::now().timestamp_millis();
+        self.sync_metadata();
+        self.metadata.push(EntryMetadata {
+            timestamp,
+            estimated_tokens: estimate_tokens(&message),
+            loop_index,
+        });
         let message = Message {
             role,
             content: Content::Text(format!("Timestamp(millis): {timestamp} \n{message}")),
@@ -104,13 +234,39 @@ impl AgentConversation {
         }
     }
 
+    /// Add a `Role::System` message from `name`, first deleting earlier copies of the same
+    /// message, so a recurring reminder appears only once, at its latest position.
+    pub fn add_system_reminder(&mut self, name: String, message: String, loop_index: Option<u32>) {
+        self.sync_metadata();
+        for index in (0..self.history.len()).rev() {
+            let Message {
+                role,
+                content: Content::Text(text),
+            } = &self.history[index];
+            if matches!(role, Role::System(owner) if *owner == name)
+                && split_timestamp_millis(text).1 == message
+            {
+                self.delete(index);
+            }
+        }
+        self.add_in_loop(Role::System(name), message, loop_index);
+    }
+
     /// Delete a message from the conversation history.
     pub fn delete(&mut self, index: usize) {
         self.history.remove(index);
+        if index < self.metadata.len() {
+            self.metadata.remove(index);
+        }
     }
 
     /// Update a message in the conversation history.
     pub fn update(&mut self, index: usize, role: Role, content: Content) {
+        let Content::Text(text) = &content;
+        let estimated_tokens = estimate_tokens(split_timestamp_millis(text).1);
+        if let Some(metadata) = self.metadata.get_mut(index) {
+            metadata.estimated_tokens = estimated_tokens;
+        }
         self.history[index] = Message { role, content };
     }
 
@@ -130,6 +286,47 @@ impl AgentConversation {
     // Clear the conversation history.
     pub fn clear(&mut self) {
         self.history.clear();
+        self.metadata.clear();
+    }
+
+    /// The messages in the conversation together with their metadata, oldest first.
+    pub fn entries(&self) -> Vec<MemoryEntry> {
+        self.history
+            .iter()
+            .enumerate()
+            .map(|(index, message)| {
+                let Content::Text(text) = &message.content;
+                let (timestamp, content) = split_timestamp_millis(text);
+                let metadata = self
+                    .metadata
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_else(|| EntryMetadata::derive(timestamp, content));
+                MemoryEntry {
+                    role: message.role.clone(),
+                    content: content.to_owned(),
+                    timestamp: metadata.timestamp,
+                    estimated_tokens: metadata.estimated_tokens,
+                    loop_index: metadata.loop_index,
+                }
+            })
+            .collect()
+    }
+
+    /// Rebuild metadata that no longer lines up with `history`, e.g. after the history was
+    /// modified directly, from the message text alone.
+    fn sync_metadata(&mut self) {
+        if self.metadata.len() != self.history.len() {
+            self.metadata = self
+                .history
+                .iter()
+                .map(|message| {
+                    let Content::Text(text) = &message.content;
+                    let (timestamp, content) = split_timestamp_millis(text);
+                    EntryMetadata::derive(timestamp, content)
+                })
+                .collect();
+        }
     }
 
     pub fn to_json(&self) -> Result<String, ConversationError> {
@@ -171,6 +368,10 @@ impl AgentConversation {
                     let role = Role::User(role.replace("(User)", "").to_string());
                     let content = Content::Text(content.to_string());
                     Message { role, content }
+                } else if role.contains("(System)") {
+                    let role = Role::System(role.replace("(System)", "").to_string());
+                    let content = Content::Text(content.to_string());
+                    Message { role, content }
                 } else {
                     let role = Role::Assistant(role.replace("(Assistant)", "").to_string());
                     let content = Content::Text(content.to_string());
@@ -179,9 +380,61 @@ impl AgentConversation {
             })
             .collect();
         self.history = history;
+        self.metadata.clear();
         Ok(())
     }
 
+    /// Render the conversation history as Markdown, one `##` section per message.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        for message in &self.history {
+            let (heading, name) = match &message.role {
+                Role::User(name) => ("User", name),
+                Role::Assistant(name) => ("Assistant", name),
+                Role::System(name) => ("System", name),
+            };
+            let Content::Text(text) = &message.content;
+            let (timestamp, body) = split_timestamp(text);
+
+            markdown.push_str(&format!("## {heading}\n\n"));
+            match timestamp {
+                Some(timestamp) => markdown.push_str(&format!("_{name} · {timestamp}_\n\n")),
+                None => markdown.push_str(&format!("_{name}_\n\n")),
+            }
+            markdown.push_str(&body_to_markdown(body));
+            markdown.push('\n');
+        }
+        markdown
+    }
+
+    /// Shrink tool results longer than `max_len` characters to reduce the context size.
+    ///
+    /// Each such result in a `[Tool name]/[Tool args]/[Tool result]` message is replaced
+    /// with `summariser(result)`, prefixed with [`COMPRESSED_MARKER`]; the tool name and
+    /// args are kept. The summariser may truncate or summarise the result. Results that
+    /// were already compressed are left alone. Returns the number of messages changed.
+    pub fn compress_tool_results(
+        &mut self,
+        max_len: usize,
+        summariser: impl Fn(&str) -> String,
+    ) -> usize {
+        self.sync_metadata();
+        let mut changed = 0;
+        for index in 0..self.history.len() {
+            let Content::Text(text) = &self.history[index].content;
+            let body = split_timestamp_millis(text).1;
+            let Some(new_body) = compress_tool_blocks(body, max_len, &summariser) else {
+                continue;
+            };
+            let prefix = &text[..text.len() - body.len()];
+            let content = Content::Text(format!("{prefix}{new_body}"));
+            let role = self.history[index].role.clone();
+            self.update(index, role, content);
+            changed += 1;
+        }
+        changed
+    }
+
     /// Count the number of messages by role
     pub fn count_messages_by_role(&self) -> HashMap<String, usize> {
         let mut count = HashMap::new();
@@ -190,6 +443,134 @@ impl AgentConversation {
         }
         count
     }
+
+    /// Message count and size per role, keyed like [`count_messages_by_role`](Self::count_messages_by_role).
+    ///
+    /// Sizes are measured in characters and exclude the timestamp prefix.
+    pub fn role_statistics(&self) -> HashMap<String, RoleStats> {
+        let mut stats = HashMap::new();
+        self.accumulate_role_statistics(&mut stats);
+        stats
+    }
+
+    /// Add this conversation's messages to `stats`, keeping the averages up to date.
+    pub(crate) fn accumulate_role_statistics(&self, stats: &mut HashMap<String, RoleStats>) {
+        for message in &self.history {
+            let Content::Text(text) = &message.content;
+            let chars = split_timestamp_millis(text).1.chars().count();
+            let entry: &mut RoleStats = stats.entry(message.role.to_string()).or_default();
+            entry.message_count += 1;
+            entry.total_chars += chars;
+            entry.avg_chars = entry.total_chars as f64 / entry.message_count as f64;
+        }
+    }
+}
+
+/// Split the `Timestamp(millis): <ms> \n` prefix added by [`AgentConversation::add`]
+/// into a formatted local time and the remaining message.
+fn split_timestamp(text: &str) -> (Option<String>, &str) {
+    let (millis, body) = split_timestamp_millis(text);
+    let time = millis
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        });
+    (time, body)
+}
+
+/// Split the `Timestamp(millis): <ms> \n` prefix into the raw milliseconds and the message.
+fn split_timestamp_millis(text: &str) -> (Option<i64>, &str) {
+    let parsed = text
+        .strip_prefix("Timestamp(millis): ")
+        .and_then(|rest| rest.split_once(" \n"))
+        .and_then(|(millis, body)| Some((millis.parse().ok()?, body)));
+    match parsed {
+        Some((millis, body)) => (Some(millis), body),
+        None => (None, text),
+    }
+}
+
+/// Rough token count of `text`, assuming about four characters per token.
+///
+/// This is only an estimate for budgeting and analytics; providers report exact usage.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+const NAME: &str = "[Tool name]: ";
+const ARGS: &str = "\n[Tool args]: ";
+const RESULT: &str = "\n[Tool result]: ";
+
+/// Prefix of tool results replaced by [`AgentConversation::compress_tool_results`]
+pub const COMPRESSED_MARKER: &str = "[compressed]";
+
+/// Replace the result of every `[Tool name]/[Tool args]/[Tool result]` block in `body`
+/// longer than `max_len` characters, or `None` if no result was replaced.
+fn compress_tool_blocks(
+    body: &str,
+    max_len: usize,
+    summariser: &impl Fn(&str) -> String,
+) -> Option<String> {
+    if !body.starts_with(NAME) {
+        return None;
+    }
+
+    let mut compressed = false;
+    let mut new_body = String::with_capacity(body.len());
+    for block in body.split(NAME).skip(1) {
+        new_body.push_str(NAME);
+        let parsed = block.split_once(ARGS).and_then(|(name, rest)| {
+            let (args, result) = rest.split_once(RESULT)?;
+            Some((name, args, result))
+        });
+        match parsed {
+            Some((name, args, result)) => {
+                let trimmed = result.trim_end();
+                if trimmed.chars().count() > max_len && !trimmed.starts_with(COMPRESSED_MARKER) {
+                    compressed = true;
+                    new_body.push_str(&format!(
+                        "{name}{ARGS}{args}{RESULT}{COMPRESSED_MARKER} {}{}",
+                        summariser(trimmed),
+                        &result[trimmed.len()..]
+                    ));
+                } else {
+                    new_body.push_str(block);
+                }
+            },
+            None => new_body.push_str(block),
+        }
+    }
+    compressed.then_some(new_body)
+}
+
+/// Render a message body, turning `[Tool name]/[Tool args]/[Tool result]` blocks
+/// into fenced code blocks and leaving anything else as plain text.
+fn body_to_markdown(body: &str) -> String {
+    if !body.starts_with(NAME) {
+        return format!("{}\n", body.trim_end());
+    }
+
+    let mut markdown = String::new();
+    for block in body.split(NAME).filter(|block| !block.trim().is_empty()) {
+        let parsed = block.split_once(ARGS).and_then(|(name, rest)| {
+            let (args, result) = rest.split_once(RESULT)?;
+            Some((name, args, result))
+        });
+        match parsed {
+            Some((name, args, result)) => markdown.push_str(&format!(
+                "**Tool call:** `{}`\n\n```json\n{}\n```\n\n**Result:**\n\n```\n{}\n```\n\n",
+                name.trim(),
+                args.trim(),
+                result.trim()
+            )),
+            None => markdown.push_str(&format!("{NAME}{}\n\n", block.trim_end())),
+        }
+    }
+    markdown.truncate(markdown.trim_end().len());
+    markdown.push('\n');
+    markdown
 }
 
 impl Display for AgentConversation {
@@ -207,10 +588,54 @@ pub struct Message {
     pub content: Content,
 }
 
+/// Per-role message statistics, see [`AgentConversation::role_statistics`].
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct RoleStats {
+    pub message_count: usize,
+    pub total_chars: usize,
+    pub avg_chars: f64,
+}
+
+/// A message in an [`AgentConversation`] together with its metadata.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MemoryEntry {
+    pub role: Role,
+    /// Message text, without the timestamp prefix
+    pub content: String,
+    /// Unix timestamp in milliseconds at which the message was added
+    pub timestamp: i64,
+    /// Estimated token count of `content`, see [`estimate_tokens`]
+    pub estimated_tokens: usize,
+    /// Zero-based agent loop that produced the message, `None` for messages added outside
+    /// the loop (the task itself, plans, imported history)
+    pub loop_index: Option<u32>,
+}
+
+#[derive(Clone, Debug)]
+struct EntryMetadata {
+    timestamp: i64,
+    estimated_tokens: usize,
+    loop_index: Option<u32>,
+}
+
+impl EntryMetadata {
+    /// Metadata for a message whose loop is unknown.
+    fn derive(timestamp: Option<i64>, content: &str) -> Self {
+        Self {
+            timestamp: timestamp.unwrap_or_default(),
+            estimated_tokens: estimate_tokens(content),
+            loop_index: None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Role {
     User(String),
     Assistant(String),
+    /// Notes about the conversation itself, e.g. a persona change, recorded on behalf of
+    /// the named agent
+    System(String),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -223,6 +648,7 @@ impl Display for Role {
         match self {
             Role::User(name) => write!(f, "{}(User)", name),
             Role::Assistant(name) => write!(f, "{}(Assistant)", name),
+            Role::System(name) => write!(f, "{}(System)", name),
         }
     }
 }
@@ -283,6 +709,11 @@ impl From<&AgentConversation> for Vec<crate::llm::completion::Message> {
                 Role::Assistant(name) => {
                     crate::llm::completion::Message::assistant(format!("{}: {}", name, msg.content))
                 },
+                // Completion requests only carry a single system prompt, so notes from the
+                // middle of a conversation are passed along as user messages
+                Role::System(_) => {
+                    crate::llm::completion::Message::user(format!("System: {}", msg.content))
+                },
             })
             .collect()
     }
@@ -16,6 +16,13 @@ use tokio::sync::Mutex;
 
 use crate::structs::agent::Agent;
 
+/// Default limit on the number of paths found by [`DAGWorkflow::find_execution_paths`]
+pub const DEFAULT_MAX_EXECUTION_PATHS: usize = 10_000;
+
+/// Default limit on the number of agents in a path found by
+/// [`DAGWorkflow::find_execution_paths`]
+pub const DEFAULT_MAX_PATH_DEPTH: usize = 1_000;
+
 /// The main graph-based workflow structure
 pub struct DAGWorkflow {
     pub name: String,
@@ -423,6 +430,122 @@ impl DAGWorkflow {
         structure
     }
 
+    /// Render the workflow as ASCII box-and-arrow art, e.g. for terminal logs.
+    ///
+    /// Nodes are placed in layers by their longest distance from a root (a simple
+    /// Sugiyama-style layering) and ordered within a layer by the average position of
+    /// their parents to reduce crossings. Each layer is drawn as a row of boxes followed
+    /// by the outgoing edges of its nodes.
+    ///
+    /// ```text
+    /// +---+   +---+
+    /// | a |   | b |
+    /// +---+   +---+
+    ///   a -> c
+    ///   b -> c
+    /// +---+
+    /// | c |
+    /// +---+
+    /// ```
+    pub fn visualise_ascii(&self) -> String {
+        if self.workflow.node_count() == 0 {
+            return String::new();
+        }
+
+        // Layer assignment: longest path from any root. The graph is acyclic, so a
+        // topological order always exists.
+        let order = petgraph::algo::toposort(&self.workflow, None)
+            .unwrap_or_else(|_| self.workflow.node_indices().collect());
+        let mut layer_of: HashMap<NodeIndex, usize> = HashMap::new();
+        for &node_idx in &order {
+            let layer = self
+                .workflow
+                .neighbors_directed(node_idx, Direction::Incoming)
+                .filter_map(|parent| layer_of.get(&parent))
+                .map(|layer| layer + 1)
+                .max()
+                .unwrap_or(0);
+            layer_of.insert(node_idx, layer);
+        }
+
+        let depth = layer_of.values().copied().max().unwrap_or(0) + 1;
+        let mut layers: Vec<Vec<NodeIndex>> = vec![Vec::new(); depth];
+        for (&node_idx, &layer) in &layer_of {
+            layers[layer].push(node_idx);
+        }
+
+        // Order each layer by the barycenter of its parents, breaking ties by name
+        let mut position: HashMap<NodeIndex, f64> = HashMap::new();
+        for layer in layers.iter_mut() {
+            let barycenter = |node_idx: NodeIndex| {
+                let parents: Vec<f64> = self
+                    .workflow
+                    .neighbors_directed(node_idx, Direction::Incoming)
+                    .filter_map(|parent| position.get(&parent).copied())
+                    .collect();
+                if parents.is_empty() {
+                    0.0
+                } else {
+                    parents.iter().sum::<f64>() / parents.len() as f64
+                }
+            };
+            layer.sort_by(|a, b| {
+                barycenter(*a)
+                    .total_cmp(&barycenter(*b))
+                    .then_with(|| self.workflow[*a].name.cmp(&self.workflow[*b].name))
+            });
+            for (i, node_idx) in layer.iter().enumerate() {
+                position.insert(*node_idx, i as f64);
+            }
+        }
+
+        let mut output = String::new();
+        for layer in &layers {
+            let names: Vec<&str> = layer
+                .iter()
+                .map(|node_idx| self.workflow[*node_idx].name.as_str())
+                .collect();
+            let border = names
+                .iter()
+                .map(|name| format!("+{}+", "-".repeat(name.chars().count() + 2)))
+                .collect::<Vec<_>>()
+                .join("   ");
+            let labels = names
+                .iter()
+                .map(|name| format!("| {} |", name))
+                .collect::<Vec<_>>()
+                .join("   ");
+
+            output.push_str(&format!("{}\n{}\n{}\n", border, labels, border));
+
+            for &node_idx in layer {
+                let mut targets: Vec<NodeIndex> = self
+                    .workflow
+                    .neighbors_directed(node_idx, Direction::Outgoing)
+                    .collect();
+                if targets.is_empty() {
+                    continue;
+                }
+                targets.sort_by(|a, b| {
+                    layer_of[a]
+                        .cmp(&layer_of[b])
+                        .then_with(|| position[a].total_cmp(&position[b]))
+                });
+                let targets = targets
+                    .iter()
+                    .map(|target| self.workflow[*target].name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                output.push_str(&format!(
+                    "  {} -> {}\n",
+                    self.workflow[node_idx].name, targets
+                ));
+            }
+        }
+
+        output
+    }
+
     /// Export the workflow to a format that can be visualized (e.g., DOT format for Graphviz)
     pub fn export_workflow_dot(&self) -> String {
         // TODO: can use petgraph's built-in dot
@@ -440,7 +563,55 @@ impl DAGWorkflow {
             }
         }
 
-        // Add edges
+        self.push_dot_edges(&mut dot);
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Export the workflow as DOT with the results of a run overlaid.
+    ///
+    /// Nodes that succeeded are filled green and nodes that failed red, with the (truncated)
+    /// output or error in their label. Nodes without a result, i.e. that did not run, are
+    /// grey. `results` is keyed by agent name, as returned by
+    /// [`execute_workflow`](Self::execute_workflow) once collected into a `HashMap`.
+    pub fn export_execution_dot<E: std::fmt::Display>(
+        &self,
+        results: &HashMap<String, Result<String, E>>,
+    ) -> String {
+        const MAX_LABEL_OUTPUT_CHARS: usize = 40;
+
+        let mut dot = String::from("digraph {\n");
+        dot.push_str("    node [style=filled];\n");
+
+        for node_idx in self.workflow.node_indices() {
+            let Some(node) = self.workflow.node_weight(node_idx) else {
+                continue;
+            };
+            let (color, detail) = match results.get(&node.name) {
+                Some(Ok(output)) => ("palegreen", output.clone()),
+                Some(Err(e)) => ("lightcoral", format!("error: {e}")),
+                None => ("lightgrey", "not run".to_owned()),
+            };
+            let mut chars = detail.chars();
+            let mut detail: String = chars.by_ref().take(MAX_LABEL_OUTPUT_CHARS).collect();
+            if chars.next().is_some() {
+                detail.push('…');
+            }
+            dot.push_str(&format!(
+                "    \"{}\" [label=\"{}\\n{}\", fillcolor={}];\n",
+                escape_dot(&node.name),
+                escape_dot(&node.name),
+                escape_dot(&detail),
+                color
+            ));
+        }
+
+        self.push_dot_edges(&mut dot);
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn push_dot_edges(&self, dot: &mut String) {
         for edge in self.workflow.edge_indices() {
             if let Some((source, target)) = self.workflow.edge_endpoints(edge) {
                 if let (Some(source_node), Some(target_node)) = (
@@ -454,15 +625,98 @@ impl DAGWorkflow {
                 }
             }
         }
+    }
 
-        dot.push_str("}\n");
-        dot
+    /// Export the workflow as a Mermaid flowchart, which GitHub renders in Markdown.
+    ///
+    /// Every agent is drawn as a rectangle, see
+    /// [`export_mermaid_with_shapes`](Self::export_mermaid_with_shapes) to change that.
+    pub fn export_mermaid(&self) -> String {
+        self.export_mermaid_with_shapes(|_| MermaidShape::Rectangle)
+    }
+
+    /// Export the workflow as a Mermaid flowchart, drawing each agent with the shape
+    /// returned by `shape`, e.g. a cylinder for agents whose description mentions tools:
+    ///
+    /// ```rust,ignore
+    /// let mermaid = workflow.export_mermaid_with_shapes(|agent| {
+    ///     if agent.description().contains("tool") {
+    ///         MermaidShape::Cylinder
+    ///     } else {
+    ///         MermaidShape::Rectangle
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// Node ids are the agent names with characters Mermaid does not accept in ids
+    /// replaced by `_`, and the names themselves are used as labels.
+    pub fn export_mermaid_with_shapes(&self, shape: impl Fn(&dyn Agent) -> MermaidShape) -> String {
+        let mut mermaid = String::from("graph TD\n");
+
+        let mut ids = HashMap::new();
+        for node_idx in self.workflow.node_indices() {
+            let Some(node) = self.workflow.node_weight(node_idx) else {
+                continue;
+            };
+            let mut id: String = node
+                .name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            if ids.values().any(|taken| *taken == id) {
+                id.push_str(&format!("_{}", node_idx.index()));
+            }
+
+            let shape = self
+                .agents
+                .get(&node.name)
+                .map_or(MermaidShape::Rectangle, |agent| shape(agent.as_ref()));
+            let (open, close) = shape.delimiters();
+            mermaid.push_str(&format!(
+                "  {id}{open}\"{}\"{close}\n",
+                node.name.replace('"', "#quot;")
+            ));
+            ids.insert(node_idx, id);
+        }
+
+        for edge in self.workflow.edge_indices() {
+            if let Some((source, target)) = self.workflow.edge_endpoints(edge)
+                && let (Some(source_id), Some(target_id)) = (ids.get(&source), ids.get(&target))
+            {
+                mermaid.push_str(&format!("  {source_id} --> {target_id}\n"));
+            }
+        }
+
+        mermaid
     }
 
     /// Helper method to find all possible execution paths
+    ///
+    /// The number of paths grows exponentially with the density of the graph, so the
+    /// search gives up with [`GraphWorkflowError::PathLimitExceeded`] after
+    /// [`DEFAULT_MAX_EXECUTION_PATHS`] paths or on a path longer than
+    /// [`DEFAULT_MAX_PATH_DEPTH`] agents. Use
+    /// [`find_execution_paths_with_limits`](Self::find_execution_paths_with_limits) for
+    /// other limits.
     pub fn find_execution_paths(
         &self,
         start_agent: &str,
+    ) -> Result<Vec<Vec<String>>, GraphWorkflowError> {
+        self.find_execution_paths_with_limits(
+            start_agent,
+            DEFAULT_MAX_EXECUTION_PATHS,
+            DEFAULT_MAX_PATH_DEPTH,
+        )
+    }
+
+    /// Like [`find_execution_paths`](Self::find_execution_paths), failing with
+    /// [`GraphWorkflowError::PathLimitExceeded`] once more than `max_paths` paths are
+    /// found or a path is longer than `max_depth` agents.
+    pub fn find_execution_paths_with_limits(
+        &self,
+        start_agent: &str,
+        max_paths: usize,
+        max_depth: usize,
     ) -> Result<Vec<Vec<String>>, GraphWorkflowError> {
         let start_idx = self.name_to_node.get(start_agent).ok_or_else(|| {
             GraphWorkflowError::AgentNotFound(format!("Start agent '{}' not found", start_agent))
@@ -471,7 +725,13 @@ impl DAGWorkflow {
         let mut paths = Vec::new();
         let mut current_path = Vec::new();
 
-        self.dfs_paths(*start_idx, &mut current_path, &mut paths);
+        self.dfs_paths(
+            *start_idx,
+            &mut current_path,
+            &mut paths,
+            max_paths,
+            max_depth,
+        )?;
 
         Ok(paths)
     }
@@ -481,8 +741,16 @@ impl DAGWorkflow {
         node_idx: NodeIndex,
         current_path: &mut Vec<String>,
         all_paths: &mut Vec<Vec<String>>,
-    ) {
+        max_paths: usize,
+        max_depth: usize,
+    ) -> Result<(), GraphWorkflowError> {
         if let Some(node) = self.workflow.node_weight(node_idx) {
+            if current_path.len() >= max_depth {
+                return Err(GraphWorkflowError::PathLimitExceeded(format!(
+                    "a path is longer than {max_depth} agents"
+                )));
+            }
+
             // Add current node to path
             current_path.push(node.name.clone());
 
@@ -494,6 +762,11 @@ impl DAGWorkflow {
                 > 0;
 
             if !has_outgoing {
+                if all_paths.len() >= max_paths {
+                    return Err(GraphWorkflowError::PathLimitExceeded(format!(
+                        "more than {max_paths} paths"
+                    )));
+                }
                 // We've reached a leaf node, save this path
                 all_paths.push(current_path.clone());
             } else {
@@ -502,13 +775,14 @@ impl DAGWorkflow {
                     .workflow
                     .neighbors_directed(node_idx, Direction::Outgoing)
                 {
-                    self.dfs_paths(neighbor, current_path, all_paths);
+                    self.dfs_paths(neighbor, current_path, all_paths, max_paths, max_depth)?;
                 }
             }
 
             // Backtrack
             current_path.pop();
         }
+        Ok(())
     }
 
     /// Detect potential deadlocks in the workflow. Whether there will actually be a deadlock depends on the flow at execution time.
@@ -584,6 +858,50 @@ pub struct AgentNode {
     pub last_result: Mutex<Option<Result<String, GraphWorkflowError>>>,
 }
 
+/// Node shape of an agent in [`DAGWorkflow::export_mermaid_with_shapes`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MermaidShape {
+    /// `id["name"]`
+    #[default]
+    Rectangle,
+    /// `id("name")`
+    Rounded,
+    /// `id(["name"])`
+    Stadium,
+    /// `id[["name"]]`
+    Subroutine,
+    /// `id[("name")]`
+    Cylinder,
+    /// `id(("name"))`
+    Circle,
+    /// `id{"name"}`
+    Rhombus,
+    /// `id{{"name"}}`
+    Hexagon,
+}
+
+impl MermaidShape {
+    fn delimiters(self) -> (&'static str, &'static str) {
+        match self {
+            MermaidShape::Rectangle => ("[", "]"),
+            MermaidShape::Rounded => ("(", ")"),
+            MermaidShape::Stadium => ("([", "])"),
+            MermaidShape::Subroutine => ("[[", "]]"),
+            MermaidShape::Cylinder => ("[(", ")]"),
+            MermaidShape::Circle => ("((", "))"),
+            MermaidShape::Rhombus => ("{", "}"),
+            MermaidShape::Hexagon => ("{{", "}}"),
+        }
+    }
+}
+
+/// Escape a string for use inside a double-quoted DOT label, keeping newlines visible.
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
 #[derive(Clone, Debug, Error)]
 pub enum GraphWorkflowError {
     #[error("Agent Error: {0}")]
@@ -598,4 +916,6 @@ pub enum GraphWorkflowError {
     Deadlock,
     #[error("Workflow execution canceled")]
     Canceled,
+    #[error("Execution path search stopped, found {0}")]
+    PathLimitExceeded(String),
 }
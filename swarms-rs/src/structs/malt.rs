@@ -0,0 +1,290 @@
+//! MALT (Multi-Agent Long-form Task): a swarm for long-form content generation.
+//!
+//! A planning agent breaks the task into sections, a pool of writing agents write the
+//! sections in parallel, and a polishing agent stitches them into the final document.
+
+use std::ops::Range;
+
+use futures::future::{self, BoxFuture};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::structs::{
+    agent::{Agent, AgentError},
+    conversation::{AgentConversation, Role},
+    swarm::{Swarm, SwarmError},
+    utils::extract_json,
+};
+
+const PLANNER_PROMPT: &str = "Break the following task into the sections of a long-form document. \
+Respond ONLY with a JSON array of section titles, in reading order.";
+
+const WRITER_PROMPT: &str = "You are writing one section of a longer document. \
+Write only the requested section; other writers are handling the rest.";
+
+const POLISHER_PROMPT: &str = "Rewrite the draft below into one coherent document. \
+Smooth the transitions, remove repetition and unify the tone, but keep every `## ` section \
+heading unchanged and in order.";
+
+#[derive(Debug, Error)]
+pub enum MaltSwarmError {
+    #[error("No planning agent provided")]
+    NoPlanner,
+    #[error("No writing agents provided")]
+    NoWriters,
+    #[error("No polishing agent provided")]
+    NoPolisher,
+    #[error("Task is empty")]
+    EmptyTask,
+    #[error("The planning agent returned no sections")]
+    NoSections,
+    #[error("Agent error: {0}")]
+    AgentError(#[from] AgentError),
+}
+
+pub struct MaltSwarmBuilder {
+    name: String,
+    description: String,
+    planner: Option<Box<dyn Agent>>,
+    writers: Vec<Box<dyn Agent>>,
+    polisher: Option<Box<dyn Agent>>,
+    max_sections: usize,
+}
+
+impl MaltSwarmBuilder {
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// The agent that breaks the task into sections.
+    pub fn planner(mut self, planner: Box<dyn Agent>) -> Self {
+        self.planner = Some(planner);
+        self
+    }
+
+    /// Adds an agent to the pool of section writers.
+    pub fn add_writer(mut self, writer: Box<dyn Agent>) -> Self {
+        self.writers.push(writer);
+        self
+    }
+
+    pub fn writers(mut self, writers: Vec<Box<dyn Agent>>) -> Self {
+        self.writers = writers;
+        self
+    }
+
+    /// The agent that rewrites and stitches the sections into the final document.
+    pub fn polisher(mut self, polisher: Box<dyn Agent>) -> Self {
+        self.polisher = Some(polisher);
+        self
+    }
+
+    /// Keeps only the first `max_sections` sections of the plan, at least 1.
+    pub fn max_sections(mut self, max_sections: usize) -> Self {
+        self.max_sections = max_sections.max(1);
+        self
+    }
+
+    pub fn build(self) -> MaltSwarm {
+        MaltSwarm {
+            name: self.name,
+            description: self.description,
+            planner: self.planner,
+            writers: self.writers,
+            polisher: self.polisher,
+            max_sections: self.max_sections,
+        }
+    }
+}
+
+/// A Multi-Agent Long-form Task swarm, see the [module documentation](self).
+///
+/// Sections are assigned to writers round-robin, so a pool smaller than the plan still
+/// covers every section.
+pub struct MaltSwarm {
+    name: String,
+    description: String,
+    planner: Option<Box<dyn Agent>>,
+    writers: Vec<Box<dyn Agent>>,
+    polisher: Option<Box<dyn Agent>>,
+    max_sections: usize,
+}
+
+/// One section of a [`MaltOutput`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaltSection {
+    pub title: String,
+    /// Name of the agent that wrote the section
+    pub writer: String,
+    /// The section as written, before polishing
+    pub content: String,
+    /// Byte range of the section, heading included, in [`MaltOutput::draft`]
+    pub draft_range: Range<usize>,
+    /// Byte offset of the section heading in [`MaltOutput::document`], if the polishing
+    /// agent kept it
+    pub document_offset: Option<usize>,
+}
+
+/// The result of a [`MaltSwarm`] run.
+#[derive(Clone, Serialize)]
+pub struct MaltOutput {
+    /// The polished document
+    pub document: String,
+    /// The sections stitched together with `## ` headings, as given to the polisher
+    pub draft: String,
+    pub sections: Vec<MaltSection>,
+    /// Every step of the run: the task, the plan, each section and the final document
+    pub conversation: AgentConversation,
+}
+
+impl MaltSwarm {
+    pub fn builder() -> MaltSwarmBuilder {
+        MaltSwarmBuilder {
+            name: "MaltSwarm".to_owned(),
+            description: "Plans a long-form document, writes its sections in parallel and polishes the result.".to_owned(),
+            planner: None,
+            writers: Vec::new(),
+            polisher: None,
+            max_sections: 10,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub async fn run(&self, task: impl Into<String>) -> Result<MaltOutput, MaltSwarmError> {
+        let task = task.into();
+        let planner = self.planner.as_ref().ok_or(MaltSwarmError::NoPlanner)?;
+        let polisher = self.polisher.as_ref().ok_or(MaltSwarmError::NoPolisher)?;
+        if self.writers.is_empty() {
+            return Err(MaltSwarmError::NoWriters);
+        }
+        if task.trim().is_empty() {
+            return Err(MaltSwarmError::EmptyTask);
+        }
+
+        let mut conversation = AgentConversation::new(self.name.clone());
+        conversation.add(Role::User("User".to_owned()), task.clone());
+
+        // Plan
+        let plan = planner
+            .run(format!("{PLANNER_PROMPT}\n\nTask: {task}"))
+            .await?;
+        conversation.add(Role::Assistant(planner.name()), plan.clone());
+        let mut titles = parse_section_titles(&plan);
+        titles.truncate(self.max_sections);
+        if titles.is_empty() {
+            return Err(MaltSwarmError::NoSections);
+        }
+        tracing::info!("{} planned {} sections", self.name, titles.len());
+
+        // Write
+        let outline = titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| format!("{}. {title}", i + 1))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let writes = titles.iter().enumerate().map(|(i, title)| {
+            let writer = &self.writers[i % self.writers.len()];
+            let prompt = format!(
+                "{WRITER_PROMPT}\n\nOverall task: {task}\n\nDocument outline:\n{outline}\n\nWrite section {}: {title}",
+                i + 1
+            );
+            async move { writer.run(prompt).await.map(|content| (writer.name(), content)) }
+        });
+        let written = future::try_join_all(writes).await?;
+
+        let mut draft = String::new();
+        let mut sections = Vec::with_capacity(titles.len());
+        for (title, (writer, content)) in titles.into_iter().zip(written) {
+            conversation.add(Role::Assistant(writer.clone()), content.clone());
+            if !draft.is_empty() {
+                draft.push_str("\n\n");
+            }
+            let start = draft.len();
+            draft.push_str(&format!("## {title}\n\n{}", content.trim()));
+            sections.push(MaltSection {
+                title,
+                writer,
+                content,
+                draft_range: start..draft.len(),
+                document_offset: None,
+            });
+        }
+
+        // Polish
+        let document = polisher
+            .run(format!(
+                "{POLISHER_PROMPT}\n\nOverall task: {task}\n\nDraft:\n{draft}"
+            ))
+            .await?;
+        conversation.add(Role::Assistant(polisher.name()), document.clone());
+
+        let mut search_from = 0;
+        for section in &mut sections {
+            let heading = format!("## {}", section.title);
+            if let Some(offset) = document[search_from..].find(&heading) {
+                section.document_offset = Some(search_from + offset);
+                search_from += offset + heading.len();
+            }
+        }
+
+        Ok(MaltOutput {
+            document,
+            draft,
+            sections,
+            conversation,
+        })
+    }
+}
+
+impl Swarm for MaltSwarm {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, task: String) -> BoxFuture<Result<Box<dyn erased_serde::Serialize>, SwarmError>> {
+        Box::pin(async move {
+            self.run(task)
+                .await
+                .map(|output| Box::new(output) as _)
+                .map_err(|e| e.into())
+        })
+    }
+}
+
+/// Section titles from the planner's response: a JSON array of strings if there is one,
+/// otherwise one title per non-empty line with list markers removed.
+fn parse_section_titles(plan: &str) -> Vec<String> {
+    if let Some(titles) = extract_json::<Vec<String>>(plan) {
+        return titles
+            .into_iter()
+            .map(|title| title.trim().to_owned())
+            .filter(|title| !title.is_empty())
+            .collect();
+    }
+
+    plan.lines()
+        .map(|line| {
+            line.trim()
+                .trim_start_matches(|c: char| {
+                    c.is_ascii_digit() || matches!(c, '.' | ')' | '-' | '*' | '#')
+                })
+                .trim()
+                .to_owned()
+        })
+        .filter(|title| !title.is_empty())
+        .collect()
+}
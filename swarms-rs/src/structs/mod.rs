@@ -3,7 +3,11 @@ pub mod concurrent_workflow;
 pub mod conversation;
 pub mod execute_agent_batch;
 pub mod graph_workflow;
+pub mod malt;
+#[cfg(feature = "pdf-export")]
+pub mod pdf_export;
 pub mod persistence;
+pub mod prebuilt_tools;
 pub mod rearrange;
 pub mod sequential_workflow;
 pub mod swarm;
@@ -0,0 +1,365 @@
+//! PDF reports of agent conversations, enabled by the `pdf-export` feature.
+//!
+//! The report uses the PDF built-in fonts, which only cover Windows-1252; characters
+//! outside of it are left out.
+
+use chrono::{DateTime, Local, Utc};
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference,
+    Rect, Rgb,
+};
+
+use crate::structs::conversation::{MemoryEntry, Role};
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+/// Text starts below the page header
+const CONTENT_TOP: f32 = PAGE_HEIGHT - MARGIN - 8.0;
+
+const BODY_SIZE: f32 = 10.0;
+const CODE_SIZE: f32 = 8.5;
+const LINE_HEIGHT: f32 = 5.0;
+const CODE_LINE_HEIGHT: f32 = 4.2;
+/// Characters per line that fit between the margins, for Helvetica and Courier
+const BODY_COLUMNS: usize = 95;
+const CODE_COLUMNS: usize = 94;
+
+/// An RGB color, each component in `0.0..=1.0`
+type Shade = (f32, f32, f32);
+/// Text drawn in one color
+type Span = (String, Shade);
+
+const TEXT: Shade = (0.1, 0.1, 0.1);
+const MUTED: Shade = (0.45, 0.45, 0.45);
+const CODE_BACKGROUND: Shade = (0.95, 0.95, 0.95);
+const JSON_KEY: Shade = (0.55, 0.1, 0.45);
+const JSON_STRING: Shade = (0.1, 0.45, 0.15);
+const JSON_LITERAL: Shade = (0.1, 0.3, 0.7);
+
+/// Metadata shown at the top of a conversation report.
+#[derive(Debug, Clone)]
+pub struct PdfReport {
+    pub agent_name: String,
+    pub agent_id: String,
+    pub model: String,
+    pub task: String,
+    pub exported_at: DateTime<Utc>,
+}
+
+/// Renders `entries` as a PDF report, with a header on every page and tool calls shown
+/// as highlighted code blocks.
+pub fn conversation_to_pdf(
+    report: &PdfReport,
+    entries: &[MemoryEntry],
+) -> Result<Vec<u8>, printpdf::Error> {
+    let (doc, page, layer) = PdfDocument::new(
+        format!("{} - conversation report", report.agent_name),
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+        "Content",
+    );
+    let fonts = Fonts {
+        regular: doc.add_builtin_font(BuiltinFont::Helvetica)?,
+        bold: doc.add_builtin_font(BuiltinFont::HelveticaBold)?,
+        code: doc.add_builtin_font(BuiltinFont::Courier)?,
+    };
+    let mut writer = PdfWriter {
+        layer: doc.get_page(page).get_layer(layer),
+        doc,
+        fonts,
+        header: report.agent_name.clone(),
+        page_number: 1,
+        y: CONTENT_TOP,
+    };
+    writer.draw_header();
+
+    writer.line("Conversation Report", 16.0, Style::Bold, TEXT);
+    writer.space(2.0);
+    let exported_at = report
+        .exported_at
+        .with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    for (label, value) in [
+        ("Agent", report.agent_name.as_str()),
+        ("Agent ID", report.agent_id.as_str()),
+        ("Model", report.model.as_str()),
+        ("Exported at", exported_at.as_str()),
+    ] {
+        writer.paragraph(&format!("{label}: {value}"), Style::Regular, MUTED);
+    }
+    writer.space(3.0);
+    writer.line("Task", 12.0, Style::Bold, TEXT);
+    writer.paragraph(report.task.trim(), Style::Regular, TEXT);
+    writer.space(4.0);
+
+    for entry in entries {
+        let (heading, name) = match &entry.role {
+            Role::User(name) => ("User", name),
+            Role::Assistant(name) => ("Assistant", name),
+            Role::System(name) => ("System", name),
+        };
+        let time = DateTime::from_timestamp_millis(entry.timestamp)
+            .map(|time| {
+                time.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        writer.ensure_space(3.0 * LINE_HEIGHT);
+        writer.line(&format!("{heading} - {name}"), 11.0, Style::Bold, TEXT);
+        writer.line(&time, 8.0, Style::Regular, MUTED);
+        writer.render_body(&entry.content);
+        writer.space(4.0);
+    }
+
+    writer.doc.save_to_bytes()
+}
+
+struct Fonts {
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    code: IndirectFontRef,
+}
+
+#[derive(Clone, Copy)]
+enum Style {
+    Regular,
+    Bold,
+}
+
+/// Lays out text top to bottom, starting new pages as needed.
+struct PdfWriter {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    fonts: Fonts,
+    header: String,
+    page_number: usize,
+    /// Baseline of the next line, in mm from the bottom of the page
+    y: f32,
+}
+
+impl PdfWriter {
+    fn draw_header(&self) {
+        self.layer.set_fill_color(color(MUTED));
+        self.layer.use_text(
+            format!("{} - conversation report", self.header),
+            8.0,
+            Mm(MARGIN),
+            Mm(PAGE_HEIGHT - MARGIN + 6.0),
+            &self.fonts.regular,
+        );
+        self.layer.use_text(
+            format!("Page {}", self.page_number),
+            8.0,
+            Mm(PAGE_WIDTH - MARGIN - 12.0),
+            Mm(PAGE_HEIGHT - MARGIN + 6.0),
+            &self.fonts.regular,
+        );
+    }
+
+    /// Starts a new page unless `height` mm are left on the current one.
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height >= MARGIN {
+            return;
+        }
+        let (page, layer) = self
+            .doc
+            .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Content");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.page_number += 1;
+        self.y = CONTENT_TOP;
+        self.draw_header();
+    }
+
+    fn space(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn line(&mut self, text: &str, size: f32, style: Style, rgb: Shade) {
+        let height = size * 0.5;
+        self.ensure_space(height);
+        let font = match style {
+            Style::Regular => &self.fonts.regular,
+            Style::Bold => &self.fonts.bold,
+        };
+        self.layer.set_fill_color(color(rgb));
+        self.layer
+            .use_text(text, size, Mm(MARGIN), Mm(self.y - size * 0.35), font);
+        self.y -= height;
+    }
+
+    fn paragraph(&mut self, text: &str, style: Style, rgb: Shade) {
+        for line in text.lines() {
+            for wrapped in wrap(line, BODY_COLUMNS) {
+                self.line(&wrapped, BODY_SIZE, style, rgb);
+            }
+        }
+    }
+
+    /// Renders a message body, turning `[Tool name]/[Tool args]/[Tool result]` blocks
+    /// into code blocks and leaving anything else as plain text.
+    fn render_body(&mut self, body: &str) {
+        const NAME: &str = "[Tool name]: ";
+        const ARGS: &str = "\n[Tool args]: ";
+        const RESULT: &str = "\n[Tool result]: ";
+
+        if !body.starts_with(NAME) {
+            self.paragraph(body.trim_end(), Style::Regular, TEXT);
+            return;
+        }
+
+        for block in body.split(NAME).filter(|block| !block.trim().is_empty()) {
+            let parsed = block.split_once(ARGS).and_then(|(name, rest)| {
+                let (args, result) = rest.split_once(RESULT)?;
+                Some((name, args, result))
+            });
+            match parsed {
+                Some((name, args, result)) => {
+                    self.line(
+                        &format!("Tool call: {}", name.trim()),
+                        BODY_SIZE,
+                        Style::Bold,
+                        TEXT,
+                    );
+                    self.code_block(&highlight_json(args.trim()));
+                    self.line("Result:", BODY_SIZE, Style::Bold, TEXT);
+                    self.code_block(&highlight_json(result.trim()));
+                },
+                None => {
+                    self.paragraph(&format!("{NAME}{}", block.trim_end()), Style::Regular, TEXT)
+                },
+            }
+        }
+    }
+
+    /// Draws colored `spans` in a monospaced block with a shaded background.
+    fn code_block(&mut self, spans: &[Span]) {
+        for line in layout_code(spans, CODE_COLUMNS) {
+            self.ensure_space(CODE_LINE_HEIGHT);
+            self.layer.set_fill_color(color(CODE_BACKGROUND));
+            self.layer.add_rect(Rect::new(
+                Mm(MARGIN - 1.0),
+                Mm(self.y - CODE_LINE_HEIGHT),
+                Mm(PAGE_WIDTH - MARGIN + 1.0),
+                Mm(self.y),
+            ));
+
+            self.layer.begin_text_section();
+            self.layer.set_font(&self.fonts.code, CODE_SIZE);
+            self.layer
+                .set_text_cursor(Mm(MARGIN), Mm(self.y - CODE_LINE_HEIGHT * 0.75));
+            for (text, rgb) in line {
+                self.layer.set_fill_color(color(rgb));
+                self.layer.write_text(text, &self.fonts.code);
+            }
+            self.layer.end_text_section();
+            self.y -= CODE_LINE_HEIGHT;
+        }
+        self.space(1.5);
+    }
+}
+
+fn color((r, g, b): Shade) -> Color {
+    Color::Rgb(Rgb::new(r, g, b, None))
+}
+
+/// Splits `line` into chunks of at most `columns` characters, preferring to break at
+/// spaces.
+fn wrap(line: &str, columns: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in line.split(' ') {
+        let fits = current.chars().count() + word.chars().count() < columns;
+        if !current.is_empty() && !fits {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+        while current.chars().count() > columns {
+            let cut = current
+                .char_indices()
+                .nth(columns)
+                .map_or(current.len(), |(i, _)| i);
+            lines.push(current[..cut].to_owned());
+            current = current[cut..].to_owned();
+        }
+    }
+    lines.push(current);
+    lines
+}
+
+/// Splits colored spans into lines of at most `columns` characters.
+fn layout_code(spans: &[Span], columns: usize) -> Vec<Vec<Span>> {
+    let mut lines = vec![Vec::new()];
+    let mut width = 0;
+    for (text, rgb) in spans {
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Vec::new());
+                width = 0;
+            }
+            let mut chars = part.chars().peekable();
+            while chars.peek().is_some() {
+                if width == columns {
+                    lines.push(Vec::new());
+                    width = 0;
+                }
+                let chunk = chars.by_ref().take(columns - width).collect::<String>();
+                width += chunk.chars().count();
+                lines.last_mut().unwrap().push((chunk, *rgb)); // Safety: lines is never empty
+            }
+        }
+    }
+    lines
+}
+
+/// Pretty-prints `text` if it is JSON and splits it into spans colored by token type.
+/// Anything else is returned as a single plain span.
+fn highlight_json(text: &str) -> Vec<Span> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+        return vec![(text.to_owned(), TEXT)];
+    };
+    let pretty = serde_json::to_string_pretty(&value).unwrap_or_else(|_| text.to_owned());
+
+    let mut spans = Vec::new();
+    let mut chars = pretty.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let (end, rgb) = match c {
+            '"' => {
+                let mut escaped = false;
+                let mut end = pretty.len();
+                for (i, c) in chars.by_ref() {
+                    match c {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => {
+                            end = i + 1;
+                            break;
+                        },
+                        _ => {},
+                    }
+                }
+                let is_key = pretty[end..].trim_start().starts_with(':');
+                (end, if is_key { JSON_KEY } else { JSON_STRING })
+            },
+            c if c.is_ascii_alphanumeric() || c == '-' => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')) {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                (end, JSON_LITERAL)
+            },
+            c => (start + c.len_utf8(), TEXT),
+        };
+        spans.push((pretty[start..end].to_owned(), rgb));
+    }
+    spans
+}
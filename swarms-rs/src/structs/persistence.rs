@@ -1,8 +1,14 @@
-use std::path::Path;
+use std::{
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+};
 
 use chrono::Local;
+use futures::future::BoxFuture;
 use thiserror::Error;
 use tokio::{fs, io::AsyncWriteExt};
+use twox_hash::XxHash64;
 
 #[derive(Debug, Error)]
 pub enum PersistenceError {
@@ -14,6 +20,30 @@ pub enum PersistenceError {
     MissingParent(String),
 }
 
+/// Check that `dir` can be created and written to by writing and removing a probe file
+pub async fn ensure_writable_dir(dir: impl AsRef<Path>) -> Result<(), std::io::Error> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir).await?;
+    let probe = dir.join(".swarms_write_check");
+    fs::write(&probe, b"").await?;
+    fs::remove_file(&probe).await
+}
+
+/// File name, without extension, under which an agent saves its state for `task`.
+///
+/// The name is `<agent_name>_<agent_id>_<hash>`, with the lower 32 bits of the task's hash
+/// in hex. Including the id keeps copies of one agent running the same task apart.
+pub fn task_state_file_stem(agent_name: &str, agent_id: &str, task: &str) -> String {
+    let mut hasher = XxHash64::default();
+    task.hash(&mut hasher);
+    format!(
+        "{}_{}_{:x}",
+        agent_name,
+        agent_id,
+        hasher.finish() & 0xFFFFFFFF
+    )
+}
+
 /// Save the data to a file, if the file exists, it will be overwritten
 pub async fn save_to_file(
     data: impl AsRef<[u8]>,
@@ -40,13 +70,14 @@ pub async fn append_to_file(
         fs::create_dir_all(&path).await?;
     }
 
-    fs::OpenOptions::new()
+    let mut file = fs::OpenOptions::new()
         .append(true)
         .create(true)
         .open(path)
-        .await?
-        .write_all(data.as_ref())
         .await?;
+    file.write_all(data.as_ref()).await?;
+    // tokio writes in the background; flush so the data is on disk when we return
+    file.flush().await?;
     Ok(())
 }
 
@@ -55,6 +86,103 @@ pub async fn load_from_file(path: impl AsRef<Path>) -> Result<Vec<u8>, Persisten
     fs::read(path).await.map_err(|e| e.into())
 }
 
+/// Storage for agent task state, see
+/// [`SwarmsAgentBuilder::memory_backend`](crate::agent::SwarmsAgentBuilder::memory_backend).
+///
+/// Implement it to keep state somewhere other than the local disk, e.g. Redis, S3 or
+/// SQLite, so agents in stateless containers can still autosave. Keys are file names
+/// such as `<agent_name>_<agent_id>_<hash>.json`, see [`task_state_file_stem`].
+pub trait MemoryBackend: Send + Sync {
+    /// Stores `data` under `key`, replacing any previous value.
+    fn save<'a>(
+        &'a self,
+        key: &'a str,
+        data: Vec<u8>,
+    ) -> BoxFuture<'a, Result<(), PersistenceError>>;
+
+    /// Returns the data stored under `key`, `None` if there is none.
+    fn load<'a>(&'a self, key: &'a str)
+    -> BoxFuture<'a, Result<Option<Vec<u8>>, PersistenceError>>;
+
+    /// Removes the data stored under `key`, if any.
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), PersistenceError>>;
+
+    /// Appends `data` to the value stored under `key`, creating it if needed. Used for
+    /// incremental saves; the default loads the value and saves it back extended.
+    fn append<'a>(
+        &'a self,
+        key: &'a str,
+        data: Vec<u8>,
+    ) -> BoxFuture<'a, Result<(), PersistenceError>> {
+        Box::pin(async move {
+            let mut value = self.load(key).await?.unwrap_or_default();
+            value.extend(data);
+            self.save(key, value).await
+        })
+    }
+}
+
+/// The default [`MemoryBackend`]: one file per key in a directory.
+#[derive(Debug, Clone)]
+pub struct FileSystemBackend {
+    dir: PathBuf,
+}
+
+impl FileSystemBackend {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl MemoryBackend for FileSystemBackend {
+    fn save<'a>(
+        &'a self,
+        key: &'a str,
+        data: Vec<u8>,
+    ) -> BoxFuture<'a, Result<(), PersistenceError>> {
+        Box::pin(save_to_file(data, self.dir.join(key)))
+    }
+
+    fn load<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> BoxFuture<'a, Result<Option<Vec<u8>>, PersistenceError>> {
+        Box::pin(async move {
+            match load_from_file(self.dir.join(key)).await {
+                Ok(data) => Ok(Some(data)),
+                Err(PersistenceError::IoError(e)) if e.kind() == io::ErrorKind::NotFound => {
+                    Ok(None)
+                },
+                Err(e) => Err(e),
+            }
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), PersistenceError>> {
+        Box::pin(async move {
+            match fs::remove_file(self.dir.join(key)).await {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            }
+        })
+    }
+
+    fn append<'a>(
+        &'a self,
+        key: &'a str,
+        data: Vec<u8>,
+    ) -> BoxFuture<'a, Result<(), PersistenceError>> {
+        Box::pin(async move {
+            fs::create_dir_all(&self.dir).await?;
+            append_to_file(data, self.dir.join(key)).await
+        })
+    }
+}
+
 /// Compress data, defaults to zstd
 pub fn compress(data: impl AsRef<[u8]>) -> Result<Vec<u8>, PersistenceError> {
     use zstd::stream::encode_all;
@@ -0,0 +1,375 @@
+//! Curated tool sets for common agent archetypes.
+//!
+//! A [`ToolPreset`] bundles ready-made tools into a [`Toolkit`], so an agent can be
+//! equipped in one call via
+//! [`SwarmsAgentBuilder::with_prebuilt_tools`](crate::agent::SwarmsAgentBuilder::with_prebuilt_tools):
+//!
+//! ```rust,no_run
+//! use swarms_rs::agent::SwarmsAgentBuilder;
+//! use swarms_rs::llm::provider::openai::OpenAI;
+//! use swarms_rs::structs::prebuilt_tools::ToolPreset;
+//!
+//! let agent = SwarmsAgentBuilder::new_with_model(OpenAI::from_env())
+//!     .with_prebuilt_tools(ToolPreset::FileSystem("./workspace".into()))
+//!     .with_prebuilt_tools(ToolPreset::WebSearch)
+//!     .build();
+//! ```
+
+use std::{
+    io::ErrorKind,
+    path::{Component, Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use swarms_macro::tool;
+use thiserror::Error;
+
+use crate::{
+    self as swarms_rs,
+    llm::request::ToolDefinition,
+    structs::tool::{Tool, Toolkit},
+};
+
+/// Maximum number of characters returned by `fetch_url`
+const MAX_FETCH_CHARS: usize = 20_000;
+/// Maximum number of characters of stdout and stderr each returned by `run_python`
+const MAX_OUTPUT_CHARS: usize = 20_000;
+/// Maximum run time of a `run_python` invocation
+const PYTHON_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum PrebuiltToolError {
+    #[error("Http error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Path '{0}' is outside the allowed root directory")]
+    PathOutsideRoot(String),
+    #[error("Execution timed out after {0} seconds")]
+    Timeout(u64),
+}
+
+/// A curated set of tools for a common agent archetype.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolPreset {
+    /// `web_search` and `fetch_url`
+    WebSearch,
+    /// `read_file`, `write_file` and `list_directory`, confined to the given root directory
+    FileSystem(PathBuf),
+    /// `run_python`, which executes Python code with `python3`
+    ///
+    /// # Warning
+    ///
+    /// The code is **not sandboxed**: it runs on the host as the current user, with
+    /// the same file system and network access as the agent process itself. Only
+    /// enable this preset for trusted models and inputs, or inside a container.
+    CodeInterpreter,
+    /// `web_search`, `fetch_url` and the file tools, with file access confined to the
+    /// current working directory
+    ///
+    /// Code execution is not included; add [`ToolPreset::CodeInterpreter`] alongside
+    /// this preset to opt in to it, keeping its warning in mind.
+    FullStack,
+}
+
+impl ToolPreset {
+    /// Builds the toolkit for this preset.
+    pub fn toolkit(&self) -> Toolkit {
+        match self {
+            ToolPreset::WebSearch => Toolkit::new("web_search")
+                .add_tool(WebSearch)
+                .add_tool(FetchUrl),
+            ToolPreset::FileSystem(root) => Toolkit::new("file_system")
+                .add_tool(ReadFile::new(root))
+                .add_tool(WriteFile::new(root))
+                .add_tool(ListDirectory::new(root)),
+            ToolPreset::CodeInterpreter => Toolkit::new("code_interpreter").add_tool(RunPython),
+            ToolPreset::FullStack => {
+                let root = PathBuf::from(".");
+                Toolkit::new("full_stack")
+                    .add_tool(WebSearch)
+                    .add_tool(FetchUrl)
+                    .add_tool(ReadFile::new(&root))
+                    .add_tool(WriteFile::new(&root))
+                    .add_tool(ListDirectory::new(&root))
+            },
+        }
+    }
+}
+
+#[tool(
+    description = "Search the web and return a short summary with related links",
+    arg(query, description = "The search query")
+)]
+async fn web_search(query: String) -> Result<String, PrebuiltToolError> {
+    let response: serde_json::Value = reqwest::Client::new()
+        .get("https://api.duckduckgo.com/")
+        .query(&[
+            ("q", query.as_str()),
+            ("format", "json"),
+            ("no_html", "1"),
+            ("skip_disambig", "1"),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let mut results = Vec::new();
+    if let Some(summary) = response["AbstractText"].as_str().filter(|s| !s.is_empty()) {
+        results.push(format!(
+            "{} ({})",
+            summary,
+            response["AbstractURL"].as_str().unwrap_or_default()
+        ));
+    }
+    if let Some(topics) = response["RelatedTopics"].as_array() {
+        results.extend(
+            topics
+                .iter()
+                .filter_map(|topic| Some((topic["Text"].as_str()?, topic["FirstURL"].as_str()?)))
+                .take(5)
+                .map(|(text, url)| format!("{text} ({url})")),
+        );
+    }
+
+    if results.is_empty() {
+        Ok(format!("No results found for '{query}'"))
+    } else {
+        Ok(results.join("\n"))
+    }
+}
+
+#[tool(
+    description = "Fetch a web page and return its body as text (truncated for large pages)",
+    arg(url, description = "The URL to fetch")
+)]
+async fn fetch_url(url: String) -> Result<String, PrebuiltToolError> {
+    let body = reqwest::get(&url).await?.error_for_status()?.text().await?;
+    Ok(body.chars().take(MAX_FETCH_CHARS).collect())
+}
+
+#[tool(
+    description = "Run a Python 3 script and return its exit code, stdout and stderr",
+    arg(code, description = "The Python source code to execute")
+)]
+async fn run_python(code: String) -> Result<String, PrebuiltToolError> {
+    let child = tokio::process::Command::new("python3")
+        .arg("-c")
+        .arg(&code)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let output = tokio::time::timeout(PYTHON_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| PrebuiltToolError::Timeout(PYTHON_TIMEOUT.as_secs()))??;
+
+    Ok(format!(
+        "exit code: {}\nstdout:\n{}\nstderr:\n{}",
+        output.status.code().unwrap_or(-1),
+        truncate_output(&output.stdout),
+        truncate_output(&output.stderr)
+    ))
+}
+
+fn truncate_output(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .chars()
+        .take(MAX_OUTPUT_CHARS)
+        .collect()
+}
+
+/// Resolves `path` relative to `root`, making sure the result stays inside it.
+///
+/// Absolute paths and `..` components are rejected up front. Every existing prefix of
+/// the path is then canonicalized, so a symlink inside the root can't lead the tools
+/// to files outside of it; components that don't exist yet are appended as-is.
+async fn resolve_in_root(root: &Path, path: &str) -> Result<PathBuf, PrebuiltToolError> {
+    let relative = Path::new(path);
+    let escapes = relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if escapes {
+        return Err(PrebuiltToolError::PathOutsideRoot(path.to_owned()));
+    }
+
+    let canonical_root = tokio::fs::canonicalize(root).await?;
+    let mut resolved = canonical_root.clone();
+    let mut components = relative
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)));
+    while let Some(component) = components.next() {
+        let next = resolved.join(component);
+        match tokio::fs::symlink_metadata(&next).await {
+            Ok(_) => resolved = tokio::fs::canonicalize(&next).await?,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                resolved = next;
+                resolved.extend(components);
+                break;
+            },
+            Err(e) => return Err(e.into()),
+        }
+        if !resolved.starts_with(&canonical_root) {
+            return Err(PrebuiltToolError::PathOutsideRoot(path.to_owned()));
+        }
+    }
+    Ok(resolved)
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PathArgs {
+    path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WriteFileArgs {
+    path: String,
+    content: String,
+}
+
+fn path_parameters(description: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "path": {"type": "string", "description": description}
+        },
+        "required": ["path"]
+    })
+}
+
+/// Reads a UTF-8 file below a root directory.
+#[derive(Debug, Clone)]
+pub struct ReadFile {
+    root: PathBuf,
+}
+
+impl ReadFile {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Tool for ReadFile {
+    type Error = PrebuiltToolError;
+    type Args = PathArgs;
+    type Output = String;
+
+    const NAME: &'static str = "read_file";
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_owned(),
+            description: "Read the contents of a text file".to_owned(),
+            parameters: path_parameters("Path of the file, relative to the workspace root"),
+            output_schema: None,
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let path = resolve_in_root(&self.root, &args.path).await?;
+        Ok(tokio::fs::read_to_string(path).await?)
+    }
+}
+
+/// Writes a file below a root directory, creating parent directories as needed.
+#[derive(Debug, Clone)]
+pub struct WriteFile {
+    root: PathBuf,
+}
+
+impl WriteFile {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Tool for WriteFile {
+    type Error = PrebuiltToolError;
+    type Args = WriteFileArgs;
+    type Output = String;
+
+    const NAME: &'static str = "write_file";
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_owned(),
+            description: "Write text to a file, replacing any existing content".to_owned(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Path of the file, relative to the workspace root"
+                    },
+                    "content": {"type": "string", "description": "The text to write"}
+                },
+                "required": ["path", "content"]
+            }),
+            output_schema: None,
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let path = resolve_in_root(&self.root, &args.path).await?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, &args.content).await?;
+        Ok(format!(
+            "Wrote {} bytes to {}",
+            args.content.len(),
+            args.path
+        ))
+    }
+}
+
+/// Lists the entries of a directory below a root directory.
+#[derive(Debug, Clone)]
+pub struct ListDirectory {
+    root: PathBuf,
+}
+
+impl ListDirectory {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Tool for ListDirectory {
+    type Error = PrebuiltToolError;
+    type Args = PathArgs;
+    type Output = Vec<String>;
+
+    const NAME: &'static str = "list_directory";
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_owned(),
+            description: "List the files and directories in a directory; directories end with '/'"
+                .to_owned(),
+            parameters: path_parameters("Path of the directory, relative to the workspace root"),
+            output_schema: None,
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let path = resolve_in_root(&self.root, &args.path).await?;
+        let mut entries = Vec::new();
+        let mut dir = tokio::fs::read_dir(path).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            let mut name = entry.file_name().to_string_lossy().into_owned();
+            if entry.file_type().await?.is_dir() {
+                name.push('/');
+            }
+            entries.push(name);
+        }
+        entries.sort();
+        Ok(entries)
+    }
+}
@@ -1,9 +1,18 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use chrono::Local;
+use chrono::{Local, Utc};
 use dashmap::DashSet;
 use erased_serde::Serialize as ErasedSerialize;
-use futures::{StreamExt, TryStreamExt, future::BoxFuture, stream};
+use futures::{
+    StreamExt, TryStreamExt,
+    future::{self, BoxFuture},
+    stream,
+};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use uuid::Uuid;
@@ -12,7 +21,8 @@ use crate::structs::{
     agent::{Agent, AgentError},
     conversation::{AgentConversation, Role},
     persistence::{self, PersistenceError},
-    swarm::{MetadataSchemaMap, Swarm, SwarmError},
+    swarm::{MetadataSchemaMap, Swarm, SwarmError, SwarmMetadata},
+    swarms_router::SwarmType,
 };
 
 /// Errors that can occur during agent rearrangement operations
@@ -30,6 +40,8 @@ pub enum AgentRearrangeError {
     InvalidFlowFormat(String),
     #[error("Duplicate agent names in flow are not allowed")]
     DuplicateAgentNames,
+    #[error("Agent '{0}' exists in both instances")]
+    AgentNameConflict(String),
     #[error("Tasks or Agents are empty")]
     EmptyTasksOrAgents,
     #[error("Json error: {0}")]
@@ -40,6 +52,48 @@ pub enum AgentRearrangeError {
     JoinError(#[from] tokio::task::JoinError),
 }
 
+/// Latency statistics over a set of samples, in milliseconds
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub mean_ms: f64,
+    /// 95th percentile (nearest rank)
+    pub p95_ms: f64,
+}
+
+impl LatencyStats {
+    /// Computes the statistics of `samples`, or `None` if there are none.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut millis: Vec<f64> = samples.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        millis.sort_by(f64::total_cmp);
+
+        let p95_rank = (millis.len() as f64 * 0.95).ceil() as usize;
+        Some(Self {
+            samples: millis.len(),
+            min_ms: millis[0],
+            max_ms: millis[millis.len() - 1],
+            mean_ms: millis.iter().sum::<f64>() / millis.len() as f64,
+            p95_ms: millis[p95_rank.max(1) - 1],
+        })
+    }
+}
+
+/// Result of [`AgentRearrange::benchmark`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BenchmarkReport {
+    pub flow: String,
+    pub runs: usize,
+    /// Latency of each agent's runs, keyed by agent name
+    pub agents: BTreeMap<String, LatencyStats>,
+    /// Latency of the full flow
+    pub total: LatencyStats,
+}
+
 /// Output format options for agent rearrange results
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum OutputType {
@@ -51,6 +105,9 @@ pub enum OutputType {
     List,
     /// Return a dictionary mapping agent names to responses
     Dict,
+    /// Return the output that won the last weighted vote of a parallel step, see
+    /// [`AgentRearrangeBuilder::with_parallel_voting`]
+    Voted,
 }
 
 impl Default for OutputType {
@@ -59,6 +116,162 @@ impl Default for OutputType {
     }
 }
 
+/// What each agent of a sequential step receives as input
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum InputMode {
+    /// The whole conversation so far, growing with every step
+    #[default]
+    FullConversation,
+    /// The original task and the output of the preceding step, which keeps the input
+    /// size flat on long flows
+    LastOutputPlusTask,
+    /// Only the output of the preceding step, or the task for the first agent
+    LastOutputOnly,
+}
+
+/// Hooks into the execution of an [`AgentRearrange`] flow, e.g. for logging or metrics.
+///
+/// Each hook fires once per agent step, sequential or parallel. Every method has a
+/// no-op default, so implementors only override the events they need.
+pub trait AgentCallback: Send + Sync {
+    /// Called right before an agent runs, with the input it receives.
+    fn on_step_start(&self, _agent_name: &str, _input: &str) {}
+
+    /// Called after an agent returns successfully.
+    fn on_step_complete(&self, _agent_name: &str, _output: &str, _duration_ms: u64) {}
+
+    /// Called when an agent fails, before the error aborts the flow.
+    fn on_error(&self, _agent_name: &str, _error: &AgentRearrangeError) {}
+}
+
+/// One agent's output in a weighted vote, see [`VotingJudge`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoteCandidate {
+    pub agent_name: String,
+    pub output: String,
+    /// The agent's voting weight, 1.0 unless set with
+    /// [`AgentRearrangeBuilder::with_parallel_voting`]
+    pub weight: f64,
+}
+
+/// Picks the final output of a parallel step when parallel voting is enabled.
+pub trait VotingJudge: Send + Sync {
+    /// Return the index in `candidates` of the winning output. Candidates are in flow
+    /// order.
+    fn pick<'a>(
+        &'a self,
+        task: &'a str,
+        candidates: &'a [VoteCandidate],
+    ) -> BoxFuture<'a, Result<usize, AgentRearrangeError>>;
+}
+
+/// The default [`VotingJudge`]: every agent votes for each output in proportion to how
+/// similar it is to its own, and the output with the most weighted support wins.
+///
+/// Similarity is the Jaccard index of the outputs' lowercase word sets. Ties go to the
+/// earliest candidate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimilarityJudge;
+
+impl VotingJudge for SimilarityJudge {
+    fn pick<'a>(
+        &'a self,
+        _task: &'a str,
+        candidates: &'a [VoteCandidate],
+    ) -> BoxFuture<'a, Result<usize, AgentRearrangeError>> {
+        let words = candidates
+            .iter()
+            .map(|candidate| word_set(&candidate.output))
+            .collect::<Vec<_>>();
+        let support = |i: usize| {
+            candidates
+                .iter()
+                .zip(&words)
+                .map(|(voter, voter_words)| voter.weight * jaccard(&words[i], voter_words))
+                .sum::<f64>()
+        };
+
+        let mut winner = 0;
+        let mut best = f64::NEG_INFINITY;
+        for i in 0..candidates.len() {
+            let score = support(i);
+            if score > best {
+                winner = i;
+                best = score;
+            }
+        }
+        Box::pin(future::ready(Ok(winner)))
+    }
+}
+
+/// A [`VotingJudge`] that asks an agent, usually an LLM, to pick the best output.
+///
+/// The agent sees the task and the numbered outputs with their weights and must answer
+/// with the number of the best one.
+pub struct AgentJudge {
+    agent: Box<dyn Agent>,
+}
+
+impl AgentJudge {
+    pub fn new(agent: Box<dyn Agent>) -> Self {
+        Self { agent }
+    }
+}
+
+impl VotingJudge for AgentJudge {
+    fn pick<'a>(
+        &'a self,
+        task: &'a str,
+        candidates: &'a [VoteCandidate],
+    ) -> BoxFuture<'a, Result<usize, AgentRearrangeError>> {
+        Box::pin(async move {
+            let mut prompt = format!(
+                "Several agents answered the task below. Each answer has a weight: the higher \
+                the weight, the more the agent is trusted. Reply ONLY with the number of the \
+                best answer.\n\n### TASK ###\n{task}\n"
+            );
+            for (i, candidate) in candidates.iter().enumerate() {
+                prompt.push_str(&format!(
+                    "\n### ANSWER {} (weight {}) ###\n{}\n",
+                    i + 1,
+                    candidate.weight,
+                    candidate.output
+                ));
+            }
+
+            let response = self.agent.run(prompt).await?;
+            response
+                .split(|c: char| !c.is_ascii_digit())
+                .filter_map(|number| number.parse::<usize>().ok())
+                .find(|number| (1..=candidates.len()).contains(number))
+                .map(|number| number - 1)
+                .ok_or_else(|| {
+                    AgentRearrangeError::ExecutionError(format!(
+                        "Voting judge gave no valid answer number: {response}"
+                    ))
+                })
+        })
+    }
+}
+
+fn word_set(text: &str) -> HashSet<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
 /// Configuration builder for AgentRearrange
 #[derive(Default)]
 pub struct AgentRearrangeBuilder {
@@ -74,6 +287,11 @@ pub struct AgentRearrangeBuilder {
     metadata_output_dir: String,
     rules: Option<String>,
     team_awareness: bool,
+    max_parallel_agents: Option<usize>,
+    callbacks: Vec<Arc<dyn AgentCallback>>,
+    voting_weights: Option<HashMap<String, f64>>,
+    voting_judge: Option<Arc<dyn VotingJudge>>,
+    input_mode: InputMode,
 }
 
 impl AgentRearrangeBuilder {
@@ -126,6 +344,13 @@ impl AgentRearrangeBuilder {
         self
     }
 
+    /// Set what each agent of a sequential step receives, the full conversation by
+    /// default. Parallel steps are unaffected.
+    pub fn input_mode(mut self, input_mode: InputMode) -> Self {
+        self.input_mode = input_mode;
+        self
+    }
+
     /// Enable or disable autosave functionality
     pub fn autosave(mut self, autosave: bool) -> Self {
         self.autosave = autosave;
@@ -156,6 +381,42 @@ impl AgentRearrangeBuilder {
         self
     }
 
+    /// Limit how many agents of a parallel step (e.g. `"a, b, c -> d"`) run at once.
+    ///
+    /// By default every agent of a step runs concurrently, so a wide step sends as many
+    /// simultaneous model calls as it has agents. Set a cap to stay within provider rate
+    /// limits. Values below 1 are treated as 1.
+    pub fn max_parallel_agents(mut self, max_parallel_agents: usize) -> Self {
+        self.max_parallel_agents = Some(max_parallel_agents.max(1));
+        self
+    }
+
+    /// Register a callback notified as each agent step starts, completes or fails.
+    /// Callbacks are invoked in registration order.
+    pub fn add_callback(mut self, callback: impl AgentCallback + 'static) -> Self {
+        self.callbacks.push(Arc::new(callback));
+        self
+    }
+
+    /// Let the agents of each parallel step vote on a single output for the step.
+    ///
+    /// The winner, picked by the [`VotingJudge`] (a [`SimilarityJudge`] unless set with
+    /// [`voting_judge`](Self::voting_judge)), becomes the input of the next step and the
+    /// result of the run, as the output type is set to [`OutputType::Voted`]. Only the
+    /// winning output is added to the conversation. Agents missing from `weights` get a
+    /// weight of 1.0.
+    pub fn with_parallel_voting(mut self, weights: HashMap<String, f64>) -> Self {
+        self.voting_weights = Some(weights);
+        self.output_type = OutputType::Voted;
+        self
+    }
+
+    /// Set the judge deciding parallel votes, e.g. an [`AgentJudge`].
+    pub fn voting_judge(mut self, judge: impl VotingJudge + 'static) -> Self {
+        self.voting_judge = Some(Arc::new(judge));
+        self
+    }
+
     /// Build the AgentRearrange instance
     pub fn build(self) -> AgentRearrange {
         AgentRearrange {
@@ -183,6 +444,14 @@ impl AgentRearrangeBuilder {
             tasks: DashSet::new(),
             rules: self.rules,
             team_awareness: self.team_awareness,
+            max_parallel_agents: self.max_parallel_agents,
+            callbacks: self.callbacks,
+            voting_weights: self.voting_weights,
+            voting_judge: self
+                .voting_judge
+                .unwrap_or_else(|| Arc::new(SimilarityJudge)),
+            input_mode: self.input_mode,
+            swarm_type: SwarmType::AgentRearrange,
         }
     }
 }
@@ -207,6 +476,7 @@ impl AgentRearrangeBuilder {
 /// - Sequential: `"agent1 -> agent2 -> agent3"`
 /// - Parallel: `"agent1, agent2 -> agent3"`
 /// - Mixed: `"agent1 -> agent2, agent3 -> agent4"`
+/// - Explicit parallel groups: `"agent1 -> (agent2, agent3) -> agent4"`
 ///
 /// # Examples
 ///
@@ -256,6 +526,18 @@ pub struct AgentRearrange {
     rules: Option<String>,
     /// Whether team awareness is enabled
     team_awareness: bool,
+    /// Maximum number of agents of a parallel step running at once, unbounded if `None`
+    max_parallel_agents: Option<usize>,
+    /// Hooks notified of each agent step
+    callbacks: Vec<Arc<dyn AgentCallback>>,
+    /// Voting weights of the agents, parallel steps are voted on if set
+    voting_weights: Option<HashMap<String, f64>>,
+    /// Judge deciding parallel votes
+    voting_judge: Arc<dyn VotingJudge>,
+    /// What each agent of a sequential step receives as input
+    input_mode: InputMode,
+    /// Swarm type reported in metadata, for compatibility with the Swarms API format
+    swarm_type: SwarmType,
 }
 
 impl Default for AgentRearrange {
@@ -277,6 +559,12 @@ impl Default for AgentRearrange {
             tasks: DashSet::new(),
             rules: None,
             team_awareness: false,
+            max_parallel_agents: None,
+            callbacks: Vec::new(),
+            voting_weights: None,
+            voting_judge: Arc::new(SimilarityJudge),
+            input_mode: InputMode::default(),
+            swarm_type: SwarmType::AgentRearrange,
         }
     }
 }
@@ -374,6 +662,7 @@ impl AgentRearrange {
     ///
     /// This method checks that:
     /// - The flow contains the `->` separator
+    /// - Parenthesized parallel groups are balanced and not nested
     /// - All referenced agents are registered in the swarm
     /// - The flow syntax is properly formatted
     ///
@@ -384,6 +673,7 @@ impl AgentRearrange {
     /// # Errors
     ///
     /// - `FlowValidationError` if the flow format is incorrect
+    /// - `InvalidFlowFormat` if a step is malformed (e.g. unbalanced parentheses)
     /// - `AgentNotFound` if referenced agents are not registered
     pub fn validate_flow(&self) -> Result<(), AgentRearrangeError> {
         if self.flow.is_empty() {
@@ -392,20 +682,10 @@ impl AgentRearrange {
             ));
         }
 
-        // Handle both sequential (with ->) and parallel-only (without ->) flows
-        let tasks: Vec<&str> = if self.flow.contains("->") {
-            self.flow.split("->").collect()
-        } else {
-            // For parallel-only flows like "agent1, agent2"
-            vec![self.flow.as_str()]
-        };
-
-        for task in tasks {
-            let agent_names: Vec<&str> = task.split(',').map(|name| name.trim()).collect();
-
-            for agent_name in agent_names {
-                if agent_name != "H" && !self.agents.contains_key(agent_name) {
-                    return Err(AgentRearrangeError::AgentNotFound(agent_name.to_string()));
+        for step in Self::parse_flow(&self.flow)? {
+            for agent_name in step {
+                if agent_name != "H" && !self.agents.contains_key(&agent_name) {
+                    return Err(AgentRearrangeError::AgentNotFound(agent_name));
                 }
             }
         }
@@ -417,6 +697,110 @@ impl AgentRearrange {
         Ok(())
     }
 
+    /// Chain `other` after this instance, returning one instance with the agents of both.
+    ///
+    /// `connection_flow` states how the two flows connect: it must start with the last step
+    /// of this flow and end with the first step of `other`'s flow, e.g.
+    /// `"lastAgentA -> firstAgentB"`. Steps in between are inserted between the two
+    /// flows. The merged instance keeps this instance's settings.
+    ///
+    /// # Errors
+    ///
+    /// - `AgentNameConflict` if both instances have an agent with the same name
+    /// - `InvalidFlowFormat` if `connection_flow` doesn't connect the two flows
+    /// - `AgentNotFound` if the merged flow references an unknown agent
+    pub fn merge(
+        mut self,
+        other: AgentRearrange,
+        connection_flow: &str,
+    ) -> Result<AgentRearrange, AgentRearrangeError> {
+        if let Some(name) = other
+            .agents
+            .keys()
+            .find(|name| self.agents.contains_key(*name))
+        {
+            return Err(AgentRearrangeError::AgentNameConflict(name.clone()));
+        }
+
+        let first = Self::parse_flow(&self.flow)?;
+        let second = Self::parse_flow(&other.flow)?;
+        let connection = Self::parse_flow(connection_flow)?;
+        let same_step = |a: &[String], b: &[String]| {
+            let mut a = a.to_vec();
+            let mut b = b.to_vec();
+            a.sort();
+            b.sort();
+            a == b
+        };
+        // Safety: parse_flow always returns at least one step
+        let connects = connection.len() >= 2
+            && same_step(&connection[0], first.last().unwrap())
+            && same_step(connection.last().unwrap(), &second[0]);
+        if !connects {
+            return Err(AgentRearrangeError::InvalidFlowFormat(format!(
+                "connection flow '{}' must lead from the last step of '{}' to the first step of '{}'",
+                connection_flow, self.flow, other.flow
+            )));
+        }
+
+        let steps = first
+            .iter()
+            .chain(&connection[1..connection.len() - 1])
+            .chain(&second)
+            .map(|step| step.join(", "))
+            .collect::<Vec<_>>();
+        self.flow = steps.join(" -> ");
+        self.agents.extend(other.agents);
+        self.validate_flow()?;
+
+        Ok(self)
+    }
+
+    /// Parse a flow string into sequential steps, each holding the agents run in parallel.
+    ///
+    /// Steps are separated by `->`. Agents within a step are separated by commas and may
+    /// optionally be wrapped in parentheses to make the parallel group explicit, so
+    /// `"a -> (b, c) -> d"` and `"a -> b, c -> d"` parse to the same steps.
+    fn parse_flow(flow: &str) -> Result<Vec<Vec<String>>, AgentRearrangeError> {
+        flow.split("->")
+            .map(|step| {
+                let step = step.trim();
+                let group = match (step.strip_prefix('('), step.strip_suffix(')')) {
+                    (Some(_), Some(_)) => &step[1..step.len() - 1],
+                    (None, None) => step,
+                    _ => {
+                        return Err(AgentRearrangeError::InvalidFlowFormat(format!(
+                            "unbalanced parentheses in step '{}'",
+                            step
+                        )));
+                    },
+                };
+
+                if group.contains(['(', ')']) {
+                    return Err(AgentRearrangeError::InvalidFlowFormat(format!(
+                        "nested or misplaced parentheses in step '{}'",
+                        step
+                    )));
+                }
+
+                group
+                    .split(',')
+                    .map(|name| {
+                        let name = name.trim();
+                        if name.is_empty() {
+                            Err(AgentRearrangeError::InvalidFlowFormat(format!(
+                                "empty agent name in step '{}'",
+                                step
+                            )))
+                        } else {
+                            Ok(name.to_string())
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
     /// Execute the agent rearrangement task
     ///
     /// This method processes a task through the configured flow of agents,
@@ -441,12 +825,38 @@ impl AgentRearrange {
 
     /// Internal execution method with full parameter support
     async fn run_internal(
+        &mut self,
+        task: impl Into<String>,
+        img: Option<String>,
+        custom_tasks: Option<HashMap<String, String>>,
+    ) -> Result<String, AgentRearrangeError> {
+        let (output, _, _) = self.run_timed(task, img, custom_tasks).await?;
+        Ok(output)
+    }
+
+    /// Executes the task like [`run`](Self::run), also returning a [`SwarmMetadata`]
+    /// report of the run. With autosave enabled the report is saved to
+    /// `metadata_output_dir` next to the instance metadata.
+    pub async fn run_with_metadata(
+        &mut self,
+        task: impl Into<String>,
+    ) -> Result<(String, SwarmMetadata), AgentRearrangeError> {
+        let (output, _, metadata) = self.run_timed(task, None, None).await?;
+        Ok((output, metadata))
+    }
+
+    /// Runs the flow, also returning how long each agent run took in execution order and
+    /// the run's metadata
+    async fn run_timed(
         &mut self,
         task: impl Into<String>,
         _img: Option<String>,
         _custom_tasks: Option<HashMap<String, String>>,
-    ) -> Result<String, AgentRearrangeError> {
+    ) -> Result<(String, Vec<(String, Duration)>, SwarmMetadata), AgentRearrangeError> {
         let task = task.into();
+        let started_at = Utc::now();
+        let first_message = self.conversation.history.len();
+        let mut timings = Vec::new();
 
         if self.verbose {
             tracing::info!("Starting task execution: {}", task);
@@ -459,22 +869,12 @@ impl AgentRearrange {
         // Validate flow before execution
         self.validate_flow()?;
 
-        // Apply rules if configured
-        if let Some(rules) = &self.rules {
-            self.conversation.add(
-                Role::User("System".to_string()),
-                format!("Rules: {}", rules),
-            );
-        }
-
-        let tasks: Vec<&str> = if self.flow.contains("->") {
-            self.flow.split("->").collect()
-        } else {
-            // For parallel-only flows like "agent1, agent2"
-            vec![self.flow.as_str()]
-        };
+        let tasks = Self::parse_flow(&self.flow)?;
         let mut current_task = task.clone();
+        // Output of the preceding step, for `InputMode`s other than the full conversation
+        let mut previous_output: Option<String> = None;
         let mut response_map = HashMap::new();
+        let mut voted_output = None;
 
         for loop_count in 0..self.max_loops {
             if self.verbose {
@@ -482,7 +882,7 @@ impl AgentRearrange {
             }
 
             for task_step in tasks.iter() {
-                let agent_names: Vec<&str> = task_step.split(',').map(|name| name.trim()).collect();
+                let agent_names: Vec<&str> = task_step.iter().map(String::as_str).collect();
 
                 if agent_names.len() > 1 {
                     // Parallel processing
@@ -490,14 +890,43 @@ impl AgentRearrange {
                         tracing::info!("Running agents in parallel: {:?}", agent_names);
                     }
 
+                    let parallel_task = self.inject_global_rules(&current_task);
                     let parallel_results = self
-                        .execute_agents_parallel(&agent_names, &current_task)
+                        .execute_agents_parallel(&agent_names, &parallel_task)
                         .await?;
 
-                    for (agent_name, result) in parallel_results {
-                        self.conversation
-                            .add(Role::Assistant(agent_name.clone()), result.clone());
-                        response_map.insert(agent_name, result);
+                    let mut candidates = Vec::with_capacity(parallel_results.len());
+                    for (agent_name, (result, elapsed)) in parallel_results {
+                        timings.push((agent_name.clone(), elapsed));
+                        response_map.insert(agent_name.clone(), result.clone());
+                        candidates.push((agent_name, result));
+                    }
+                    previous_output = Some(
+                        candidates
+                            .iter()
+                            .map(|(agent_name, result)| format!("{agent_name}: {result}"))
+                            .collect::<Vec<_>>()
+                            .join("\n\n"),
+                    );
+
+                    match self
+                        .vote_on_parallel_step(&agent_names, &parallel_task, candidates.clone())
+                        .await?
+                    {
+                        // Only the winner is kept in the conversation, so later steps
+                        // build on the voted output rather than on every candidate
+                        Some(winner) => {
+                            self.conversation
+                                .add(Role::Assistant(winner.agent_name), winner.output.clone());
+                            current_task = winner.output.clone();
+                            previous_output = Some(winner.output.clone());
+                            voted_output = Some(winner.output);
+                        },
+                        None => {
+                            for (agent_name, result) in candidates {
+                                self.conversation.add(Role::Assistant(agent_name), result);
+                            }
+                        },
                     }
                 } else {
                     // Sequential processing
@@ -519,15 +948,23 @@ impl AgentRearrange {
                         AgentRearrangeError::AgentNotFound(agent_name.to_string())
                     })?;
 
-                    let result = agent
-                        .run(self.conversation.to_string())
-                        .await
-                        .map_err(AgentRearrangeError::AgentError)?;
+                    let input = match (self.input_mode, &previous_output) {
+                        (InputMode::FullConversation, _) => self.conversation.to_string(),
+                        (InputMode::LastOutputPlusTask, Some(output)) => format!(
+                            "Original task:\n{task}\n\nOutput of the previous step:\n{output}"
+                        ),
+                        (InputMode::LastOutputOnly, Some(output)) => output.clone(),
+                        (_, None) => task.clone(),
+                    };
+                    let input = self.inject_global_rules(&input);
+                    let (result, elapsed) = self.run_agent_step(agent_name, agent, input).await?;
+                    timings.push((agent_name.to_string(), elapsed));
 
                     self.conversation
                         .add(Role::Assistant(agent_name.to_string()), result.clone());
 
                     response_map.insert(agent_name.to_string(), result.clone());
+                    previous_output = Some(result.clone());
                     current_task = result;
                 }
             }
@@ -538,57 +975,150 @@ impl AgentRearrange {
         }
 
         // Format output based on output_type
-        let output = self.format_output(&response_map, &current_task);
+        let output = match (&self.output_type, voted_output) {
+            (OutputType::Voted, Some(voted_output)) => voted_output,
+            _ => self.format_output(&response_map, &current_task),
+        };
+
+        let agent_names = tasks
+            .iter()
+            .flatten()
+            .filter(|name| name.as_str() != "H")
+            .cloned()
+            .collect();
+        let entries = self.conversation.entries();
+        let run_metadata = SwarmMetadata::new(
+            started_at,
+            agent_names,
+            &task,
+            entries.get(first_message..).unwrap_or_default(),
+            &output,
+        );
 
         if self.autosave {
             self.save_metadata().await?;
+            if !self.metadata_output_dir.is_empty() {
+                run_metadata.save(&self.metadata_output_dir).await?;
+            }
         }
 
-        Ok(output)
+        Ok((output, timings, run_metadata))
     }
 
-    /// Execute multiple agents in parallel
+    /// Prepend the configured rules to a task as a preamble.
+    ///
+    /// Rules are injected into the input of every agent at every step of the flow,
+    /// so agents in the middle of a chain see them as well. Returns the task unchanged
+    /// when no rules are configured.
+    pub fn inject_global_rules(&self, task: &str) -> String {
+        match &self.rules {
+            Some(rules) if !rules.trim().is_empty() => {
+                format!("### RULES ###\n{}\n\n### TASK ###\n{}", rules, task)
+            },
+            _ => task.to_string(),
+        }
+    }
+
+    /// Execute multiple agents in parallel, at most `max_parallel_agents` at a time
     async fn execute_agents_parallel(
         &self,
         agent_names: &[&str],
         task: &str,
-    ) -> Result<HashMap<String, String>, AgentRearrangeError> {
-        let mut handles = Vec::new();
-
-        for agent_name in agent_names {
-            if *agent_name == "H" {
-                continue; // Skip human-in-the-loop for parallel execution
-            }
-
-            let agent = self
-                .agents
-                .get(*agent_name)
-                .ok_or_else(|| AgentRearrangeError::AgentNotFound(agent_name.to_string()))?;
-
-            let task_clone = task.to_string();
-            let agent_name_clone = agent_name.to_string();
-
-            // Clone the agent for parallel execution
-            let agent_clone = agent.clone_box();
-
-            let handle = tokio::spawn(async move {
-                let result = agent_clone.run(task_clone).await;
-                (agent_name_clone, result)
-            });
+    ) -> Result<HashMap<String, (String, Duration)>, AgentRearrangeError> {
+        // Skip human-in-the-loop for parallel execution
+        let agents = agent_names
+            .iter()
+            .filter(|agent_name| **agent_name != "H")
+            .map(|agent_name| {
+                self.agents
+                    .get(*agent_name)
+                    .map(|agent| (agent_name.to_string(), agent))
+                    .ok_or_else(|| AgentRearrangeError::AgentNotFound(agent_name.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let limit = self.max_parallel_agents.unwrap_or(agents.len()).max(1);
+        stream::iter(agents)
+            .map(|(agent_name, agent)| async move {
+                let step = self
+                    .run_agent_step(&agent_name, agent, task.to_string())
+                    .await?;
+                Ok::<_, AgentRearrangeError>((agent_name, step))
+            })
+            .buffer_unordered(limit)
+            .try_collect()
+            .await
+    }
 
-            handles.push(handle);
+    /// Pick the output of a parallel step by weighted vote, if voting is enabled.
+    async fn vote_on_parallel_step(
+        &self,
+        agent_names: &[&str],
+        task: &str,
+        mut results: Vec<(String, String)>,
+    ) -> Result<Option<VoteCandidate>, AgentRearrangeError> {
+        let Some(weights) = &self.voting_weights else {
+            return Ok(None);
+        };
+        if results.is_empty() {
+            return Ok(None);
         }
 
-        // Wait for all parallel tasks to complete
-        let mut results = HashMap::new();
-        for handle in handles {
-            let (agent_name, result) = handle.await?;
+        // Parallel results arrive in completion order, vote in flow order instead
+        results.sort_by_key(|(agent_name, _)| {
+            agent_names
+                .iter()
+                .position(|name| *name == agent_name.as_str())
+        });
+        let candidates = results
+            .into_iter()
+            .map(|(agent_name, output)| VoteCandidate {
+                weight: weights.get(&agent_name).copied().unwrap_or(1.0),
+                agent_name,
+                output,
+            })
+            .collect::<Vec<_>>();
+
+        let winner = self.voting_judge.pick(task, &candidates).await?;
+        let winner = candidates.into_iter().nth(winner).ok_or_else(|| {
+            AgentRearrangeError::ExecutionError(format!(
+                "Voting judge picked candidate {winner} out of range"
+            ))
+        })?;
+        if self.verbose {
+            tracing::info!("Parallel vote won by {}", winner.agent_name);
+        }
+        Ok(Some(winner))
+    }
 
-            let result = result.map_err(AgentRearrangeError::AgentError)?;
-            results.insert(agent_name, result);
+    /// Run a single agent step, notifying the registered callbacks.
+    async fn run_agent_step(
+        &self,
+        agent_name: &str,
+        agent: &dyn Agent,
+        input: String,
+    ) -> Result<(String, Duration), AgentRearrangeError> {
+        for callback in &self.callbacks {
+            callback.on_step_start(agent_name, &input);
         }
 
-        Ok(results)
+        let started = Instant::now();
+        match agent.run(input).await {
+            Ok(output) => {
+                let elapsed = started.elapsed();
+                for callback in &self.callbacks {
+                    callback.on_step_complete(agent_name, &output, elapsed.as_millis() as u64);
+                }
+                Ok((output, elapsed))
+            },
+            Err(e) => {
+                let error = AgentRearrangeError::AgentError(e);
+                for callback in &self.callbacks {
+                    callback.on_error(agent_name, &error);
+                }
+                Err(error)
+            },
+        }
     }
 
     /// Format the output based on the configured output type
@@ -601,7 +1131,7 @@ impl AgentRearrange {
                 }
                 output
             },
-            OutputType::Final => final_result.to_string(),
+            OutputType::Final | OutputType::Voted => final_result.to_string(),
             OutputType::List => {
                 let responses: Vec<String> = response_map.values().cloned().collect();
                 if self.return_json {
@@ -713,6 +1243,58 @@ impl AgentRearrange {
         results
     }
 
+    /// Runs the full flow `runs` times and reports per-agent and total latency.
+    ///
+    /// Every run starts from a fresh conversation, like [`batch_run`](Self::batch_run),
+    /// so runs don't slow each other down by growing the shared context. Runs are
+    /// sequential to keep the measurements free of contention between runs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use swarms_rs::structs::rearrange::AgentRearrange;
+    /// # async fn example(rearrange: AgentRearrange) -> Result<(), Box<dyn std::error::Error>> {
+    /// let report = rearrange.benchmark("Summarize the quarterly report", 10).await?;
+    /// println!("{}", serde_json::to_string_pretty(&report)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn benchmark(
+        &self,
+        task: &str,
+        runs: usize,
+    ) -> Result<BenchmarkReport, AgentRearrangeError> {
+        if runs == 0 {
+            return Err(AgentRearrangeError::ExecutionError(
+                "Benchmark needs at least one run".to_string(),
+            ));
+        }
+
+        let mut agent_samples: BTreeMap<String, Vec<Duration>> = BTreeMap::new();
+        let mut total_samples = Vec::with_capacity(runs);
+        for _ in 0..runs {
+            let mut rearrange = self.clone_for_task();
+            let started = Instant::now();
+            let (_, timings, _) = rearrange.run_timed(task, None, None).await?;
+            total_samples.push(started.elapsed());
+
+            for (agent_name, elapsed) in timings {
+                agent_samples.entry(agent_name).or_default().push(elapsed);
+            }
+        }
+
+        Ok(BenchmarkReport {
+            flow: self.flow.clone(),
+            runs,
+            agents: agent_samples
+                .into_iter()
+                .filter_map(|(name, samples)| Some((name, LatencyStats::from_samples(&samples)?)))
+                .collect(),
+            total: LatencyStats::from_samples(&total_samples)
+                .expect("at least one run was benchmarked"),
+        })
+    }
+
     /// Create a lightweight clone for task execution
     fn clone_for_task(&self) -> Self {
         let mut cloned_agents = HashMap::new();
@@ -737,6 +1319,12 @@ impl AgentRearrange {
             tasks: DashSet::new(),
             rules: self.rules.clone(),
             team_awareness: self.team_awareness,
+            max_parallel_agents: self.max_parallel_agents,
+            callbacks: self.callbacks.clone(),
+            voting_weights: self.voting_weights.clone(),
+            voting_judge: Arc::clone(&self.voting_judge),
+            input_mode: self.input_mode,
+            swarm_type: self.swarm_type,
         }
     }
 
@@ -752,11 +1340,15 @@ impl AgentRearrange {
     }
 
     /// Convert the agent rearrange instance to metadata for persistence
-    fn to_metadata(&self) -> serde_json::Value {
+    ///
+    /// The `swarm_type` field matches the format used by the Swarms API, so local
+    /// results can be compared with remote ones.
+    pub fn to_metadata(&self) -> serde_json::Value {
         serde_json::json!({
             "id": self.id,
             "name": self.name,
             "description": self.description,
+            "swarm_type": self.swarm_type,
             "flow": self.flow,
             "max_loops": self.max_loops,
             "agents": self.agents.keys().collect::<Vec<_>>(),
@@ -780,6 +1372,11 @@ impl AgentRearrange {
         &self.description
     }
 
+    /// Get the swarm type of this instance
+    pub fn swarm_type(&self) -> SwarmType {
+        self.swarm_type
+    }
+
     /// Get the current flow pattern
     pub fn flow(&self) -> &str {
         &self.flow
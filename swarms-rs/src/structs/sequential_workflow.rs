@@ -4,7 +4,8 @@ use std::{
     path::Path,
 };
 
-use chrono::Local;
+use chrono::{Local, Utc};
+use futures::future::BoxFuture;
 use thiserror::Error;
 use twox_hash::XxHash3_64;
 use uuid::Uuid;
@@ -13,7 +14,7 @@ use crate::structs::{
     agent::{Agent, AgentError},
     conversation::{AgentConversation, Role},
     persistence,
-    swarm::MetadataSchema,
+    swarm::{MetadataSchema, SkippedAgent, Swarm, SwarmError, SwarmMetadata},
     utils::run_agent_with_output_schema,
 };
 
@@ -22,6 +23,7 @@ pub struct SequentialWorkflowBuilder {
     description: String,
     metadata_output_dir: String,
     agents: Vec<Box<dyn Agent>>,
+    skip_on_error: bool,
 }
 
 impl SequentialWorkflowBuilder {
@@ -50,12 +52,23 @@ impl SequentialWorkflowBuilder {
         self
     }
 
+    /// When an agent fails, pass the last successful output (or the task, if no agent
+    /// succeeded yet) on to the next agent instead of aborting the workflow.
+    ///
+    /// Skipped agents are listed with their errors in
+    /// [`SwarmMetadata::skipped_agents`]. Disabled by default.
+    pub fn skip_on_error(mut self, skip_on_error: bool) -> Self {
+        self.skip_on_error = skip_on_error;
+        self
+    }
+
     pub fn build(self) -> SequentialWorkflow {
         SequentialWorkflow {
             name: self.name,
             description: self.description,
             metadata_output_dir: self.metadata_output_dir,
             agents: self.agents,
+            skip_on_error: self.skip_on_error,
         }
     }
 }
@@ -65,6 +78,7 @@ pub struct SequentialWorkflow {
     description: String,
     metadata_output_dir: String,
     agents: Vec<Box<dyn Agent>>,
+    skip_on_error: bool,
 }
 
 impl SequentialWorkflow {
@@ -74,6 +88,55 @@ impl SequentialWorkflow {
             description: "A Workflow to solve a problem with sequential agents, each agent's output becomes the input for the next agent.".to_string(),
             metadata_output_dir: "./temp/sequential_workflow/metadata".to_string(),
             agents: Vec::new(),
+            skip_on_error: false,
+        }
+    }
+
+    /// Insert `agent` so that it runs at `position`, shifting later agents back.
+    /// `position` may equal the number of agents, appending the agent.
+    pub fn insert_agent_at(
+        &mut self,
+        position: usize,
+        agent: Box<dyn Agent>,
+    ) -> Result<(), SequentialWorkflowError> {
+        if position > self.agents.len() {
+            return Err(self.invalid_position(position));
+        }
+        self.agents.insert(position, agent);
+        Ok(())
+    }
+
+    /// Remove and return the agent at `position`, shifting later agents forward.
+    pub fn remove_agent_at(
+        &mut self,
+        position: usize,
+    ) -> Result<Box<dyn Agent>, SequentialWorkflowError> {
+        if position >= self.agents.len() {
+            return Err(self.invalid_position(position));
+        }
+        Ok(self.agents.remove(position))
+    }
+
+    /// Swap the agents at positions `i` and `j`.
+    pub fn swap_agents(&mut self, i: usize, j: usize) -> Result<(), SequentialWorkflowError> {
+        for position in [i, j] {
+            if position >= self.agents.len() {
+                return Err(self.invalid_position(position));
+            }
+        }
+        self.agents.swap(i, j);
+        Ok(())
+    }
+
+    /// Names of the agents, in the order they run.
+    pub fn agent_names(&self) -> Vec<String> {
+        self.agents.iter().map(|agent| agent.name()).collect()
+    }
+
+    fn invalid_position(&self, position: usize) -> SequentialWorkflowError {
+        SequentialWorkflowError::InvalidPosition {
+            position,
+            len: self.agents.len(),
         }
     }
 
@@ -81,7 +144,18 @@ impl SequentialWorkflow {
         &self,
         task: impl Into<String>,
     ) -> Result<AgentConversation, SequentialWorkflowError> {
+        let (conversation, _) = self.run_with_metadata(task).await?;
+        Ok(conversation)
+    }
+
+    /// Runs the workflow like [`run`](Self::run), also returning a [`SwarmMetadata`]
+    /// report of the run, which is saved to `metadata_output_dir` as well.
+    pub async fn run_with_metadata(
+        &self,
+        task: impl Into<String>,
+    ) -> Result<(AgentConversation, SwarmMetadata), SequentialWorkflowError> {
         let task = task.into();
+        let started_at = Utc::now();
 
         if self.agents.is_empty() {
             return Err(SequentialWorkflowError::NoAgents);
@@ -96,8 +170,21 @@ impl SequentialWorkflow {
 
         let mut next_input = task.clone();
         let mut agents_output_schema = Vec::with_capacity(self.agents.len());
+        let mut skipped_agents = Vec::new();
         for agent in &self.agents {
-            let output = run_agent_with_output_schema(agent.deref(), next_input.clone()).await?;
+            let output = match run_agent_with_output_schema(agent.deref(), next_input.clone()).await
+            {
+                Ok(output) => output,
+                Err(e) if self.skip_on_error => {
+                    tracing::warn!("Skipping agent {} after error: {e}", agent.name());
+                    skipped_agents.push(SkippedAgent {
+                        agent_name: agent.name(),
+                        error: e.to_string(),
+                    });
+                    continue;
+                },
+                Err(e) => return Err(e.into()),
+            };
             conversation.add(Role::Assistant(agent.name()), output.output.clone());
             next_input = format!("[From Agent] {}:\n{}", agent.name(), output.output);
             agents_output_schema.push(output);
@@ -121,7 +208,35 @@ impl SequentialWorkflow {
         let metadata_data = serde_json::to_string_pretty(&metadata)?;
         persistence::save_to_file(metadata_data, &metadata_output_dir).await?;
 
-        Ok(conversation)
+        let mut run_metadata = SwarmMetadata::new(
+            started_at,
+            self.agents.iter().map(|agent| agent.name()).collect(),
+            &task,
+            &conversation.entries(),
+            metadata
+                .agents_output_schema
+                .last()
+                .map_or("", |output| output.output.as_str()),
+        );
+        run_metadata.skipped_agents = skipped_agents;
+        run_metadata.save(metadata_path_dir).await?;
+
+        Ok((conversation, run_metadata))
+    }
+}
+
+impl Swarm for SequentialWorkflow {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, task: String) -> BoxFuture<Result<Box<dyn erased_serde::Serialize>, SwarmError>> {
+        Box::pin(async move {
+            self.run(task)
+                .await
+                .map(|output| Box::new(output) as _)
+                .map_err(|e| e.into())
+        })
     }
 }
 
@@ -131,6 +246,8 @@ pub enum SequentialWorkflowError {
     NoAgents,
     #[error("No tasks provided.")]
     NoTasks,
+    #[error("Position {position} is out of range for a workflow of {len} agents.")]
+    InvalidPosition { position: usize, len: usize },
     #[error("Agent error: {0}")]
     AgentError(#[from] AgentError),
     #[error("Persistence error: {0}")]
@@ -1,12 +1,23 @@
-use chrono::{DateTime, Local};
+use std::{
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, Utc};
 use dashmap::DashMap;
 use erased_serde::Serialize as ErasedSerialize;
 use futures::future::BoxFuture;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use twox_hash::XxHash3_64;
+use ulid::Ulid;
 use uuid::Uuid;
 
-use crate::structs::concurrent_workflow::ConcurrentWorkflowError;
+use crate::structs::{
+    concurrent_workflow::ConcurrentWorkflowError,
+    conversation::MemoryEntry,
+    persistence::{self, PersistenceError},
+};
 
 pub trait Swarm {
     fn name(&self) -> &str;
@@ -20,6 +31,12 @@ pub enum SwarmError {
     ConcurrentWorkflowError(#[from] ConcurrentWorkflowError),
     #[error("AgentRearrangeError: {0}")]
     AgentRearrangeError(#[from] crate::structs::rearrange::AgentRearrangeError),
+    #[error("SequentialWorkflowError: {0}")]
+    SequentialWorkflowError(#[from] crate::structs::sequential_workflow::SequentialWorkflowError),
+    #[error("MaltSwarmError: {0}")]
+    MaltSwarmError(#[from] crate::structs::malt::MaltSwarmError),
+    #[error("Invalid swarm configuration: {0}")]
+    InvalidConfig(String),
 }
 
 #[derive(Clone, Default, Serialize)]
@@ -50,3 +67,69 @@ pub struct AgentOutputSchema {
     pub end: DateTime<Local>,
     pub duration: i64,
 }
+
+/// Characters of the task kept in [`SwarmMetadata::task_summary`].
+pub const TASK_SUMMARY_CHARS: usize = 200;
+
+/// A report on a single swarm run, saved next to the run's conversation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SwarmMetadata {
+    /// ULID of the run, so reports sort by start time
+    pub run_id: String,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    /// Estimated tokens of every message of the run, see
+    /// [`estimate_tokens`](crate::structs::conversation::estimate_tokens)
+    pub total_tokens: u64,
+    /// Agents that took part in the run, in execution order
+    pub agent_names: Vec<String>,
+    /// The task, truncated to [`TASK_SUMMARY_CHARS`] characters
+    pub task_summary: String,
+    /// Hex XxHash3 hash of the final output, to compare outputs across runs
+    pub output_hash: String,
+    /// Agents whose failure was skipped instead of aborting the run, in execution order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_agents: Vec<SkippedAgent>,
+}
+
+/// An agent that failed during a run that continued without its output.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedAgent {
+    pub agent_name: String,
+    pub error: String,
+}
+
+impl SwarmMetadata {
+    /// Metadata for a run that started at `started_at` and completes now.
+    pub fn new(
+        started_at: DateTime<Utc>,
+        agent_names: Vec<String>,
+        task: &str,
+        entries: &[MemoryEntry],
+        output: &str,
+    ) -> Self {
+        let mut hasher = XxHash3_64::default();
+        output.hash(&mut hasher);
+
+        Self {
+            run_id: Ulid::new().to_string(),
+            started_at,
+            completed_at: Utc::now(),
+            total_tokens: entries
+                .iter()
+                .map(|entry| entry.estimated_tokens as u64)
+                .sum(),
+            agent_names,
+            task_summary: task.chars().take(TASK_SUMMARY_CHARS).collect(),
+            output_hash: format!("{:016x}", hasher.finish()),
+            skipped_agents: Vec::new(),
+        }
+    }
+
+    /// Saves the metadata as `<run_id>.json` in `dir`, returning the file path.
+    pub async fn save(&self, dir: impl AsRef<Path>) -> Result<PathBuf, PersistenceError> {
+        let path = dir.as_ref().join(&self.run_id).with_extension("json");
+        persistence::save_to_file(serde_json::to_string_pretty(self)?, &path).await?;
+        Ok(path)
+    }
+}
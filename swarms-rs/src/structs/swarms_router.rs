@@ -1,5 +1,5 @@
 use dashmap::DashMap;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::agent::SwarmsAgent;
 use crate::llm::provider::openai::OpenAI;
@@ -8,16 +8,19 @@ use crate::structs::agent::Agent;
 use crate::structs::concurrent_workflow::ConcurrentWorkflow;
 use crate::structs::concurrent_workflow::ConcurrentWorkflowError;
 use crate::structs::conversation::AgentConversation;
+use crate::structs::malt::{MaltSwarm, MaltSwarmError};
 use crate::structs::rearrange::AgentRearrange;
 use crate::structs::sequential_workflow::SequentialWorkflow;
 use crate::structs::sequential_workflow::SequentialWorkflowError;
+use crate::structs::swarm::{Swarm, SwarmError};
 
 /// The different allowed types of Swarms
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SwarmType {
     SequentialWorkflow,
     ConcurrentWorkflow,
     AgentRearrange,
+    Malt,
 }
 
 /// Configuration model for SwarmsRouter
@@ -114,6 +117,12 @@ impl SwarmRouterConfig {
             )));
         }
 
+        if self.swarm_type == SwarmType::Malt && self.agents.len() < 3 {
+            return Err(SwarmRouterError::ValidationError(String::from(
+                "Malt requires at least 3 agents: a planner, one or more writers and a polisher.",
+            )));
+        }
+
         Ok(())
     }
 }
@@ -126,10 +135,12 @@ impl SwarmRouterConfig {
 ///     - SequentialWorkflow: Executes tasks sequentially
 ///     - ConcurrentWorkflow: Executes tasks in parallel
 ///     - AgentRearrange: Executes tasks with custom flow patterns
+///     - Malt: Plans a long-form document, writes its sections in parallel and polishes it
 pub enum SwarmRouter {
     SequentialWorkflow(SequentialWorkflow),
     ConcurrentWorkflow(ConcurrentWorkflow),
     AgentRearrange(AgentRearrange),
+    Malt(MaltSwarm),
 }
 
 impl SwarmRouter {
@@ -228,6 +239,7 @@ impl SwarmRouter {
                 // In the future, we could implement a conversion from AgentRearrange's conversation
                 conversation
             },
+            SwarmRouter::Malt(malt) => malt.run(task).await?.conversation,
         };
         tracing::info!("Swarm completed successfully");
 
@@ -258,6 +270,14 @@ impl SwarmRouter {
                 }
                 results
             },
+            SwarmRouter::Malt(malt) => {
+                let results = DashMap::with_capacity(tasks.len());
+                for task in tasks {
+                    let output = malt.run(&task).await?;
+                    results.insert(task, output.conversation);
+                }
+                results
+            },
         };
         tracing::info!("Swarm completed successfully");
 
@@ -269,6 +289,7 @@ impl SwarmRouter {
             SwarmRouter::SequentialWorkflow(_) => SwarmType::SequentialWorkflow,
             SwarmRouter::ConcurrentWorkflow(_) => SwarmType::ConcurrentWorkflow,
             SwarmRouter::AgentRearrange(_) => SwarmType::AgentRearrange,
+            SwarmRouter::Malt(_) => SwarmType::Malt,
         }
     }
 
@@ -317,6 +338,20 @@ impl SwarmRouter {
                 let rearrange = builder.build();
                 SwarmRouter::AgentRearrange(rearrange)
             },
+            SwarmType::Malt => {
+                // The first agent plans, the last one polishes and the rest write
+                let mut writers = agents;
+                let planner = writers.remove(0);
+                let polisher = writers.pop().expect("validated to have at least 3 agents");
+                let malt = MaltSwarm::builder()
+                    .name(config.name)
+                    .description(config.description)
+                    .planner(planner)
+                    .writers(writers)
+                    .polisher(polisher)
+                    .build();
+                SwarmRouter::Malt(malt)
+            },
         }
     }
 }
@@ -356,6 +391,112 @@ pub async fn swarm_router(
     Ok(result)
 }
 
+/// Options for [`build_swarm`]. Unset fields keep the defaults of the chosen swarm.
+#[derive(Debug, Clone, Default)]
+pub struct SwarmOptions {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Flow pattern for AgentRearrange, defaults to running the agents in order
+    pub flow: Option<String>,
+    /// Maximum loops for AgentRearrange
+    pub max_loops: Option<u32>,
+    /// Rules for AgentRearrange
+    pub rules: Option<String>,
+}
+
+/// Build the local swarm implementation matching `swarm_type`.
+///
+/// Unlike [`SwarmRouter`], any [`Agent`] can be used and the swarm is returned as a
+/// [`Swarm`] trait object, so callers that pick the swarm type at runtime (from a config
+/// file or a request) can run it without matching on the type themselves.
+///
+/// For [`SwarmType::Malt`] the first agent plans, the last one polishes and the rest
+/// write, as in [`SwarmRouter`].
+///
+/// # Error
+///
+/// - SwarmError::InvalidConfig: If no agents are given, or fewer than 3 for Malt
+pub fn build_swarm(
+    swarm_type: SwarmType,
+    agents: Vec<Box<dyn Agent>>,
+    opts: SwarmOptions,
+) -> Result<Box<dyn Swarm>, SwarmError> {
+    if agents.is_empty() {
+        return Err(SwarmError::InvalidConfig(String::from(
+            "No agents provided for the swarm.",
+        )));
+    }
+
+    let swarm: Box<dyn Swarm> = match swarm_type {
+        SwarmType::SequentialWorkflow => {
+            let mut builder = SequentialWorkflow::builder().agents(agents);
+            if let Some(name) = opts.name {
+                builder = builder.name(name);
+            }
+            if let Some(description) = opts.description {
+                builder = builder.description(description);
+            }
+            Box::new(builder.build())
+        },
+        SwarmType::ConcurrentWorkflow => {
+            let mut builder = ConcurrentWorkflow::builder().agents(agents);
+            if let Some(name) = opts.name {
+                builder = builder.name(name);
+            }
+            if let Some(description) = opts.description {
+                builder = builder.description(description);
+            }
+            Box::new(builder.build())
+        },
+        SwarmType::AgentRearrange => {
+            let flow = opts.flow.unwrap_or_else(|| {
+                agents
+                    .iter()
+                    .map(|agent| agent.name())
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            });
+            let mut builder = AgentRearrange::builder().agents(agents).flow(flow);
+            if let Some(name) = opts.name {
+                builder = builder.name(name);
+            }
+            if let Some(description) = opts.description {
+                builder = builder.description(description);
+            }
+            if let Some(max_loops) = opts.max_loops {
+                builder = builder.max_loops(max_loops);
+            }
+            if let Some(rules) = opts.rules {
+                builder = builder.rules(rules);
+            }
+            Box::new(builder.build())
+        },
+        SwarmType::Malt => {
+            if agents.len() < 3 {
+                return Err(SwarmError::InvalidConfig(String::from(
+                    "Malt requires at least 3 agents: a planner, one or more writers and a polisher.",
+                )));
+            }
+            let mut writers = agents;
+            let planner = writers.remove(0);
+            let polisher = writers.pop().expect("checked to have at least 3 agents");
+            let mut builder = MaltSwarm::builder()
+                .planner(planner)
+                .writers(writers)
+                .polisher(polisher);
+            if let Some(name) = opts.name {
+                builder = builder.name(name);
+            }
+            if let Some(description) = opts.description {
+                builder = builder.description(description);
+            }
+            Box::new(builder.build())
+        },
+    };
+
+    Ok(swarm)
+}
+
 fn boxed_agent(agent: SwarmsAgent<OpenAI>) -> Box<dyn Agent> {
     Box::new(agent)
 }
@@ -373,4 +514,7 @@ pub enum SwarmRouterError {
 
     #[error(transparent)]
     AgentRearrangeError(#[from] crate::structs::rearrange::AgentRearrangeError),
+
+    #[error(transparent)]
+    MaltSwarmError(#[from] MaltSwarmError),
 }
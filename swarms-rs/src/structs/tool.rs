@@ -5,19 +5,75 @@ use rmcp::{
     service::{DynService, RunningService},
 };
 use serde::{Deserialize, Serialize};
-use std::{future::Future, ops::Deref, sync::Arc};
+use std::{future::Future, ops::Deref, sync::Arc, time::Duration};
 use thiserror::Error;
 
 use crate::llm::request::ToolDefinition;
 
+/// Why a tool call failed, so that the agent can react to each case differently.
 #[derive(Debug, thiserror::Error)]
 pub enum ToolError {
-    /// Error returned by the tool
-    #[error("ToolCallError: {0}")]
-    ToolCallError(#[from] Box<dyn core::error::Error + Send + Sync>),
+    /// The arguments could not be parsed or were rejected. The model can usually correct
+    /// them when told what was wrong
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
 
-    #[error("JsonError: {0}")]
-    JsonError(#[from] serde_json::Error),
+    /// The tool ran and reported a failure
+    #[error("Execution failed: {0}")]
+    ExecutionFailed(String),
+
+    /// The tool did not finish within the agent's tool timeout
+    #[error("Tool {name} timed out after {timeout:?}")]
+    Timeout { name: String, timeout: Duration },
+
+    /// No tool with this name is available
+    #[error("Tool {0} not found")]
+    NotFound(String),
+
+    /// A failure outside the tool itself, e.g. the connection to an MCP server
+    #[error("Upstream error: {0}")]
+    Upstream(#[from] anyhow::Error),
+}
+
+impl ToolError {
+    /// A generic suggestion for recovering from this kind of failure, see
+    /// [`ToolDyn::describe_failure`].
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            ToolError::InvalidArguments(_) => {
+                "Check the arguments against the tool's parameter schema and call it again."
+            },
+            ToolError::ExecutionFailed(_) => {
+                "Adjust the arguments based on the error, or try a different approach."
+            },
+            ToolError::Timeout { .. } => "Try a smaller request, or continue without this tool.",
+            ToolError::NotFound(_) => "Call one of the available tools instead.",
+            ToolError::Upstream(_) => {
+                "The service behind the tool failed; retry later or continue without it."
+            },
+        }
+    }
+}
+
+/// Output of a tool that returns either prose or structured data, e.g. a chart's values.
+///
+/// Tools returning `ToolOutput` hand it to the model as is instead of JSON-encoding it:
+/// text verbatim and JSON as a JSON document. There is no image variant, because agent
+/// memory keeps tool results as text and the model couldn't see an image in it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolOutput {
+    Text(String),
+    Json(serde_json::Value),
+}
+
+impl std::fmt::Display for ToolOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToolOutput::Text(text) => f.write_str(text),
+            ToolOutput::Json(value) => write!(f, "{value}"),
+        }
+    }
 }
 
 pub trait Tool: Sized + Send + Sync {
@@ -34,10 +90,35 @@ pub trait Tool: Sized + Send + Sync {
         args: Self::Args,
     ) -> impl Future<Output = Result<Self::Output, Self::Error>> + Send + Sync;
 
-    // Provided method
+    // Provided methods
     fn name(&self) -> String {
         Self::NAME.to_string()
     }
+
+    /// JSON schema describing the tool's output, included in its [`ToolDefinition`].
+    fn output_schema(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Renders the output handed back to the model, as JSON by default. Tools returning
+    /// [`ToolOutput`] should render it with its `Display` implementation instead, as
+    /// `#[tool]` functions do.
+    fn format_output(&self, output: Self::Output) -> Result<String, ToolError> {
+        serde_json::to_string(&output)
+            .map_err(|e| ToolError::ExecutionFailed(format!("failed to serialize output: {e}")))
+    }
+
+    /// Checks the parsed arguments before [`call`](Tool::call). An `Err` holds the reason
+    /// they were rejected and fails the call with [`ToolError::InvalidArguments`].
+    fn validate(&self, _args: &Self::Args) -> impl Future<Output = Result<(), String>> + Send {
+        async { Ok(()) }
+    }
+
+    /// The message handed back to the model when a call fails, see
+    /// [`ToolDyn::describe_failure`].
+    fn describe_failure(&self, error: &ToolError) -> String {
+        format!("{error}\n{}", error.suggestion())
+    }
 }
 
 pub trait ToolDyn: Send + Sync {
@@ -46,6 +127,15 @@ pub trait ToolDyn: Send + Sync {
     fn definition(&self) -> ToolDefinition;
 
     fn call(&self, args: String) -> BoxFuture<Result<String, ToolError>>;
+
+    /// The message handed back to the model when a call fails, instead of the raw error.
+    ///
+    /// Defaults to the error followed by [`ToolError::suggestion`]. Override it for
+    /// domain-specific recovery hints, e.g. "The file was not found. Check that the path
+    /// is within the allowed directories."
+    fn describe_failure(&self, error: &ToolError) -> String {
+        format!("{error}\n{}", error.suggestion())
+    }
 }
 
 impl<T: Tool> ToolDyn for T {
@@ -54,22 +144,82 @@ impl<T: Tool> ToolDyn for T {
     }
 
     fn definition(&self) -> ToolDefinition {
-        <Self as Tool>::definition(self)
+        let mut definition = <Self as Tool>::definition(self);
+        if definition.output_schema.is_none() {
+            definition.output_schema = <Self as Tool>::output_schema(self);
+        }
+        definition
     }
 
     fn call(&self, args: String) -> BoxFuture<Result<String, ToolError>> {
         Box::pin(async move {
             match serde_json::from_str(&args) {
-                Ok(args) => <Self as Tool>::call(self, args)
-                    .await
-                    .map_err(|e| ToolError::ToolCallError(Box::new(e)))
-                    .and_then(|output| {
-                        serde_json::to_string(&output).map_err(ToolError::JsonError)
-                    }),
-                Err(e) => Err(ToolError::JsonError(e)),
+                Ok(args) => {
+                    <Self as Tool>::validate(self, &args)
+                        .await
+                        .map_err(ToolError::InvalidArguments)?;
+                    <Self as Tool>::call(self, args)
+                        .await
+                        .map_err(|e| ToolError::ExecutionFailed(e.to_string()))
+                        .and_then(|output| <Self as Tool>::format_output(self, output))
+                },
+                Err(e) => Err(ToolError::InvalidArguments(e.to_string())),
             }
         })
     }
+
+    fn describe_failure(&self, error: &ToolError) -> String {
+        <Self as Tool>::describe_failure(self, error)
+    }
+}
+
+/// A named bundle of tools that can be added to an agent in one call.
+///
+/// Toolkits make it easy to package a cohesive set of tools (e.g. a "filesystem toolkit")
+/// and reuse it across agents via `SwarmsAgentBuilder::add_toolkit`.
+#[derive(Clone, Default)]
+pub struct Toolkit {
+    name: String,
+    tools: Vec<Arc<dyn ToolDyn>>,
+}
+
+impl Toolkit {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            tools: Vec::new(),
+        }
+    }
+
+    pub fn add_tool(mut self, tool: impl ToolDyn + 'static) -> Self {
+        self.tools.push(Arc::new(tool));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn tools(&self) -> &[Arc<dyn ToolDyn>] {
+        &self.tools
+    }
+
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+}
+
+impl IntoIterator for Toolkit {
+    type Item = Arc<dyn ToolDyn>;
+    type IntoIter = std::vec::IntoIter<Arc<dyn ToolDyn>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tools.into_iter()
+    }
 }
 
 pub struct MCPTool {
@@ -84,6 +234,39 @@ impl MCPTool {
     ) -> Self {
         Self { tool, client }
     }
+
+    /// Checks that `tool` advertises a well-formed input schema.
+    ///
+    /// The schema must be a JSON Schema object of `"type": "object"`; its `properties`,
+    /// `items` and `required` keywords are checked recursively. Tools failing this check
+    /// would otherwise only error once the model tries to call them.
+    pub fn validate_schema(tool: &rmcp::model::Tool) -> Result<(), MCPToolError> {
+        let schema = tool.input_schema.deref();
+        if schema.is_empty() {
+            return Err(MCPToolError("missing input schema".to_owned()));
+        }
+        if schema.get("type").and_then(serde_json::Value::as_str) != Some("object") {
+            return Err(MCPToolError(
+                r#"input schema must have "type": "object""#.to_owned(),
+            ));
+        }
+        validate_subschema("input schema", schema).map_err(MCPToolError)
+    }
+
+    /// Checks that the MCP server still responds and still offers this tool.
+    ///
+    /// Returns `Ok(false)` if the server answers but no longer lists the tool, and an
+    /// error if the server cannot be reached.
+    pub fn health_check(&self) -> BoxFuture<'_, Result<bool, ToolError>> {
+        Box::pin(async move {
+            let tools = self
+                .client
+                .list_all_tools()
+                .await
+                .map_err(|e| MCPToolError(format!("MCP health check failed: {e}")))?;
+            Ok(tools.iter().any(|tool| tool.name == self.tool.name))
+        })
+    }
 }
 
 impl Tool for MCPTool {
@@ -118,10 +301,10 @@ impl Tool for MCPTool {
             .map_err(|e| MCPToolError(format!("MCP tool call failed: {e}")))?;
 
         if result.is_error.unwrap_or(false) {
-            return Err(ToolError::from(MCPToolError(format!(
+            return Err(ToolError::ExecutionFailed(format!(
                 "MCP tool call failed, content: {:?}",
                 result.content
-            ))));
+            )));
         }
 
         Ok(result
@@ -168,6 +351,63 @@ impl Tool for MCPTool {
     }
 }
 
+const JSON_SCHEMA_TYPES: [&str; 7] = [
+    "string", "number", "integer", "boolean", "object", "array", "null",
+];
+
+fn validate_subschema(
+    path: &str,
+    schema: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(), String> {
+    use serde_json::Value;
+
+    match schema.get("type") {
+        None => {},
+        Some(Value::String(ty)) if JSON_SCHEMA_TYPES.contains(&ty.as_str()) => {},
+        Some(Value::Array(types))
+            if types.iter().all(|ty| {
+                ty.as_str()
+                    .is_some_and(|ty| JSON_SCHEMA_TYPES.contains(&ty))
+            }) => {},
+        Some(other) => return Err(format!("{path} has an invalid type: {other}")),
+    }
+
+    let properties = match schema.get("properties") {
+        None => None,
+        Some(Value::Object(properties)) => Some(properties),
+        Some(_) => return Err(format!("{path}.properties must be an object")),
+    };
+    for (name, property) in properties.into_iter().flatten() {
+        let Value::Object(property) = property else {
+            return Err(format!("{path}.properties.{name} must be an object"));
+        };
+        validate_subschema(&format!("{path}.properties.{name}"), property)?;
+    }
+
+    match schema.get("required") {
+        None => {},
+        Some(Value::Array(required)) => {
+            for name in required {
+                let Some(name) = name.as_str() else {
+                    return Err(format!("{path}.required must only contain strings"));
+                };
+                if properties.is_some_and(|properties| !properties.contains_key(name)) {
+                    return Err(format!(
+                        "{path}.required lists `{name}`, which is not a property"
+                    ));
+                }
+            }
+        },
+        Some(_) => return Err(format!("{path}.required must be an array")),
+    }
+
+    match schema.get("items") {
+        None => Ok(()),
+        Some(Value::Object(items)) => validate_subschema(&format!("{path}.items"), items),
+        Some(_) => Err(format!("{path}.items must be an object")),
+    }
+}
+
 #[derive(Debug, Error)]
 #[error("MCPToolError: {0}")]
 pub struct MCPToolError(String);
@@ -183,16 +423,18 @@ impl From<&rmcp::model::Tool> for ToolDefinition {
             .to_string();
         let parameters = serde_json::Value::Object(value.input_schema.deref().to_owned());
 
+        // rmcp 0.1.5 does not expose `outputSchema` from the server's tool listing yet
         Self {
             name,
             description,
             parameters,
+            output_schema: None,
         }
     }
 }
 
 impl From<MCPToolError> for ToolError {
     fn from(value: MCPToolError) -> Self {
-        Self::ToolCallError(Box::new(value))
+        Self::Upstream(value.into())
     }
 }
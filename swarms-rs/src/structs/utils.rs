@@ -1,4 +1,5 @@
 use chrono::Local;
+use serde::de::DeserializeOwned;
 use uuid::Uuid;
 
 use crate::structs::{
@@ -28,3 +29,51 @@ pub async fn run_agent_with_output_schema(
 
     Ok(agent_output)
 }
+
+/// Extracts the first JSON object or array in `text` that deserializes into `T`.
+///
+/// Models often wrap JSON in prose or Markdown code fences, so the whole text is tried
+/// first and then every balanced `{...}` or `[...]` span, in order of appearance.
+pub fn extract_json<T: DeserializeOwned>(text: &str) -> Option<T> {
+    if let Ok(value) = serde_json::from_str(text.trim()) {
+        return Some(value);
+    }
+
+    text.char_indices()
+        .filter(|(_, c)| matches!(c, '{' | '['))
+        .filter_map(|(start, _)| {
+            balanced_json_end(&text[start..]).map(|end| &text[start..start + end])
+        })
+        .find_map(|candidate| serde_json::from_str(candidate).ok())
+}
+
+/// Byte length of the balanced JSON object or array at the start of `text`, skipping
+/// brackets inside string literals.
+fn balanced_json_end(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {},
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            },
+            _ => {},
+        }
+    }
+    None
+}
@@ -130,6 +130,7 @@ mod unit_tests {
             name: "test_tool".to_string(),
             description: "A test tool".to_string(),
             parameters: json!({"type": "object", "properties": {"param": {"type": "string"}}}),
+            output_schema: None,
         };
 
         assert_eq!(tool.name, "test_tool");
@@ -342,6 +343,7 @@ mod integration_tests {
                 },
                 "required": ["location"]
             }),
+            output_schema: None,
         }];
 
         let client = Anthropic::from_env();
@@ -377,6 +379,7 @@ mod integration_tests {
                 );
                 println!("Tool response (tool call): {}", tool_call.function.name);
             },
+            AssistantContent::Thinking(_) => panic!("Thinking is not enabled"),
         }
     }
 }
@@ -1,7 +1,9 @@
 //! Tests for Agent Configuration
 //! This module tests the agent configuration builder and agent config struct
 
-use swarms_rs::structs::agent::{AgentConfig, AgentError};
+use swarms_rs::structs::agent::{
+    AgentBuildError, AgentConfig, AgentError, ConfigChange, ConfigSnapshot, StopWordMatching,
+};
 use tempfile::tempdir;
 
 #[test]
@@ -371,3 +373,171 @@ mod agent_error_tests {
         assert_eq!(error.to_string(), "No choice found");
     }
 }
+
+#[test]
+fn test_agent_config_presets() {
+    let coding = AgentConfig::coding();
+    let research = AgentConfig::research();
+    let creative = AgentConfig::creative();
+
+    assert!(coding.temperature < research.temperature);
+    assert!(research.temperature < creative.temperature);
+    assert_eq!(research.max_loops, 5);
+    assert!(coding.task_evaluator_tool_enabled);
+    assert!(!creative.task_evaluator_tool_enabled);
+
+    for config in [&coding, &research, &creative] {
+        assert!(config.plan_enabled);
+        assert!(config.planning_prompt.as_deref().unwrap().ends_with(':'));
+        // Everything else keeps the defaults
+        assert_eq!(config.max_tokens, AgentConfig::default().max_tokens);
+    }
+    assert_ne!(coding.id, research.id);
+}
+
+#[test]
+fn test_agent_config_diff() {
+    let before = AgentConfig::builder()
+        .agent_name("Agent")
+        .temperature(0.7)
+        .add_stop_word("DONE")
+        .build();
+    let after = AgentConfig::builder()
+        .agent_name("Agent")
+        .temperature(0.2)
+        .add_stop_word("FINISHED")
+        .enable_autosave()
+        .build();
+
+    // Distinct ids alone are not a change
+    assert!(before.diff(&before.as_ref().clone()).is_empty());
+
+    let changes = before.diff(&after);
+    assert_eq!(
+        changes,
+        [
+            ConfigChange::Temperature { from: 0.7, to: 0.2 },
+            ConfigChange::StopWords {
+                added: vec!["FINISHED".to_string()],
+                removed: vec!["DONE".to_string()],
+            },
+            ConfigChange::Other {
+                field: "autosave".to_string(),
+                from: serde_json::json!(false),
+                to: serde_json::json!(true),
+            },
+        ]
+    );
+    assert_eq!(changes[0].to_string(), "temperature: 0.7 -> 0.2");
+}
+
+#[tokio::test]
+async fn test_config_snapshot_round_trip() {
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("agent.json");
+
+    let snapshot = AgentConfig::builder().max_loops(3).build().snapshot();
+    snapshot.save(&path).await.unwrap();
+
+    let loaded = ConfigSnapshot::load(&path).await.unwrap();
+    assert_eq!(loaded.taken_at, snapshot.taken_at);
+    assert!(loaded.diff(&snapshot).is_empty());
+
+    let changed = AgentConfig::builder().max_loops(4).build().snapshot();
+    assert_eq!(
+        loaded.diff(&changed),
+        [ConfigChange::MaxLoops { from: 3, to: 4 }]
+    );
+}
+
+#[tokio::test]
+async fn test_config_snapshot_loads_without_newer_fields() {
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("agent.json");
+
+    // A snapshot written before these settings existed
+    let snapshot = AgentConfig::builder().max_loops(3).build().snapshot();
+    let mut json = serde_json::to_value(&snapshot).unwrap();
+    let config = json["config"].as_object_mut().unwrap();
+    for field in ["incremental_save", "stop_word_matching", "tools_only"] {
+        assert!(config.remove(field).is_some());
+    }
+    std::fs::write(&path, serde_json::to_string(&json).unwrap()).unwrap();
+
+    let loaded = ConfigSnapshot::load(&path).await.unwrap();
+    assert_eq!(loaded.config.max_loops, 3);
+    assert_eq!(
+        loaded.config.stop_word_matching,
+        StopWordMatching::WholeWord
+    );
+    assert!(!loaded.config.incremental_save && !loaded.config.tools_only);
+}
+
+#[test]
+fn test_agent_config_reload_from_env() {
+    let mut config = AgentConfig::builder().temperature(0.7).max_loops(1).build();
+
+    // SAFETY: no other test reads or writes these variables
+    unsafe {
+        std::env::set_var("SWARMS_TEMPERATURE", "0.2");
+        std::env::set_var("SWARMS_MAX_LOOPS", "4");
+        std::env::set_var("SWARMS_STOP_WORDS", "DONE, STOP");
+    }
+    let changes = config.reload_from_env().unwrap();
+    assert_eq!(config.temperature, 0.2);
+    assert_eq!(config.max_loops, 4);
+    assert!(config.stop_words.contains("DONE") && config.stop_words.contains("STOP"));
+    assert!(changes.contains(&ConfigChange::MaxLoops { from: 1, to: 4 }));
+
+    // An invalid value leaves the whole config untouched
+    unsafe {
+        std::env::set_var("SWARMS_TEMPERATURE", "0.9");
+        std::env::set_var("SWARMS_MAX_LOOPS", "many");
+    }
+    assert!(matches!(
+        config.reload_from_env(),
+        Err(AgentError::InvalidEnvVar { name, value })
+            if name == "SWARMS_MAX_LOOPS" && value == "many"
+    ));
+    assert_eq!(config.temperature, 0.2);
+
+    // So does a value that fails validation
+    unsafe {
+        std::env::set_var("SWARMS_MAX_LOOPS", "0");
+    }
+    assert!(matches!(
+        config.reload_from_env(),
+        Err(AgentError::InvalidConfig(AgentBuildError::ZeroMaxLoops))
+    ));
+    assert_eq!(config.max_loops, 4);
+
+    unsafe {
+        std::env::remove_var("SWARMS_TEMPERATURE");
+        std::env::remove_var("SWARMS_MAX_LOOPS");
+        std::env::remove_var("SWARMS_STOP_WORDS");
+    }
+}
+
+#[test]
+fn test_stop_word_matching_modes() {
+    assert_eq!(
+        AgentConfig::default().stop_word_matching,
+        StopWordMatching::WholeWord
+    );
+
+    let whole_word = StopWordMatching::WholeWord;
+    assert!(!whole_word.matches("The plan was abandoned", "done"));
+    assert!(whole_word.matches("All done.", "done"));
+    assert!(whole_word.matches("Finished<DONE>", "<DONE>"));
+    assert!(StopWordMatching::Substring.matches("The plan was abandoned", "done"));
+    assert!(StopWordMatching::Regex.matches("Score: 42/100", r"\d+/100"));
+
+    let config = AgentConfig::builder()
+        .add_stop_word("(unclosed")
+        .stop_word_matching(StopWordMatching::Regex)
+        .build();
+    assert!(matches!(
+        config.validate(),
+        Err(AgentBuildError::InvalidStopWordPattern { pattern, .. }) if pattern == "(unclosed"
+    ));
+}
@@ -0,0 +1,176 @@
+//! Tests for completion message helpers
+
+use std::time::Duration;
+
+use swarms_rs::llm::completion::{
+    AssistantContent, Message, Text, ToolCall, ToolFunction, ToolResult, ToolResultContent,
+    UserContent, truncate_history,
+};
+use swarms_rs::llm::request::{CompletionRequest, CompletionResponse};
+use swarms_rs::llm::{CompletionError, parse_retry_after};
+
+fn tool_call(id: &str) -> Message {
+    Message::Assistant {
+        content: vec![AssistantContent::ToolCall(ToolCall {
+            id: id.to_string(),
+            function: ToolFunction {
+                name: "search".to_string(),
+                arguments: serde_json::json!({"query": id}),
+            },
+        })],
+    }
+}
+
+fn tool_result(id: &str) -> Message {
+    Message::User {
+        content: vec![UserContent::ToolResult(ToolResult {
+            id: id.to_string(),
+            content: vec![ToolResultContent::Text(Text {
+                text: format!("result {id}"),
+            })],
+        })],
+    }
+}
+
+#[test]
+fn test_truncate_history_keeps_recent_messages() {
+    let history: Vec<_> = (0..5)
+        .map(|i| Message::user(format!("message {i}")))
+        .collect();
+    let truncated = truncate_history(history.clone(), 2);
+    assert_eq!(truncated, history[3..]);
+
+    assert_eq!(truncate_history(history.clone(), 10), history);
+}
+
+#[test]
+fn test_truncate_history_never_orphans_tool_results() {
+    let history = vec![
+        Message::user("find the weather"),
+        tool_call("call_1"),
+        tool_result("call_1"),
+        Message::assistant("It is sunny"),
+    ];
+
+    // Keeping the last two messages naively would keep `call_1`'s result without its call
+    let truncated = truncate_history(history.clone(), 2);
+    assert_eq!(truncated, vec![Message::assistant("It is sunny")]);
+
+    // With room for the whole pair, the call and result stay together
+    let truncated = truncate_history(history.clone(), 3);
+    assert_eq!(truncated, history[1..]);
+}
+
+#[test]
+fn test_truncate_history_drops_results_without_calls() {
+    let history = vec![
+        tool_result("call_0"),
+        Message::user("next question"),
+        tool_call("call_1"),
+        tool_result("call_1"),
+    ];
+
+    let truncated = truncate_history(history.clone(), 10);
+    assert_eq!(truncated, history[1..]);
+}
+
+#[test]
+fn test_completion_response_reasoning() {
+    let response = CompletionResponse {
+        choice: vec![
+            AssistantContent::thinking("first"),
+            AssistantContent::text("answer"),
+            AssistantContent::thinking("second"),
+        ],
+        raw_response: (),
+    };
+    assert_eq!(response.reasoning().as_deref(), Some("first\n\nsecond"));
+
+    let response = CompletionResponse {
+        choice: vec![AssistantContent::text("answer")],
+        raw_response: (),
+    };
+    assert_eq!(response.reasoning(), None);
+}
+
+#[test]
+fn test_completion_response_to_assistant_message() {
+    let response = CompletionResponse {
+        choice: vec![
+            AssistantContent::text("Let me check."),
+            AssistantContent::tool_call("call_1", "search", serde_json::json!({"q": "rust"})),
+        ],
+        raw_response: (),
+    };
+
+    let message = response.to_assistant_message();
+    assert_eq!(
+        message,
+        Message::Assistant {
+            content: response.choice.clone()
+        }
+    );
+
+    let request = CompletionRequest::builder()
+        .prompt("And now?")
+        .chat_history(vec![message])
+        .build();
+    assert!(matches!(
+        &request.chat_history[0],
+        Message::Assistant { content } if content.len() == 2
+    ));
+}
+
+#[test]
+fn test_completion_request_builder() {
+    let request = CompletionRequest::builder()
+        .prompt("hello")
+        .system_prompt("Be brief".to_string())
+        .chat_history(vec![Message::assistant("hi")])
+        .temperature(0.2)
+        .max_tokens(64)
+        .build();
+    assert_eq!(request.prompt, Message::user("hello"));
+    assert_eq!(request.system_prompt.as_deref(), Some("Be brief"));
+    assert_eq!(request.chat_history, [Message::assistant("hi")]);
+    assert!(request.tools.is_empty());
+    assert_eq!(request.temperature, Some(0.2));
+    assert_eq!(request.max_tokens, Some(64));
+
+    let request = CompletionRequest::builder().build();
+    assert_eq!(request.prompt, Message::user(""));
+    assert_eq!((request.temperature, request.max_tokens), (None, None));
+}
+
+#[test]
+fn test_parse_retry_after() {
+    assert_eq!(parse_retry_after("20"), Some(Duration::from_secs(20)));
+    assert_eq!(
+        parse_retry_after(" 1.5 "),
+        Some(Duration::from_millis(1500))
+    );
+    assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None); // In the past
+    assert_eq!(parse_retry_after("soon"), None);
+
+    let date = (chrono::Utc::now() + chrono::Duration::seconds(120)).to_rfc2822();
+    let retry_after = parse_retry_after(&date).unwrap();
+    assert!(retry_after > Duration::from_secs(100) && retry_after <= Duration::from_secs(120));
+}
+
+#[test]
+fn test_openai_rate_limit_is_mapped() {
+    let error = async_openai::error::OpenAIError::ApiError(async_openai::error::ApiError {
+        message: "Rate limit reached for gpt-4o-mini. Please try again in 1m2.5s. Visit our docs."
+            .to_string(),
+        r#type: Some("requests".to_string()),
+        param: None,
+        code: Some("rate_limit_exceeded".to_string()),
+    });
+
+    match CompletionError::from(error) {
+        CompletionError::RateLimited { retry_after } => {
+            assert_eq!(retry_after, Some(Duration::from_millis(62_500)))
+        },
+        other => panic!("expected RateLimited, got {other:?}"),
+    }
+}
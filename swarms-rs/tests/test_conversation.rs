@@ -1,6 +1,7 @@
 use std::path::Path;
 use swarms_rs::structs::conversation::{
-    AgentConversation, AgentLog, Content, Message, Role, SwarmConversation,
+    AgentConversation, AgentLog, AgentShortMemory, COMPRESSED_MARKER, Content, Message, Role,
+    SwarmConversation, estimate_tokens,
 };
 use tempfile::TempDir;
 
@@ -108,6 +109,37 @@ fn test_agent_conversation_update_message() {
     assert_eq!(text, "Updated message");
 }
 
+#[test]
+fn test_agent_conversation_compress_tool_results() {
+    let mut conversation = AgentConversation::new("test_agent".to_string());
+    conversation.add(Role::User("user".to_string()), "x".repeat(100));
+    let page = "<html>".repeat(50);
+    conversation.add(
+        Role::Assistant("agent".to_string()),
+        format!(
+            "[Tool name]: fetch_url\n[Tool args]: {{\"url\":\"https://example.com\"}}\n[Tool result]: {page}\n\n\
+             [Tool name]: add\n[Tool args]: {{\"a\":1,\"b\":2}}\n[Tool result]: 3\n\n"
+        ),
+    );
+
+    let changed = conversation.compress_tool_results(20, |result| result.chars().take(6).collect());
+    assert_eq!(changed, 1);
+
+    let entries = conversation.entries();
+    assert_eq!(entries[0].content, "x".repeat(100));
+    assert_eq!(
+        entries[1].content,
+        format!(
+            "[Tool name]: fetch_url\n[Tool args]: {{\"url\":\"https://example.com\"}}\n[Tool result]: {COMPRESSED_MARKER} <html>\n\n\
+             [Tool name]: add\n[Tool args]: {{\"a\":1,\"b\":2}}\n[Tool result]: 3\n\n"
+        )
+    );
+    assert!(entries[1].estimated_tokens < estimate_tokens(&page));
+
+    // Compressed results are not compressed again
+    assert_eq!(conversation.compress_tool_results(5, |_| String::new()), 0);
+}
+
 #[test]
 fn test_agent_conversation_query_message() {
     let mut conversation = AgentConversation::new("test_agent".to_string());
@@ -461,3 +493,174 @@ fn test_content_debug() {
     assert!(debug_output.contains("Text"));
     assert!(debug_output.contains("debug test"));
 }
+
+#[test]
+fn test_short_memory_to_markdown() {
+    let memory = AgentShortMemory::new();
+    let task = "Add two numbers";
+    memory.add(task, "agent", Role::User("alice".to_string()), task);
+    memory.add(
+        task,
+        "agent",
+        Role::Assistant("agent".to_string()),
+        "[Tool name]: add\n[Tool args]: {\"a\":1,\"b\":2}\n[Tool result]: 3\n\n",
+    );
+    memory.add(
+        task,
+        "agent",
+        Role::Assistant("agent".to_string()),
+        "The sum is 3",
+    );
+
+    let markdown = memory.to_markdown(task);
+
+    assert!(markdown.starts_with("# Task\n\nAdd two numbers\n\n"));
+    assert_eq!(markdown.matches("## User\n").count(), 1);
+    assert_eq!(markdown.matches("## Assistant\n").count(), 2);
+    assert!(markdown.contains("_alice · "));
+    assert!(markdown.contains("**Tool call:** `add`"));
+    assert!(markdown.contains("```json\n{\"a\":1,\"b\":2}\n```"));
+    assert!(markdown.contains("```\n3\n```"));
+    assert!(markdown.contains("The sum is 3\n"));
+    assert!(!markdown.contains("Timestamp(millis)"));
+}
+
+#[test]
+fn test_short_memory_to_markdown_unknown_task() {
+    let memory = AgentShortMemory::new();
+    let markdown = memory.to_markdown("missing");
+    assert!(markdown.contains("_No messages recorded._"));
+}
+
+#[test]
+fn test_short_memory_entries_for() {
+    let memory = AgentShortMemory::new();
+    let task = "Count to two";
+    memory.add(task, "agent", Role::User("user".to_string()), task);
+    memory.add_in_loop(
+        task,
+        "agent",
+        Role::Assistant("agent".to_string()),
+        "one",
+        0,
+    );
+    memory.add_in_loop(
+        task,
+        "agent",
+        Role::Assistant("agent".to_string()),
+        "one two",
+        1,
+    );
+
+    let entries = memory.entries_for(task);
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].content, task);
+    assert_eq!(entries[0].loop_index, None);
+    assert_eq!(entries[1].loop_index, Some(0));
+    assert_eq!(entries[2].loop_index, Some(1));
+    assert_eq!(entries[2].content, "one two");
+    assert_eq!(entries[2].estimated_tokens, estimate_tokens("one two"));
+    assert!(entries[0].timestamp > 0);
+    assert!(entries[0].timestamp <= entries[2].timestamp);
+
+    assert!(memory.entries_for("unknown task").is_empty());
+}
+
+#[test]
+fn test_conversation_entries_follow_history_changes() {
+    let mut conversation = AgentConversation::with_max_messages("agent".to_string(), Some(2));
+    conversation.add_in_loop(Role::User("user".to_string()), "first".to_string(), Some(0));
+    conversation.add_in_loop(
+        Role::User("user".to_string()),
+        "second".to_string(),
+        Some(1),
+    );
+    conversation.add_in_loop(Role::User("user".to_string()), "third".to_string(), Some(2));
+
+    // The oldest message and its metadata were evicted together
+    let loops: Vec<_> = conversation
+        .entries()
+        .iter()
+        .map(|e| e.loop_index)
+        .collect();
+    assert_eq!(loops, [Some(1), Some(2)]);
+
+    conversation.delete(0);
+    let entries = conversation.entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].content, "third");
+    assert_eq!(entries[0].loop_index, Some(2));
+
+    // Messages pushed directly get metadata derived from their text
+    conversation.history.push(Message {
+        role: Role::Assistant("agent".to_string()),
+        content: Content::Text("external".to_string()),
+    });
+    let entries = conversation.entries();
+    assert_eq!(entries[1].content, "external");
+    assert_eq!(entries[1].loop_index, None);
+}
+
+#[test]
+fn test_role_statistics() {
+    let mut conversation = AgentConversation::new("agent".to_string());
+    conversation.add(Role::User("user".to_string()), "hi".to_string());
+    conversation.add(Role::Assistant("agent".to_string()), "hello".to_string());
+    conversation.add(
+        Role::Assistant("agent".to_string()),
+        "how are you?".to_string(),
+    );
+
+    let stats = conversation.role_statistics();
+    assert_eq!(stats.len(), 2);
+
+    let user = &stats["user(User)"];
+    assert_eq!(user.message_count, 1);
+    assert_eq!(user.total_chars, 2);
+
+    let assistant = &stats["agent(Assistant)"];
+    assert_eq!(assistant.message_count, 2);
+    assert_eq!(assistant.total_chars, 17);
+    assert!((assistant.avg_chars - 8.5).abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_system_role_messages() {
+    let mut conversation = AgentConversation::new("agent".to_string());
+    conversation.add(
+        Role::System("agent".to_string()),
+        "Persona switched".to_string(),
+    );
+
+    assert!(conversation.to_string().starts_with("agent(System): "));
+    assert!(
+        conversation
+            .to_markdown()
+            .starts_with("## System\n\n_agent · ")
+    );
+
+    let messages: Vec<swarms_rs::llm::completion::Message> = (&conversation).into();
+    let json = serde_json::to_string(&messages).unwrap();
+    assert!(json.contains(r#""role":"user""#));
+    assert!(json.contains("System: "));
+    assert!(json.contains("Persona switched"));
+}
+
+#[test]
+fn test_short_memory_fork_is_independent() {
+    let memory = AgentShortMemory::new();
+    memory.add("task", "agent", Role::User("user".to_string()), "hello");
+
+    let fork = memory.fork();
+    fork.add(
+        "task",
+        "agent",
+        Role::Assistant("agent".to_string()),
+        "branch",
+    );
+    memory.add("other", "agent", Role::User("user".to_string()), "main");
+
+    assert_eq!(memory.entries_for("task").len(), 1);
+    assert_eq!(fork.entries_for("task").len(), 2);
+    assert!(fork.entries_for("other").is_empty());
+}
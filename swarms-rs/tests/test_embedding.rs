@@ -0,0 +1,52 @@
+//! Tests for the embedding abstraction and the in-memory vector store
+
+use futures::future::BoxFuture;
+use swarms_rs::llm::CompletionError;
+use swarms_rs::llm::embedding::{EmbeddingModel, VectorStore, cosine_similarity};
+
+// Mock embedder: counts occurrences of a few keywords
+#[derive(Clone)]
+struct KeywordEmbedder;
+
+impl EmbeddingModel for KeywordEmbedder {
+    fn embed(&self, texts: Vec<String>) -> BoxFuture<'_, Result<Vec<Vec<f32>>, CompletionError>> {
+        Box::pin(async move {
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    ["rust", "python", "cooking"]
+                        .iter()
+                        .map(|keyword| text.matches(keyword).count() as f32)
+                        .collect()
+                })
+                .collect())
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_vector_store_returns_most_similar_documents() {
+    let store = VectorStore::new(KeywordEmbedder);
+    store
+        .add_documents(vec![
+            "cooking pasta".to_string(),
+            "rust ownership and rust lifetimes".to_string(),
+            "python and rust interop".to_string(),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(store.len().await, 3);
+
+    let results = store.search("rust", 2).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].text, "rust ownership and rust lifetimes");
+    assert_eq!(results[1].text, "python and rust interop");
+    assert!(results[0].score > results[1].score);
+}
+
+#[test]
+fn test_cosine_similarity() {
+    assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < f32::EPSILON);
+    assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+    assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+}
@@ -7,7 +7,7 @@ mod tests {
     use dashmap::DashMap;
     use futures::future;
 
-    use swarms_rs::structs::graph_workflow::{DAGWorkflow, Flow, GraphWorkflowError};
+    use swarms_rs::structs::graph_workflow::{DAGWorkflow, Flow, GraphWorkflowError, MermaidShape};
 
     // Import test utilities from the tests module
     use crate::test_utils::{MockAgent, create_failing_agent, create_mock_agent};
@@ -444,6 +444,34 @@ mod tests {
         assert!(has_path2);
     }
 
+    #[test]
+    fn test_find_execution_paths_with_limits() {
+        let mut workflow = DAGWorkflow::new("test", "Test workflow");
+        workflow.register_agent(create_mock_agent("0", "start", "Start", "start"));
+        workflow.register_agent(create_mock_agent("1", "a", "Middle A", "a"));
+        workflow.register_agent(create_mock_agent("2", "b", "Middle B", "b"));
+        workflow.register_agent(create_mock_agent("3", "end", "End", "end"));
+        for (from, to) in [("start", "a"), ("start", "b"), ("a", "end"), ("b", "end")] {
+            workflow.connect_agents(from, to, Flow::default()).unwrap();
+        }
+
+        let paths = workflow
+            .find_execution_paths_with_limits("start", 2, 3)
+            .unwrap();
+        assert_eq!(paths.len(), 2);
+
+        let result = workflow.find_execution_paths_with_limits("start", 1, 3);
+        assert!(matches!(
+            result,
+            Err(GraphWorkflowError::PathLimitExceeded(_))
+        ));
+        let result = workflow.find_execution_paths_with_limits("start", 2, 2);
+        assert!(matches!(
+            result,
+            Err(GraphWorkflowError::PathLimitExceeded(_))
+        ));
+    }
+
     #[test]
     fn test_detect_potential_deadlocks() {
         let mut workflow = DAGWorkflow::new("test", "Test workflow");
@@ -512,6 +540,31 @@ mod tests {
         assert!(dot.contains("}"));
     }
 
+    #[test]
+    fn test_visualise_ascii() {
+        let mut workflow = DAGWorkflow::new("test", "Test workflow");
+        workflow.register_agent(create_mock_agent("1", "a", "Agent A", "a"));
+        workflow.register_agent(create_mock_agent("2", "b", "Agent B", "b"));
+        workflow.register_agent(create_mock_agent("3", "c", "Agent C", "c"));
+
+        workflow.connect_agents("a", "c", Flow::default()).unwrap();
+        workflow.connect_agents("b", "c", Flow::default()).unwrap();
+
+        let ascii = workflow.visualise_ascii();
+        let expected = "\
++---+   +---+
+| a |   | b |
++---+   +---+
+  a -> c
+  b -> c
++---+
+| c |
++---+
+";
+        assert_eq!(ascii, expected);
+        assert!(DAGWorkflow::new("empty", "").visualise_ascii().is_empty());
+    }
+
     #[tokio::test]
     async fn test_caching_execution_results() {
         let mut workflow = DAGWorkflow::new("test", "Test workflow");
@@ -683,4 +736,68 @@ mod tests {
         // the results should contain the new call count, indicating that the agent was re-executed
         assert_eq!(result3, "response for 'input3' (call #2)");
     }
+
+    #[tokio::test]
+    async fn test_export_execution_dot() {
+        let mut workflow = DAGWorkflow::new("test", "Test workflow");
+        for (id, name) in [("1", "agent1"), ("2", "agent2"), ("3", "agent3")] {
+            workflow.register_agent(create_mock_agent(id, name, "Agent", "response"));
+        }
+        workflow
+            .connect_agents("agent1", "agent2", Flow::default())
+            .unwrap();
+        workflow
+            .connect_agents("agent1", "agent3", Flow::default())
+            .unwrap();
+
+        let results: std::collections::HashMap<_, _> = [
+            ("agent1".to_string(), Ok("a \"quoted\" answer ".repeat(5))),
+            (
+                "agent2".to_string(),
+                Err(GraphWorkflowError::AgentError("boom".to_string())),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let dot = workflow.export_execution_dot(&results);
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.contains(r#""agent1" [label="agent1\na \"quoted\" answer a \"quoted\" answer a \"q…", fillcolor=palegreen];"#));
+        assert!(dot.contains(
+            r#""agent2" [label="agent2\nerror: Agent Error: boom", fillcolor=lightcoral];"#
+        ));
+        assert!(dot.contains(r#""agent3" [label="agent3\nnot run", fillcolor=lightgrey];"#));
+        assert!(dot.contains(r#""agent1" -> "agent2";"#));
+    }
+
+    #[test]
+    fn test_export_mermaid() {
+        let mut workflow = DAGWorkflow::new("test", "Test workflow");
+        workflow.register_agent(create_mock_agent("1", "agent1", "Agent", "a"));
+        workflow.register_agent(create_mock_agent("2", "agent 2", "Agent with tools", "b"));
+        workflow.register_agent(create_mock_agent("3", "agent3", "Agent", "c"));
+        workflow
+            .connect_agents("agent1", "agent 2", Flow::default())
+            .unwrap();
+        workflow
+            .connect_agents("agent1", "agent3", Flow::default())
+            .unwrap();
+
+        let mermaid = workflow.export_mermaid();
+        assert!(mermaid.starts_with("graph TD\n"));
+        assert!(mermaid.contains("  agent1[\"agent1\"]\n"));
+        assert!(mermaid.contains("  agent_2[\"agent 2\"]\n"));
+        assert!(mermaid.contains("  agent1 --> agent_2\n"));
+        assert!(mermaid.contains("  agent1 --> agent3\n"));
+
+        let mermaid = workflow.export_mermaid_with_shapes(|agent| {
+            if agent.description().contains("tools") {
+                MermaidShape::Cylinder
+            } else {
+                MermaidShape::Rounded
+            }
+        });
+        assert!(mermaid.contains("  agent_2[(\"agent 2\")]\n"));
+        assert!(mermaid.contains("  agent3(\"agent3\")\n"));
+    }
 }
@@ -0,0 +1,44 @@
+//! Tests for JSON logging initialization
+//! Runs in its own test binary because it installs a global tracing subscriber
+
+use swarms_rs::logging::{LogRecord, init_json_logging};
+use swarms_rs::{log_agent, log_error_ctx, log_perf, log_task};
+
+#[test]
+fn test_json_logging_with_structured_macros() {
+    init_json_logging();
+
+    log_agent!(info, "TestAgent", "agent-123", "Agent started successfully");
+    log_task!(
+        info,
+        "TestAgent",
+        "agent-123",
+        "Process data",
+        "Task finished in {}ms",
+        42
+    );
+    log_perf!(info, "Agent", "total_execution_time", 42, "ms");
+    log_error_ctx!("TestAgent", "agent-123", "timeout", "LLM completion");
+
+    // Records from the `log` crate are captured by the JSON subscriber as well
+    log::info!("Plain log record");
+}
+
+#[test]
+fn test_log_record_json_shape() {
+    let record = LogRecord::new(
+        "info",
+        "task",
+        Some("TestAgent".to_string()),
+        Some("agent-123".to_string()),
+        "Task finished",
+    );
+
+    let value: serde_json::Value = serde_json::from_str(&record.to_json()).unwrap();
+    assert_eq!(value["level"], "INFO");
+    assert_eq!(value["agent_name"], "TestAgent");
+    assert_eq!(value["agent_id"], "agent-123");
+    assert_eq!(value["event_type"], "task");
+    assert_eq!(value["message"], "Task finished");
+    assert!(value["timestamp_ms"].as_i64().unwrap() > 0);
+}
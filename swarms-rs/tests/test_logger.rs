@@ -1,7 +1,7 @@
 use std::env;
 use std::sync::Once;
-use swarms_rs::logging::init_logger;
-use swarms_rs::{log_agent, log_llm, log_memory, log_task, log_tool};
+use swarms_rs::logging::{MetricsCollector, init_logger};
+use swarms_rs::{log_agent, log_llm, log_memory, log_perf, log_task, log_tool};
 
 static LOGGER_INIT: Once = Once::new();
 
@@ -88,4 +88,51 @@ mod tests {
 
         assert!(true);
     }
+
+    #[test]
+    pub fn test_metrics_collector_snapshot() {
+        let metrics = MetricsCollector::new();
+        metrics.increment_counter("requests");
+        metrics.add_counter("requests", 2);
+        metrics.set_gauge("running_agents", 3);
+        metrics.decrement_gauge("running_agents");
+        metrics.record_histogram("latency_ms", 10);
+        metrics.record_histogram("latency_ms", 30);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.counters["requests"], 3);
+        assert_eq!(snapshot.gauges["running_agents"], 2);
+        let latency = &snapshot.histograms["latency_ms"];
+        assert_eq!((latency.count, latency.sum), (2, 40));
+        assert_eq!((latency.min, latency.max), (10, 30));
+        assert_eq!(latency.mean, 20.0);
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["counters"]["requests"], 3);
+
+        metrics.reset();
+        assert!(metrics.snapshot().counters.is_empty());
+    }
+
+    #[test]
+    pub fn test_log_macros_do_not_record_metrics() {
+        ensure_logger_initialized();
+
+        log_llm!(
+            info,
+            "TestAgent",
+            "agent-123",
+            "GPT-4",
+            "Completion received"
+        );
+        log_perf!(info, "MetricsTest", "completion_time", 25, "ms");
+
+        let snapshot = MetricsCollector::global().snapshot();
+        assert!(!snapshot.counters.contains_key("llm_events"));
+        assert!(
+            !snapshot
+                .histograms
+                .contains_key("MetricsTest.completion_time")
+        );
+    }
 }
@@ -0,0 +1,193 @@
+use futures::future::{BoxFuture, ready};
+use swarms_rs::structs::agent::{Agent, AgentError};
+use swarms_rs::structs::malt::{MaltSwarm, MaltSwarmError};
+
+// Mock Agent whose response depends on the role it plays in the swarm
+#[derive(Clone)]
+struct MockAgent {
+    name: String,
+    kind: MockKind,
+}
+
+#[derive(Clone)]
+enum MockKind {
+    Planner(String),
+    Writer,
+    Polisher,
+    Failing,
+}
+
+impl MockAgent {
+    fn new(name: &str, kind: MockKind) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+        }
+    }
+}
+
+impl Agent for MockAgent {
+    fn run(&self, task: String) -> BoxFuture<Result<String, AgentError>> {
+        let response = match &self.kind {
+            MockKind::Planner(plan) => plan.clone(),
+            MockKind::Writer => {
+                let section = task.rsplit("Write section ").next().unwrap_or_default();
+                format!("{} wrote {section}", self.name)
+            },
+            MockKind::Polisher => {
+                let draft = task.split("Draft:\n").nth(1).unwrap_or_default();
+                format!("Polished.\n\n{draft}")
+            },
+            MockKind::Failing => return Box::pin(ready(Err(AgentError::NoChoiceFound))),
+        };
+        Box::pin(ready(Ok(response)))
+    }
+
+    fn run_multiple_tasks(
+        &mut self,
+        _tasks: Vec<String>,
+    ) -> BoxFuture<Result<Vec<String>, AgentError>> {
+        Box::pin(ready(Ok(Vec::new())))
+    }
+
+    fn plan(&self, _task: String) -> BoxFuture<Result<(), AgentError>> {
+        Box::pin(ready(Ok(())))
+    }
+
+    fn query_long_term_memory(&self, _task: String) -> BoxFuture<Result<(), AgentError>> {
+        Box::pin(ready(Ok(())))
+    }
+
+    fn save_task_state(&self, _task: String) -> BoxFuture<Result<(), AgentError>> {
+        Box::pin(ready(Ok(())))
+    }
+
+    fn is_response_complete(&self, _response: String) -> bool {
+        true
+    }
+
+    fn id(&self) -> String {
+        format!("mock-{}", self.name)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn description(&self) -> String {
+        format!("Mock agent: {}", self.name)
+    }
+
+    fn clone_box(&self) -> Box<dyn Agent> {
+        Box::new(self.clone())
+    }
+}
+
+fn malt(plan: &str) -> MaltSwarm {
+    MaltSwarm::builder()
+        .planner(Box::new(MockAgent::new(
+            "planner",
+            MockKind::Planner(plan.to_string()),
+        )))
+        .add_writer(Box::new(MockAgent::new("writer-a", MockKind::Writer)))
+        .add_writer(Box::new(MockAgent::new("writer-b", MockKind::Writer)))
+        .polisher(Box::new(MockAgent::new("polisher", MockKind::Polisher)))
+        .build()
+}
+
+#[tokio::test]
+async fn test_malt_writes_every_planned_section() {
+    let swarm = malt(r#"Here is the plan: ["Introduction", "Method", "Conclusion"]"#);
+
+    let output = swarm.run("Write a report").await.unwrap();
+
+    let titles = output
+        .sections
+        .iter()
+        .map(|s| s.title.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(titles, ["Introduction", "Method", "Conclusion"]);
+
+    let writers = output
+        .sections
+        .iter()
+        .map(|s| s.writer.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(writers, ["writer-a", "writer-b", "writer-a"]);
+    assert!(output.sections[1].content.contains("2: Method"));
+
+    assert!(output.document.starts_with("Polished."));
+    // task, plan, 3 sections, final document
+    assert_eq!(output.conversation.history.len(), 6);
+}
+
+#[tokio::test]
+async fn test_malt_tracks_section_boundaries() {
+    let swarm = malt("1. Introduction\n2. Conclusion\n");
+
+    let output = swarm.run("Write a report").await.unwrap();
+
+    assert_eq!(output.sections.len(), 2);
+    for section in &output.sections {
+        let in_draft = &output.draft[section.draft_range.clone()];
+        assert!(in_draft.starts_with(&format!("## {}", section.title)));
+        assert!(in_draft.ends_with(section.content.trim()));
+
+        let offset = section.document_offset.expect("polisher kept the heading");
+        assert!(output.document[offset..].starts_with(&format!("## {}", section.title)));
+    }
+    assert!(output.sections[0].draft_range.end <= output.sections[1].draft_range.start);
+}
+
+#[tokio::test]
+async fn test_malt_max_sections() {
+    let swarm = MaltSwarm::builder()
+        .planner(Box::new(MockAgent::new(
+            "planner",
+            MockKind::Planner(r#"["A", "B", "C", "D"]"#.to_string()),
+        )))
+        .add_writer(Box::new(MockAgent::new("writer", MockKind::Writer)))
+        .polisher(Box::new(MockAgent::new("polisher", MockKind::Polisher)))
+        .max_sections(2)
+        .build();
+
+    let output = swarm.run("Write a report").await.unwrap();
+    assert_eq!(output.sections.len(), 2);
+}
+
+#[tokio::test]
+async fn test_malt_errors() {
+    let no_writers = MaltSwarm::builder()
+        .planner(Box::new(MockAgent::new(
+            "planner",
+            MockKind::Planner("[]".to_string()),
+        )))
+        .polisher(Box::new(MockAgent::new("polisher", MockKind::Polisher)))
+        .build();
+    assert!(matches!(
+        no_writers.run("task").await,
+        Err(MaltSwarmError::NoWriters)
+    ));
+
+    assert!(matches!(
+        malt("[]").run("task").await,
+        Err(MaltSwarmError::NoSections)
+    ));
+    assert!(matches!(
+        malt(r#"["A"]"#).run("  ").await,
+        Err(MaltSwarmError::EmptyTask)
+    ));
+
+    let failing_writer = MaltSwarm::builder()
+        .planner(Box::new(MockAgent::new(
+            "planner",
+            MockKind::Planner(r#"["A"]"#.to_string()),
+        )))
+        .add_writer(Box::new(MockAgent::new("writer", MockKind::Failing)))
+        .polisher(Box::new(MockAgent::new("polisher", MockKind::Polisher)))
+        .build();
+    assert!(matches!(
+        failing_writer.run("task").await,
+        Err(MaltSwarmError::AgentError(_))
+    ));
+}
@@ -0,0 +1,42 @@
+//! Tests for the built-in agent middlewares
+
+use swarms_rs::agent::{Middleware, OutputTrimmer, ProfanityFilter, TaskLogger};
+
+#[tokio::test]
+async fn test_profanity_filter_masks_words_case_insensitively() {
+    let filter = ProfanityFilter::new(["darn", "heck"]);
+
+    let task = filter
+        .before("Darn it, what the HECK happened?")
+        .await
+        .unwrap();
+    assert_eq!(task, "**** it, what the **** happened?");
+
+    let output = filter.after("nothing to filter").await.unwrap();
+    assert_eq!(output, "nothing to filter");
+}
+
+#[tokio::test]
+async fn test_profanity_filter_custom_mask() {
+    let filter = ProfanityFilter::new(["darn"]).mask('#');
+    let output = filter.after("darn darn").await.unwrap();
+    assert_eq!(output, "#### ####");
+}
+
+#[tokio::test]
+async fn test_output_trimmer() {
+    let trimmer = OutputTrimmer::new();
+    assert_eq!(trimmer.after("  result \n").await.unwrap(), "result");
+    // `before` leaves the task untouched
+    assert_eq!(trimmer.before("  task ").await.unwrap(), "  task ");
+
+    let capped = OutputTrimmer::new().max_chars(3);
+    assert_eq!(capped.after("  abcdef  ").await.unwrap(), "abc");
+}
+
+#[tokio::test]
+async fn test_task_logger_is_passthrough() {
+    let logger = TaskLogger;
+    assert_eq!(logger.before("task").await.unwrap(), "task");
+    assert_eq!(logger.after("output").await.unwrap(), "output");
+}
@@ -0,0 +1,68 @@
+use swarms_rs::agent::SwarmsAgentBuilder;
+use swarms_rs::llm::completion::AssistantContent;
+use swarms_rs::llm::request::{CompletionRequest, CompletionResponse};
+use swarms_rs::llm::testing::MockModel;
+use swarms_rs::llm::{CompletionError, Model};
+use swarms_rs::structs::agent::Agent;
+
+#[tokio::test]
+async fn test_mock_model_returns_responses_in_order() {
+    let model = MockModel::from_texts(["first", "second"]);
+
+    for expected in ["first", "second"] {
+        let response = model
+            .completion(CompletionRequest::builder().prompt("hi").build())
+            .await
+            .unwrap();
+        assert!(
+            matches!(&response.choice[0], AssistantContent::Text(text) if text.text == expected)
+        );
+    }
+    assert_eq!(model.remaining(), Some(0));
+
+    let exhausted = model.completion(CompletionRequest::builder().build()).await;
+    assert!(matches!(exhausted, Err(CompletionError::Other(_))));
+    assert_eq!(model.call_count(), 3);
+}
+
+#[tokio::test]
+async fn test_mock_model_from_fn_sees_the_request() {
+    let model = MockModel::from_fn(|request| CompletionResponse {
+        choice: vec![AssistantContent::text(format!(
+            "{} tools",
+            request.tools.len()
+        ))],
+        raw_response: (),
+    });
+
+    let response = model
+        .completion(CompletionRequest::builder().build())
+        .await
+        .unwrap();
+    assert!(matches!(&response.choice[0], AssistantContent::Text(text) if text.text == "0 tools"));
+    assert_eq!(model.remaining(), None);
+}
+
+#[tokio::test]
+async fn test_mock_model_from_fallible_fn_returns_errors() {
+    let model = MockModel::from_fallible_fn(|_| Err(CompletionError::Provider("down".into())));
+
+    let result = model.completion(CompletionRequest::builder().build()).await;
+    assert!(matches!(result, Err(CompletionError::Provider(message)) if message == "down"));
+    assert_eq!(model.call_count(), 1);
+}
+
+#[tokio::test]
+async fn test_mock_model_drives_an_agent() {
+    let model = MockModel::from_texts(["The answer is 42"]).set_supports_tools(false);
+    let agent = SwarmsAgentBuilder::new_with_model(model.clone())
+        .max_loops(1)
+        .disable_task_complete_tool()
+        .build();
+
+    let result = agent.run("What is the answer?".to_owned()).await.unwrap();
+
+    assert!(result.contains("The answer is 42"));
+    assert_eq!(model.call_count(), 1);
+    assert!(!model.supports_tools());
+}
@@ -0,0 +1,113 @@
+//! Tests for the prebuilt tool presets
+
+use swarms_rs::structs::prebuilt_tools::ToolPreset;
+use swarms_rs::structs::tool::{ToolDyn, ToolError};
+
+fn tool_names(preset: &ToolPreset) -> Vec<String> {
+    preset.toolkit().tools().iter().map(|t| t.name()).collect()
+}
+
+fn find_tool(preset: &ToolPreset, name: &str) -> std::sync::Arc<dyn ToolDyn> {
+    preset
+        .toolkit()
+        .tools()
+        .iter()
+        .find(|t| t.name() == name)
+        .cloned()
+        .unwrap()
+}
+
+#[test]
+fn test_presets_register_curated_tools() {
+    assert_eq!(
+        tool_names(&ToolPreset::WebSearch),
+        ["web_search", "fetch_url"]
+    );
+    assert_eq!(
+        tool_names(&ToolPreset::FileSystem("/tmp".into())),
+        ["read_file", "write_file", "list_directory"]
+    );
+    assert_eq!(tool_names(&ToolPreset::CodeInterpreter), ["run_python"]);
+    assert_eq!(
+        tool_names(&ToolPreset::FullStack),
+        [
+            "web_search",
+            "fetch_url",
+            "read_file",
+            "write_file",
+            "list_directory"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_file_system_preset_is_confined_to_root() {
+    let dir = tempfile::tempdir().unwrap();
+    let preset = ToolPreset::FileSystem(dir.path().to_path_buf());
+
+    let write = find_tool(&preset, "write_file");
+    write
+        .call(r#"{"path": "notes/todo.txt", "content": "ship it"}"#.to_string())
+        .await
+        .unwrap();
+
+    let read = find_tool(&preset, "read_file");
+    let content = read
+        .call(r#"{"path": "notes/todo.txt"}"#.to_string())
+        .await
+        .unwrap();
+    assert_eq!(content, "\"ship it\"");
+
+    let list = find_tool(&preset, "list_directory");
+    let entries = list.call(r#"{"path": "."}"#.to_string()).await.unwrap();
+    assert_eq!(entries, r#"["notes/"]"#);
+
+    for path in ["../secret.txt", "/etc/passwd"] {
+        let result = read.call(format!(r#"{{"path": "{path}"}}"#)).await;
+        assert!(matches!(result, Err(ToolError::ExecutionFailed(_))));
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_file_system_preset_rejects_symlinks_out_of_root() {
+    let outside = tempfile::tempdir().unwrap();
+    std::fs::write(outside.path().join("secret.txt"), "hunter2").unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    std::os::unix::fs::symlink(outside.path(), dir.path().join("escape")).unwrap();
+    std::os::unix::fs::symlink(
+        outside.path().join("secret.txt"),
+        dir.path().join("secret.txt"),
+    )
+    .unwrap();
+    let preset = ToolPreset::FileSystem(dir.path().to_path_buf());
+
+    let read = find_tool(&preset, "read_file");
+    for path in ["escape/secret.txt", "secret.txt"] {
+        let result = read.call(format!(r#"{{"path": "{path}"}}"#)).await;
+        assert!(matches!(result, Err(ToolError::ExecutionFailed(_))));
+    }
+
+    let write = find_tool(&preset, "write_file");
+    for path in [
+        "escape/planted.txt",
+        "escape/nested/planted.txt",
+        "secret.txt",
+    ] {
+        let result = write
+            .call(format!(r#"{{"path": "{path}", "content": "pwned"}}"#))
+            .await;
+        assert!(matches!(result, Err(ToolError::ExecutionFailed(_))));
+    }
+    assert!(!outside.path().join("planted.txt").exists());
+    assert!(!outside.path().join("nested").exists());
+    assert_eq!(
+        std::fs::read_to_string(outside.path().join("secret.txt")).unwrap(),
+        "hunter2"
+    );
+
+    let list = find_tool(&preset, "list_directory");
+    let result = list.call(r#"{"path": "escape"}"#.to_string()).await;
+    assert!(matches!(result, Err(ToolError::ExecutionFailed(_))));
+}
@@ -0,0 +1,47 @@
+//! Tests for the prebuilt system prompts
+
+use swarms_rs::agent::SwarmsAgentBuilder;
+use swarms_rs::llm::provider::openai::OpenAI;
+use swarms_rs::prompts;
+
+#[test]
+fn test_prebuilt_prompts_are_trimmed_and_non_empty() {
+    for prompt in [prompts::react_agent(), prompts::summarizer()] {
+        assert!(!prompt.is_empty());
+        assert_eq!(prompt, prompt.trim());
+    }
+    assert!(prompts::TASK_EVALUATOR_PROMPT.contains("task_evaluator"));
+}
+
+#[test]
+fn test_json_output_prompt_embeds_schema() {
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": {"answer": {"type": "string"}},
+        "required": ["answer"]
+    });
+
+    let prompt = prompts::json_output(&schema);
+    let embedded = &prompt[prompt.find('{').unwrap()..];
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(embedded).unwrap(),
+        schema
+    );
+}
+
+#[test]
+fn test_prompts_can_be_used_as_system_prompts() {
+    let agent = SwarmsAgentBuilder::new_with_model(OpenAI::new("test-key"))
+        .system_prompt(format!(
+            "{}\n{}",
+            prompts::summarizer(),
+            prompts::TASK_EVALUATOR_PROMPT
+        ))
+        .build();
+    assert!(
+        agent
+            .get_system_prompt()
+            .unwrap()
+            .starts_with("You are an expert summarizer.")
+    );
+}
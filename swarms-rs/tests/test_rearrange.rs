@@ -1,6 +1,15 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 use futures::future::BoxFuture;
 use swarms_rs::structs::agent::{Agent, AgentError};
-use swarms_rs::structs::rearrange::{AgentRearrange, OutputType, rearrange};
+use swarms_rs::structs::rearrange::{
+    AgentCallback, AgentJudge, AgentRearrange, AgentRearrangeError, InputMode, OutputType,
+    rearrange,
+};
+use swarms_rs::structs::swarms_router::SwarmType;
 use uuid::Uuid;
 
 // Mock agent for testing
@@ -8,6 +17,8 @@ use uuid::Uuid;
 struct MockAgent {
     name: String,
     response: String,
+    /// Every task the agent was run with
+    inputs: Arc<Mutex<Vec<String>>>,
 }
 
 impl MockAgent {
@@ -15,12 +26,14 @@ impl MockAgent {
         Self {
             name: name.into(),
             response: response.into(),
+            inputs: Arc::default(),
         }
     }
 }
 
 impl Agent for MockAgent {
-    fn run(&self, _task: String) -> BoxFuture<Result<String, AgentError>> {
+    fn run(&self, task: String) -> BoxFuture<Result<String, AgentError>> {
+        self.inputs.lock().unwrap().push(task);
         let response = self.response.clone();
         Box::pin(async move { Ok(response) })
     }
@@ -147,6 +160,96 @@ async fn test_parallel_execution() {
     assert!(output.contains("agent2"));
 }
 
+// Agent that records how many agents run at the same time
+#[derive(Clone)]
+struct ConcurrencyProbe {
+    name: String,
+    running: Arc<AtomicUsize>,
+    max_running: Arc<AtomicUsize>,
+}
+
+impl Agent for ConcurrencyProbe {
+    fn run(&self, _task: String) -> BoxFuture<Result<String, AgentError>> {
+        Box::pin(async move {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(self.name.clone())
+        })
+    }
+
+    fn run_multiple_tasks(
+        &mut self,
+        _tasks: Vec<String>,
+    ) -> BoxFuture<Result<Vec<String>, AgentError>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn plan(&self, _task: String) -> BoxFuture<Result<(), AgentError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn query_long_term_memory(&self, _task: String) -> BoxFuture<Result<(), AgentError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn save_task_state(&self, _task: String) -> BoxFuture<Result<(), AgentError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn is_response_complete(&self, _response: String) -> bool {
+        true
+    }
+
+    fn id(&self) -> String {
+        self.name.clone()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn description(&self) -> String {
+        format!("Concurrency probe: {}", self.name)
+    }
+
+    fn clone_box(&self) -> Box<dyn Agent> {
+        Box::new(self.clone())
+    }
+}
+
+async fn max_concurrent_agents(max_parallel_agents: Option<usize>) -> usize {
+    let running = Arc::new(AtomicUsize::new(0));
+    let max_running = Arc::new(AtomicUsize::new(0));
+    let names = ["a", "b", "c", "d", "e", "f"];
+
+    let mut builder = AgentRearrange::builder().flow(names.join(", "));
+    for name in names {
+        builder = builder.add_agent(Box::new(ConcurrencyProbe {
+            name: name.to_string(),
+            running: Arc::clone(&running),
+            max_running: Arc::clone(&max_running),
+        }));
+    }
+    if let Some(limit) = max_parallel_agents {
+        builder = builder.max_parallel_agents(limit);
+    }
+
+    let output = builder.build().run("test task").await.unwrap();
+    for name in names {
+        assert!(output.contains(&format!("{name}: {name}")));
+    }
+    max_running.load(Ordering::SeqCst)
+}
+
+#[tokio::test]
+async fn test_max_parallel_agents_bounds_parallel_steps() {
+    assert_eq!(max_concurrent_agents(None).await, 6);
+    assert_eq!(max_concurrent_agents(Some(2)).await, 2);
+    assert_eq!(max_concurrent_agents(Some(0)).await, 1);
+}
+
 #[tokio::test]
 async fn test_batch_run() {
     let agent1 = Box::new(MockAgent::new("agent1", "response1")) as Box<dyn Agent>;
@@ -206,6 +309,76 @@ async fn test_output_types() {
     assert_eq!(result, "response1");
 }
 
+#[tokio::test]
+async fn test_run_with_metadata_saves_report() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut rearrange = AgentRearrange::builder()
+        .add_agent(Box::new(MockAgent::new("agent1", "response1")))
+        .add_agent(Box::new(MockAgent::new("agent2", "response2")))
+        .flow("agent1 -> agent2")
+        .output_type(OutputType::Final)
+        .autosave(true)
+        .metadata_output_dir(temp_dir.path().to_str().unwrap())
+        .build();
+
+    let (output, metadata) = rearrange.run_with_metadata("test").await.unwrap();
+    assert_eq!(output, "response2");
+    assert_eq!(metadata.agent_names, ["agent1", "agent2"]);
+    assert_eq!(metadata.task_summary, "test");
+    assert!(
+        temp_dir
+            .path()
+            .join(&metadata.run_id)
+            .with_extension("json")
+            .exists()
+    );
+}
+
+#[tokio::test]
+async fn test_merge_chains_two_instances() {
+    let first = AgentRearrange::builder()
+        .add_agent(Box::new(MockAgent::new("a1", "r1")))
+        .add_agent(Box::new(MockAgent::new("a2", "r2")))
+        .flow("a1 -> a2")
+        .output_type(OutputType::Final)
+        .build();
+    let second = AgentRearrange::builder()
+        .add_agent(Box::new(MockAgent::new("b1", "r3")))
+        .add_agent(Box::new(MockAgent::new("b2", "r4")))
+        .flow("b1, b2")
+        .build();
+
+    let mut merged = first.merge(second, "a2 -> b2, b1").unwrap();
+    assert!(merged.validate_flow().is_ok());
+    let (_, metadata) = merged.run_with_metadata("test").await.unwrap();
+    assert_eq!(metadata.agent_names, ["a1", "a2", "b1", "b2"]);
+}
+
+#[test]
+fn test_merge_rejects_conflicts_and_bad_connections() {
+    let instance = |names: &[&str], flow: &str| {
+        names
+            .iter()
+            .fold(AgentRearrange::builder(), |builder, name| {
+                builder.add_agent(Box::new(MockAgent::new(*name, "response")))
+            })
+            .flow(flow)
+            .build()
+    };
+
+    let conflict = instance(&["a", "b"], "a -> b").merge(instance(&["b", "c"], "b -> c"), "b -> b");
+    assert!(matches!(
+        conflict,
+        Err(AgentRearrangeError::AgentNameConflict(name)) if name == "b"
+    ));
+
+    let disconnected = instance(&["a", "b"], "a -> b").merge(instance(&["c"], "c"), "a -> c");
+    assert!(matches!(
+        disconnected,
+        Err(AgentRearrangeError::InvalidFlowFormat(_))
+    ));
+}
+
 #[tokio::test]
 async fn test_convenience_function() {
     let agent1 = Box::new(MockAgent::new("agent1", "response1")) as Box<dyn Agent>;
@@ -279,6 +452,53 @@ async fn test_flow_patterns() {
     assert!(output.contains("agent3"));
 }
 
+#[tokio::test]
+async fn test_parenthesized_parallel_group() {
+    let agent1 = Box::new(MockAgent::new("agent1", "response1")) as Box<dyn Agent>;
+    let agent2 = Box::new(MockAgent::new("agent2", "response2")) as Box<dyn Agent>;
+    let agent3 = Box::new(MockAgent::new("agent3", "response3")) as Box<dyn Agent>;
+    let agent4 = Box::new(MockAgent::new("agent4", "response4")) as Box<dyn Agent>;
+
+    let mut rearrange = AgentRearrange::builder()
+        .add_agent(agent1)
+        .add_agent(agent2)
+        .add_agent(agent3)
+        .add_agent(agent4)
+        .flow("agent1 -> (agent2, agent3) -> agent4")
+        .output_type(OutputType::Dict)
+        .build();
+
+    assert!(rearrange.validate_flow().is_ok());
+
+    let output = rearrange.run("test task").await.unwrap();
+    for name in ["agent1", "agent2", "agent3", "agent4"] {
+        assert!(output.contains(name));
+    }
+}
+
+#[tokio::test]
+async fn test_malformed_parentheses_rejected() {
+    let agent1 = Box::new(MockAgent::new("agent1", "response1")) as Box<dyn Agent>;
+    let agent2 = Box::new(MockAgent::new("agent2", "response2")) as Box<dyn Agent>;
+
+    let mut rearrange = AgentRearrange::builder()
+        .add_agent(agent1)
+        .add_agent(agent2)
+        .flow("agent1 -> (agent2")
+        .build();
+
+    assert!(matches!(
+        rearrange.validate_flow(),
+        Err(AgentRearrangeError::InvalidFlowFormat(_))
+    ));
+
+    rearrange.set_custom_flow("(agent1, (agent2))");
+    assert!(matches!(
+        rearrange.validate_flow(),
+        Err(AgentRearrangeError::InvalidFlowFormat(_))
+    ));
+}
+
 #[tokio::test]
 async fn test_human_in_the_loop_placeholder() {
     let agent1 = Box::new(MockAgent::new("agent1", "response1")) as Box<dyn Agent>;
@@ -347,19 +567,220 @@ async fn test_empty_flow_validation() {
 
 #[tokio::test]
 async fn test_rules_injection() {
-    let agent1 = Box::new(MockAgent::new("agent1", "response1")) as Box<dyn Agent>;
+    let agents = ["agent1", "agent2", "agent3"].map(|name| MockAgent::new(name, "response"));
 
-    let mut rearrange = AgentRearrange::builder()
-        .add_agent(agent1)
-        .flow("agent1")
+    let mut rearrange = agents
+        .iter()
+        .fold(AgentRearrange::builder(), |builder, agent| {
+            builder.add_agent(Box::new(agent.clone()))
+        })
+        .flow("agent1 -> agent2, agent3")
         .rules("Always be helpful and accurate")
         .build();
 
     let result = rearrange.run("test task").await;
     assert!(result.is_ok());
 
-    // Check that rules were added to conversation
-    let conversation = rearrange.conversation();
-    let conversation_str = conversation.to_string();
-    assert!(conversation_str.contains("Rules: Always be helpful and accurate"));
+    // Rules are prepended to the input of every sequential and parallel step rather than
+    // stored in the conversation
+    for agent in &agents {
+        let inputs = agent.inputs.lock().unwrap();
+        assert_eq!(inputs.len(), 1, "{} ran {} times", agent.name, inputs.len());
+        assert!(
+            inputs[0].starts_with("### RULES ###\nAlways be helpful and accurate"),
+            "{} got {:?}",
+            agent.name,
+            inputs[0]
+        );
+    }
+    assert!(agents[0].inputs.lock().unwrap()[0].contains("test task"));
+}
+
+#[tokio::test]
+async fn test_inject_global_rules_without_rules() {
+    let rearrange = AgentRearrange::builder().flow("agent1").build();
+    assert_eq!(rearrange.inject_global_rules("test task"), "test task");
+}
+
+#[tokio::test]
+async fn test_metadata_includes_swarm_type() {
+    let rearrange = AgentRearrange::builder()
+        .name("MetaSwarm")
+        .flow("agent1")
+        .build();
+
+    assert_eq!(rearrange.swarm_type(), SwarmType::AgentRearrange);
+    let metadata = rearrange.to_metadata();
+    assert_eq!(metadata["swarm_type"], "AgentRearrange");
+    assert_eq!(metadata["name"], "MetaSwarm");
+}
+
+#[tokio::test]
+async fn test_benchmark_reports_agent_and_total_latency() {
+    let rearrange = AgentRearrange::builder()
+        .add_agent(Box::new(MockAgent::new("agent1", "response1")))
+        .add_agent(Box::new(MockAgent::new("agent2", "response2")))
+        .add_agent(Box::new(MockAgent::new("agent3", "response3")))
+        .flow("agent1 -> agent2, agent3")
+        .build();
+
+    let report = rearrange.benchmark("Benchmark task", 5).await.unwrap();
+    assert_eq!(report.runs, 5);
+    assert_eq!(report.flow, "agent1 -> agent2, agent3");
+    assert_eq!(
+        report.agents.keys().collect::<Vec<_>>(),
+        ["agent1", "agent2", "agent3"]
+    );
+    for stats in report.agents.values().chain([&report.total]) {
+        assert_eq!(stats.samples, 5);
+        assert!(stats.min_ms <= stats.mean_ms && stats.mean_ms <= stats.max_ms);
+        assert!(stats.p95_ms <= stats.max_ms);
+    }
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert!(json["total"]["p95_ms"].is_number());
+
+    assert!(rearrange.benchmark("Benchmark task", 0).await.is_err());
+}
+
+#[derive(Clone, Default)]
+struct RecordingCallback {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl AgentCallback for RecordingCallback {
+    fn on_step_start(&self, agent_name: &str, _input: &str) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("start {agent_name}"));
+    }
+
+    fn on_step_complete(&self, agent_name: &str, output: &str, _duration_ms: u64) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("complete {agent_name}: {output}"));
+    }
+
+    fn on_error(&self, agent_name: &str, _error: &AgentRearrangeError) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("error {agent_name}"));
+    }
+}
+
+#[tokio::test]
+async fn test_callbacks_notified_for_each_step() {
+    let callback = RecordingCallback::default();
+    let mut rearrange = AgentRearrange::builder()
+        .add_agent(Box::new(MockAgent::new("agent1", "response1")))
+        .add_agent(Box::new(MockAgent::new("agent2", "response2")))
+        .add_agent(Box::new(MockAgent::new("agent3", "response3")))
+        .flow("agent1 -> agent2, agent3")
+        .add_callback(callback.clone())
+        .build();
+
+    rearrange.run("Test task").await.unwrap();
+
+    let events = callback.events.lock().unwrap().clone();
+    assert_eq!(events.len(), 6);
+    assert_eq!(events[..2], ["start agent1", "complete agent1: response1"]);
+    for event in [
+        "start agent2",
+        "complete agent2: response2",
+        "start agent3",
+        "complete agent3: response3",
+    ] {
+        assert!(events[2..].contains(&event.to_string()));
+    }
+    assert!(!events.iter().any(|event| event.starts_with("error")));
+}
+
+#[tokio::test]
+async fn test_parallel_voting_picks_weighted_consensus() {
+    let build = |weights: HashMap<String, f64>| {
+        AgentRearrange::builder()
+            .add_agent(Box::new(MockAgent::new("a", "the sky is blue")))
+            .add_agent(Box::new(MockAgent::new("b", "the sky is blue today")))
+            .add_agent(Box::new(MockAgent::new("c", "grass is green")))
+            .flow("a, b, c")
+            .with_parallel_voting(weights)
+            .build()
+    };
+
+    // a and b agree with each other, so one of them wins with equal weights
+    let output = build(HashMap::new()).run("What color?").await.unwrap();
+    assert!(output.starts_with("the sky is blue"));
+
+    // A heavy enough weight lets the outlier win
+    let weights = HashMap::from([("c".to_string(), 10.0)]);
+    let output = build(weights).run("What color?").await.unwrap();
+    assert_eq!(output, "grass is green");
+}
+
+#[tokio::test]
+async fn test_parallel_voting_with_agent_judge() {
+    let mut rearrange = AgentRearrange::builder()
+        .add_agent(Box::new(MockAgent::new("a", "first answer")))
+        .add_agent(Box::new(MockAgent::new("b", "second answer")))
+        .flow("a, b")
+        .with_parallel_voting(HashMap::new())
+        .voting_judge(AgentJudge::new(Box::new(MockAgent::new(
+            "judge",
+            "Answer 2 is best",
+        ))))
+        .build();
+
+    let output = rearrange.run("Answer").await.unwrap();
+    assert_eq!(output, "second answer");
+}
+
+#[tokio::test]
+async fn test_parallel_vote_winner_feeds_next_step() {
+    let next = MockAgent::new("c", "summary");
+    let inputs = Arc::clone(&next.inputs);
+    let mut rearrange = AgentRearrange::builder()
+        .add_agent(Box::new(MockAgent::new("a", "cats are best")))
+        .add_agent(Box::new(MockAgent::new("b", "dogs rule")))
+        .add_agent(Box::new(next))
+        .flow("a, b -> c")
+        .with_parallel_voting(HashMap::from([("a".to_string(), 3.0)]))
+        .build();
+
+    rearrange.run("Which pet?").await.unwrap();
+
+    let input = inputs.lock().unwrap().remove(0);
+    assert!(input.contains("Which pet?"));
+    assert!(input.contains("cats are best"));
+    assert!(!input.contains("dogs rule"));
+}
+
+#[tokio::test]
+async fn test_input_mode_controls_sequential_agent_input() {
+    let run = |input_mode: InputMode| async move {
+        let third = MockAgent::new("agent3", "response3");
+        let inputs = Arc::clone(&third.inputs);
+        let mut rearrange = AgentRearrange::builder()
+            .agents(vec![
+                Box::new(MockAgent::new("agent1", "response1")),
+                Box::new(MockAgent::new("agent2", "response2")),
+                Box::new(third),
+            ])
+            .flow("agent1 -> agent2 -> agent3")
+            .input_mode(input_mode)
+            .build();
+        rearrange.run("original task").await.unwrap();
+        inputs.lock().unwrap().remove(0)
+    };
+
+    let full = run(InputMode::FullConversation).await;
+    assert!(full.contains("original task") && full.contains("response1"));
+
+    let plus_task = run(InputMode::LastOutputPlusTask).await;
+    assert!(plus_task.contains("original task") && plus_task.contains("response2"));
+    assert!(!plus_task.contains("response1"));
+
+    assert_eq!(run(InputMode::LastOutputOnly).await, "response2");
 }
@@ -1,5 +1,6 @@
 use futures::future::{BoxFuture, ready};
 use swarms_rs::structs::agent::{Agent, AgentError};
+use swarms_rs::structs::conversation::Content;
 use swarms_rs::structs::sequential_workflow::{SequentialWorkflow, SequentialWorkflowError};
 
 // Mock Agent for testing
@@ -366,6 +367,39 @@ async fn test_sequential_workflow_with_metadata_dir() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_sequential_workflow_run_metadata() {
+    use tempfile::tempdir;
+
+    let temp_dir = tempdir().unwrap();
+    let workflow = SequentialWorkflow::builder()
+        .metadata_output_dir(temp_dir.path().to_str().unwrap())
+        .add_agent(Box::new(MockAgent::new("Agent1", "Response1")))
+        .add_agent(Box::new(MockAgent::new("Agent2", "Response2")))
+        .build();
+
+    let (_, metadata) = workflow.run_with_metadata("test task").await.unwrap();
+    assert_eq!(metadata.run_id.len(), 26);
+    assert_eq!(metadata.agent_names, ["Agent1", "Agent2"]);
+    assert_eq!(metadata.task_summary, "test task");
+    assert!(metadata.total_tokens > 0);
+    assert!(metadata.completed_at >= metadata.started_at);
+
+    let (_, rerun) = workflow.run_with_metadata("test task").await.unwrap();
+    assert_eq!(rerun.output_hash, metadata.output_hash);
+    assert_ne!(rerun.run_id, metadata.run_id);
+
+    let saved = std::fs::read_to_string(
+        temp_dir
+            .path()
+            .join(&metadata.run_id)
+            .with_extension("json"),
+    )
+    .unwrap();
+    let saved: swarms_rs::structs::swarm::SwarmMetadata = serde_json::from_str(&saved).unwrap();
+    assert_eq!(saved, metadata);
+}
+
 #[tokio::test]
 async fn test_sequential_workflow_error_propagation() {
     let workflow = SequentialWorkflow::builder()
@@ -379,6 +413,32 @@ async fn test_sequential_workflow_error_propagation() {
     assert!(result.is_err());
 }
 
+#[tokio::test]
+async fn test_sequential_workflow_skip_on_error() {
+    use tempfile::tempdir;
+
+    let temp_dir = tempdir().unwrap();
+    let workflow = SequentialWorkflow::builder()
+        .metadata_output_dir(temp_dir.path().to_str().unwrap())
+        .add_agent(Box::new(MockAgent::new("Agent1", "Success")))
+        .add_agent(Box::new(MockAgent::new_failing("Agent2", "Fail")))
+        .add_agent(Box::new(MockAgent::new("Agent3", "Reached")))
+        .skip_on_error(true)
+        .build();
+
+    let (conversation, metadata) = workflow.run_with_metadata("test task").await.unwrap();
+    assert_eq!(conversation.history.len(), 3);
+    let Content::Text(ref text) = conversation.history[2].content;
+    assert_eq!(text, "Reached");
+    assert_eq!(metadata.agent_names, ["Agent1", "Agent2", "Agent3"]);
+    assert_eq!(metadata.skipped_agents.len(), 1);
+    assert_eq!(metadata.skipped_agents[0].agent_name, "Agent2");
+    assert_eq!(
+        metadata.skipped_agents[0].error,
+        AgentError::NoChoiceFound.to_string()
+    );
+}
+
 #[tokio::test]
 async fn test_sequential_workflow_first_agent_fails() {
     let workflow = SequentialWorkflow::builder()
@@ -442,3 +502,43 @@ async fn test_sequential_workflow_empty_name() {
     let result = workflow.run("test task").await;
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_sequential_workflow_insert_remove_swap_agents() {
+    let mut workflow = SequentialWorkflow::builder()
+        .add_agent(Box::new(MockAgent::new("Agent1", "Response1")))
+        .add_agent(Box::new(MockAgent::new("Agent3", "Response3")))
+        .build();
+
+    workflow
+        .insert_agent_at(1, Box::new(MockAgent::new("Agent2", "Response2")))
+        .unwrap();
+    workflow
+        .insert_agent_at(3, Box::new(MockAgent::new("Agent4", "Response4")))
+        .unwrap();
+    assert_eq!(
+        workflow.agent_names(),
+        ["Agent1", "Agent2", "Agent3", "Agent4"]
+    );
+
+    workflow.swap_agents(0, 3).unwrap();
+    assert_eq!(
+        workflow.agent_names(),
+        ["Agent4", "Agent2", "Agent3", "Agent1"]
+    );
+
+    let removed = workflow.remove_agent_at(1).unwrap();
+    assert_eq!(removed.name(), "Agent2");
+    assert_eq!(workflow.agent_names(), ["Agent4", "Agent3", "Agent1"]);
+
+    assert!(matches!(
+        workflow.insert_agent_at(5, Box::new(MockAgent::new("Agent5", "Response5"))),
+        Err(SequentialWorkflowError::InvalidPosition {
+            position: 5,
+            len: 3
+        })
+    ));
+    assert!(workflow.remove_agent_at(3).is_err());
+    assert!(workflow.swap_agents(0, 3).is_err());
+    assert_eq!(workflow.agent_names(), ["Agent4", "Agent3", "Agent1"]);
+}
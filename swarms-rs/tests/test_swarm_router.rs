@@ -2,7 +2,12 @@
 //! Note: Full SwarmRouter integration tests require actual SwarmsAgent instances
 //! which are tested in other integration test files
 
-use swarms_rs::structs::swarms_router::{SwarmRouterConfig, SwarmRouterError, SwarmType};
+use futures::future::{BoxFuture, ready};
+use swarms_rs::structs::agent::{Agent, AgentError};
+use swarms_rs::structs::swarm::SwarmError;
+use swarms_rs::structs::swarms_router::{
+    SwarmOptions, SwarmRouterConfig, SwarmRouterError, SwarmType, build_swarm,
+};
 
 #[test]
 fn test_swarm_router_config_default() {
@@ -320,3 +325,109 @@ fn test_swarm_router_config_change_swarm_type() {
     config.swarm_type = serde_json::from_str(r#""AgentRearrange""#).unwrap();
     assert!(matches!(config.swarm_type, SwarmType::AgentRearrange));
 }
+
+#[derive(Clone)]
+struct MockAgent {
+    name: String,
+}
+
+impl Agent for MockAgent {
+    fn run(&self, task: String) -> BoxFuture<Result<String, AgentError>> {
+        Box::pin(ready(Ok(format!("{} saw: {}", self.name, task))))
+    }
+
+    fn run_multiple_tasks(
+        &mut self,
+        _tasks: Vec<String>,
+    ) -> BoxFuture<Result<Vec<String>, AgentError>> {
+        Box::pin(ready(Ok(Vec::new())))
+    }
+
+    fn plan(&self, _task: String) -> BoxFuture<Result<(), AgentError>> {
+        Box::pin(ready(Ok(())))
+    }
+
+    fn query_long_term_memory(&self, _task: String) -> BoxFuture<Result<(), AgentError>> {
+        Box::pin(ready(Ok(())))
+    }
+
+    fn save_task_state(&self, _task: String) -> BoxFuture<Result<(), AgentError>> {
+        Box::pin(ready(Ok(())))
+    }
+
+    fn is_response_complete(&self, _response: String) -> bool {
+        true
+    }
+
+    fn id(&self) -> String {
+        format!("mock-{}", self.name)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn description(&self) -> String {
+        format!("Mock agent: {}", self.name)
+    }
+
+    fn clone_box(&self) -> Box<dyn Agent> {
+        Box::new(self.clone())
+    }
+}
+
+fn mock_agents(names: &[&str]) -> Vec<Box<dyn Agent>> {
+    names
+        .iter()
+        .map(|name| {
+            Box::new(MockAgent {
+                name: name.to_string(),
+            }) as Box<dyn Agent>
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_build_swarm_runs_matching_implementation() {
+    let opts = SwarmOptions {
+        name: Some("built".to_string()),
+        ..Default::default()
+    };
+    let swarm = build_swarm(
+        SwarmType::SequentialWorkflow,
+        mock_agents(&["first", "second"]),
+        opts,
+    )
+    .unwrap();
+    assert_eq!(swarm.name(), "built");
+
+    let output = swarm.run("write a poem".to_string()).await.unwrap();
+    let output = serde_json::to_string(&output).unwrap();
+    assert!(output.contains("first saw: write a poem"));
+    assert!(output.contains("second saw"));
+
+    let rearrange = build_swarm(
+        SwarmType::AgentRearrange,
+        mock_agents(&["a", "b"]),
+        SwarmOptions::default(),
+    )
+    .unwrap();
+    assert!(rearrange.run("task".to_string()).await.is_ok());
+}
+
+#[test]
+fn test_build_swarm_rejects_invalid_config() {
+    let no_agents = build_swarm(
+        SwarmType::ConcurrentWorkflow,
+        Vec::new(),
+        SwarmOptions::default(),
+    );
+    assert!(matches!(no_agents, Err(SwarmError::InvalidConfig(_))));
+
+    let small_malt = build_swarm(
+        SwarmType::Malt,
+        mock_agents(&["planner", "polisher"]),
+        SwarmOptions::default(),
+    );
+    assert!(matches!(small_malt, Err(SwarmError::InvalidConfig(_))));
+}
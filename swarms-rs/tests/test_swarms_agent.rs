@@ -0,0 +1,1453 @@
+//! Tests for SwarmsAgent execution behaviour using mock models
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use swarms_rs::agent::{
+    CompletionStrategy, ContextDocument, Document, MetricsSnapshot, ProfanityFilter, SwarmsAgent,
+    SwarmsAgentBuilder,
+};
+use swarms_rs::llm::cache::CachingModel;
+use swarms_rs::llm::completion::{
+    AssistantContent, Message, ToolResult, ToolResultContent, UserContent,
+};
+use swarms_rs::llm::request::{CompletionRequest, CompletionResponse, ToolDefinition};
+use swarms_rs::llm::testing::MockModel;
+use swarms_rs::llm::{CompletionError, Model};
+use swarms_rs::structs::agent::{Agent, AgentBuildError, AgentError, StopWordMatching, pipe};
+use swarms_rs::structs::conversation::AgentConversation;
+use swarms_rs::structs::persistence::{MemoryBackend, PersistenceError};
+use swarms_rs::structs::prebuilt_tools::ToolPreset;
+use swarms_rs::structs::tool::Tool;
+
+fn text_response(text: impl Into<String>) -> CompletionResponse<()> {
+    CompletionResponse {
+        choice: vec![AssistantContent::text(text)],
+        raw_response: (),
+    }
+}
+
+// Mock model that always answers with the same text
+fn answering(text: impl Into<String>) -> MockModel {
+    let text = text.into();
+    MockModel::from_fn(move |_| text_response(text.clone()))
+}
+
+// Mock model that answers with `response` and records what `record` extracts from every
+// request
+fn recording<T: Send + 'static>(
+    response: &str,
+    record: impl Fn(&CompletionRequest) -> T + Send + Sync + 'static,
+) -> (MockModel, Arc<Mutex<Vec<T>>>) {
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let response = response.to_string();
+    let model = MockModel::from_fn({
+        let records = Arc::clone(&records);
+        move |request| {
+            records.lock().unwrap().push(record(request));
+            text_response(response.clone())
+        }
+    });
+    (model, records)
+}
+
+fn prompt_text(request: &CompletionRequest) -> String {
+    serde_json::to_value(&request.prompt).unwrap()["content"][0]["text"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_empty_task_is_rejected_by_default() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("done")).build();
+
+    let result = agent.run("   \n".to_string()).await;
+    assert!(matches!(result, Err(AgentError::EmptyTask)));
+}
+
+#[tokio::test]
+async fn test_empty_task_uses_default_task() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("done"))
+        .default_task("Summarize the latest report")
+        .build();
+
+    let result = agent.run("".to_string()).await.unwrap();
+    assert!(result.contains("Summarize the latest report"));
+    assert!(result.contains("done"));
+}
+
+#[tokio::test]
+async fn test_empty_task_allowed() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("done"))
+        .allow_empty_task(true)
+        .build();
+
+    let result = agent.run(" ".to_string()).await.unwrap();
+    assert!(result.contains("done"));
+}
+
+#[tokio::test]
+async fn test_run_until_approved_stops_on_approval() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("draft answer"))
+        .max_loops(5)
+        .build();
+
+    let reviews = AtomicUsize::new(0);
+    let result = agent
+        .run_until_approved("Write a slogan".to_string(), |output| {
+            assert!(output.contains("draft answer"));
+            reviews.fetch_add(1, Ordering::SeqCst) >= 1
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(reviews.load(Ordering::SeqCst), 2);
+    assert_eq!(result.matches("draft answer").count(), 2);
+}
+
+#[tokio::test]
+async fn test_run_until_approved_is_bounded_by_max_loops() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("draft answer"))
+        .max_loops(3)
+        .build();
+
+    let result = agent
+        .run_until_approved("Write a slogan".to_string(), |_| false)
+        .await
+        .unwrap();
+
+    assert_eq!(result.matches("draft answer").count(), 3);
+}
+
+#[tokio::test]
+async fn test_completion_strategy_controls_early_stop() {
+    let run = |strategy: CompletionStrategy| async move {
+        SwarmsAgentBuilder::new_with_model(answering("draft answer"))
+            .max_loops(3)
+            .add_stop_word("answer")
+            .completion_strategy(strategy)
+            .build()
+            .run("Write a slogan".to_string())
+            .await
+            .unwrap()
+            .matches("draft answer")
+            .count()
+    };
+
+    assert_eq!(run(CompletionStrategy::MaxLoopsOnly).await, 3);
+    assert_eq!(
+        run(CompletionStrategy::StopWords(vec!["draft".to_string()])).await,
+        1
+    );
+    assert_eq!(
+        run(CompletionStrategy::Custom(Arc::new(|response| {
+            response.contains("draft answer")
+        })))
+        .await,
+        1
+    );
+    assert_eq!(
+        run(CompletionStrategy::Custom(Arc::new(|_| false))).await,
+        3
+    );
+}
+
+#[tokio::test]
+async fn test_stop_words_match_whole_words_by_default() {
+    let run = |matching: Option<StopWordMatching>| async move {
+        let builder = SwarmsAgentBuilder::new_with_model(answering("The plan was abandoned"))
+            .max_loops(3)
+            .disable_task_complete_tool()
+            .add_stop_word("done");
+        match matching {
+            Some(matching) => builder.stop_word_matching(matching),
+            None => builder,
+        }
+        .build()
+        .run("Write a plan".to_string())
+        .await
+        .unwrap()
+        .matches("abandoned")
+        .count()
+    };
+
+    assert_eq!(run(None).await, 3);
+    assert_eq!(run(Some(StopWordMatching::Substring)).await, 1);
+    assert_eq!(run(Some(StopWordMatching::Regex)).await, 1);
+}
+
+#[tokio::test]
+async fn test_stagnation_window_stops_repeated_responses() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("Same  answer"))
+        .max_loops(10)
+        .stagnation_window(3)
+        .build();
+
+    let result = agent.run("Write a slogan".to_string()).await.unwrap();
+    assert_eq!(result.matches("Same  answer").count(), 3);
+}
+
+#[tokio::test]
+async fn test_stagnation_window_ignores_changing_responses() {
+    let model = counting();
+    let agent = SwarmsAgentBuilder::new_with_model(model.clone())
+        .max_loops(4)
+        .stagnation_window(2)
+        .build();
+
+    agent.run("Write a slogan".to_string()).await.unwrap();
+    assert_eq!(model.call_count(), 4);
+}
+
+// Mock model that numbers its answers, so no two responses are the same
+fn counting() -> MockModel {
+    let calls = AtomicUsize::new(0);
+    MockModel::from_fn(move |_| {
+        text_response(format!("answer {}", calls.fetch_add(1, Ordering::SeqCst)))
+    })
+}
+
+#[tokio::test]
+async fn test_cache_completions_reuses_identical_requests() {
+    let model = counting();
+    let agent = SwarmsAgentBuilder::new_with_model(model.clone())
+        .cache_completions(Duration::from_secs(60))
+        .build();
+    // Clones share the completion cache but start with their own empty memory
+    let other = agent.clone();
+
+    let first = agent.run("Same task".to_string()).await.unwrap();
+    let second = other.run("Same task".to_string()).await.unwrap();
+
+    assert!(first.contains("answer 0"));
+    assert!(second.contains("answer 0"));
+    assert_eq!(model.call_count(), 1);
+}
+
+#[tokio::test]
+async fn test_caching_model_expires_entries() {
+    let model = counting();
+    let cached = CachingModel::new(model.clone(), Duration::ZERO);
+
+    let request = || CompletionRequest {
+        prompt: Message::user("hello"),
+        system_prompt: None,
+        chat_history: vec![],
+        tools: vec![],
+        temperature: Some(0.0),
+        max_tokens: None,
+    };
+
+    cached.completion(request()).await.unwrap();
+    cached.completion(request()).await.unwrap();
+    assert_eq!(model.call_count(), 2);
+}
+
+#[test]
+fn test_request_key_ignores_message_timestamps() {
+    let request = |timestamp: u64| CompletionRequest {
+        prompt: Message::user(""),
+        system_prompt: None,
+        chat_history: vec![Message::user(format!(
+            "Timestamp(millis): {timestamp} \nSame task"
+        ))],
+        tools: vec![],
+        temperature: Some(0.0),
+        max_tokens: None,
+    };
+
+    let key = |timestamp| CachingModel::<MockModel>::request_key(&request(timestamp)).unwrap();
+    assert_eq!(key(1_700_000_000_000), key(1_700_000_000_123));
+}
+
+#[tokio::test]
+async fn test_unwritable_save_state_dir_fails_before_running() {
+    let dir = tempfile::tempdir().unwrap();
+    // A regular file where the state directory should be
+    let blocker = dir.path().join("not_a_dir");
+    std::fs::write(&blocker, "").unwrap();
+    let state_dir = blocker.join("states");
+
+    let model = counting();
+    let agent = SwarmsAgentBuilder::new_with_model(model.clone())
+        .enable_autosave()
+        .save_state_dir(state_dir.to_string_lossy())
+        .build();
+
+    let result = agent.run("Persist me".to_string()).await;
+    match result {
+        Err(AgentError::PersistenceSetup { path, .. }) => {
+            assert_eq!(path, state_dir.to_string_lossy())
+        },
+        other => panic!("expected PersistenceSetup error, got {other:?}"),
+    }
+    assert_eq!(model.call_count(), 0);
+}
+
+// Mock model that answers with the index of the task it was given, finishing the
+// first task last. `MockModel` answers immediately, so it can't reorder completions.
+#[derive(Clone)]
+struct IndexedModel;
+
+impl Model for IndexedModel {
+    type RawCompletionResponse = ();
+
+    fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> BoxFuture<'_, Result<CompletionResponse<Self::RawCompletionResponse>, CompletionError>>
+    {
+        Box::pin(async move {
+            // The task itself is carried in the chat history
+            let history = serde_json::to_string(&request.chat_history).unwrap();
+            let index = (0..3)
+                .find(|i| history.contains(&format!("task {i}")))
+                .unwrap();
+            let delay = if index == 0 { 50 } else { 0 };
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            Ok(CompletionResponse {
+                choice: vec![AssistantContent::text(format!("result {index}"))],
+                raw_response: (),
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_run_parallel_tasks_ordered_preserves_input_order() {
+    let agent = SwarmsAgentBuilder::new_with_model(IndexedModel).build();
+
+    let tasks: Vec<String> = (0..3).map(|i| format!("task {i}")).collect();
+    let results = agent.run_parallel_tasks_ordered(tasks).await.unwrap();
+
+    assert_eq!(results.len(), 3);
+    for (i, result) in results.into_iter().enumerate() {
+        assert!(result.unwrap().contains(&format!("result {i}")));
+    }
+}
+
+#[tokio::test]
+async fn test_incremental_save_appends_ndjson() {
+    let dir = tempfile::tempdir().unwrap();
+    let agent = SwarmsAgentBuilder::new_with_model(answering("step"))
+        .agent_name("Saver")
+        .max_loops(3)
+        .enable_autosave()
+        .save_state_dir(dir.path().to_string_lossy())
+        .incremental_save(true)
+        .build();
+
+    agent.run("Long running task".to_string()).await.unwrap();
+
+    let files: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].extension().unwrap(), "ndjson");
+
+    let contents = std::fs::read_to_string(&files[0]).unwrap();
+    let lines: Vec<serde_json::Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    // The task plus one assistant reply per loop, each written exactly once
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0]["role"]["User"].is_string());
+    assert!(lines[3]["role"]["Assistant"].is_string());
+}
+
+#[derive(Default)]
+struct InMemoryBackend {
+    values: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryBackend for InMemoryBackend {
+    fn save<'a>(
+        &'a self,
+        key: &'a str,
+        data: Vec<u8>,
+    ) -> BoxFuture<'a, Result<(), PersistenceError>> {
+        self.values.lock().unwrap().insert(key.to_owned(), data);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn load<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> BoxFuture<'a, Result<Option<Vec<u8>>, PersistenceError>> {
+        let value = self.values.lock().unwrap().get(key).cloned();
+        Box::pin(async move { Ok(value) })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), PersistenceError>> {
+        self.values.lock().unwrap().remove(key);
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[tokio::test]
+async fn test_memory_backend_replaces_save_state_dir() {
+    let backend = Arc::new(InMemoryBackend::default());
+    let agent = SwarmsAgentBuilder::new_with_model(answering("step"))
+        .agent_name("Saver")
+        .max_loops(2)
+        .disable_task_complete_tool()
+        .enable_autosave()
+        .memory_backend(backend.clone())
+        .try_build()
+        .unwrap();
+    agent.run("Stateless task".to_string()).await.unwrap();
+
+    let values = backend.values.lock().unwrap();
+    assert_eq!(values.len(), 1);
+    let (key, data) = values.iter().next().unwrap();
+    assert!(key.ends_with(".json"));
+    let state: serde_json::Value = serde_json::from_slice(data).unwrap();
+    assert_eq!(state["history"].as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_conversation_loads_from_saved_state() {
+    for incremental_save in [false, true] {
+        let dir = tempfile::tempdir().unwrap();
+        let agent = SwarmsAgentBuilder::new_with_model(answering("step"))
+            .agent_name("Saver")
+            .max_loops(2)
+            .disable_task_complete_tool()
+            .enable_autosave()
+            .save_state_dir(dir.path().to_string_lossy())
+            .incremental_save(incremental_save)
+            .build();
+        agent.run("Saved task".to_string()).await.unwrap();
+
+        let conversation =
+            AgentConversation::from_saved_state(dir.path(), "Saver", &agent.id(), "Saved task")
+                .await
+                .unwrap();
+        let entries = conversation.entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].content, "Saved task");
+        assert_eq!(entries[2].content, "step");
+
+        let missing =
+            AgentConversation::from_saved_state(dir.path(), "Saver", &agent.id(), "Other task")
+                .await;
+        assert!(missing.is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown_without_mcp_servers() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("done")).build();
+    let clone = agent.clone();
+
+    agent.shutdown().await;
+    // Clones stay usable after another clone shut down
+    assert!(clone.run("Still here".to_string()).await.is_ok());
+    clone.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_conversation_stats_across_tasks() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("done"))
+        .agent_name("Stats")
+        .user_name("Alice")
+        .build();
+
+    agent.run("first".to_string()).await.unwrap();
+    agent.run("second task".to_string()).await.unwrap();
+
+    let stats = agent.conversation_stats();
+    let user = &stats["Alice(User)"];
+    assert_eq!(user.message_count, 2);
+    assert_eq!(user.total_chars, "first".len() + "second task".len());
+    assert_eq!(stats["Stats(Assistant)"].message_count, 2);
+}
+
+#[tokio::test]
+async fn test_max_response_chars_truncates_responses() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("x".repeat(100)))
+        .max_response_chars(10)
+        .build();
+
+    let response = agent.prompt("Say a lot").await.unwrap();
+    assert_eq!(
+        response,
+        format!("{}… [truncated 90 characters]", "x".repeat(10))
+    );
+
+    let result = agent.run("Say a lot".to_string()).await.unwrap();
+    assert!(!result.contains(&"x".repeat(11)));
+    assert!(result.contains("[truncated 90 characters]"));
+
+    // Short responses are left untouched
+    let agent = SwarmsAgentBuilder::new_with_model(answering("short"))
+        .max_response_chars(10)
+        .build();
+    assert_eq!(agent.prompt("Be brief").await.unwrap(), "short");
+}
+
+#[tokio::test]
+async fn test_max_history_messages_never_orphans_tool_results() {
+    let (model, histories) = recording("done", |request| request.chat_history.clone());
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .max_history_messages(2)
+        .build();
+
+    let history = vec![
+        Message::user("What's the weather?"),
+        Message::Assistant {
+            content: vec![AssistantContent::tool_call(
+                "call_1",
+                "weather",
+                serde_json::json!({"city": "Paris"}),
+            )],
+        },
+        Message::User {
+            content: vec![UserContent::ToolResult(ToolResult {
+                id: "call_1".to_string(),
+                content: vec![ToolResultContent::text("sunny")],
+            })],
+        },
+        Message::assistant("It is sunny in Paris"),
+    ];
+    agent.chat("Thanks!", history.clone()).await.unwrap();
+
+    // The question is kept, and the last message alone would send `call_1`'s result
+    // without its call
+    assert_eq!(
+        histories.lock().unwrap()[0],
+        [
+            Message::user("What's the weather?"),
+            Message::assistant("It is sunny in Paris")
+        ]
+    );
+
+    // Short-term memory of a task is cut the same way before it is sent
+    agent.run("Plan a trip".to_string()).await.unwrap();
+    assert!(histories.lock().unwrap()[1..].iter().all(|h| h.len() <= 2));
+}
+
+#[tokio::test]
+async fn test_max_history_messages_keeps_the_task() {
+    let (model, histories) = recording("step", |request| request.chat_history.clone());
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .user_name("Ann")
+        .max_loops(5)
+        .disable_task_complete_tool()
+        .max_history_messages(3)
+        .build();
+
+    agent.run("Plan a trip".to_string()).await.unwrap();
+
+    let histories = histories.lock().unwrap();
+    assert_eq!(histories.len(), 5);
+    for history in histories.iter() {
+        assert!(history.len() <= 3);
+        assert_eq!(history[0], Message::user("Ann: Plan a trip"));
+    }
+}
+
+#[tokio::test]
+async fn test_persona_switching() {
+    let mut agent = SwarmsAgentBuilder::new_with_model(answering("done"))
+        .agent_name("Writer")
+        .system_prompt("You are a lawyer.")
+        .build();
+    agent.run("Draft a memo".to_string()).await.unwrap();
+
+    agent.push_persona("You are a journalist.");
+    assert_eq!(agent.get_system_prompt(), Some("You are a journalist."));
+
+    let result = agent.run("Draft a memo".to_string()).await.unwrap();
+    assert!(result.contains("Writer(System): "));
+    assert!(result.contains("Persona switched to: You are a journalist."));
+
+    assert_eq!(
+        agent.pop_persona().as_deref(),
+        Some("You are a journalist.")
+    );
+    assert_eq!(agent.get_system_prompt(), Some("You are a lawyer."));
+    assert_eq!(agent.pop_persona(), None);
+    assert_eq!(agent.get_system_prompt(), Some("You are a lawyer."));
+
+    agent.switch_persona("You are a poet.");
+    assert_eq!(agent.get_system_prompt(), Some("You are a poet."));
+}
+
+#[tokio::test]
+async fn test_mcp_health_check_all_without_mcp_servers() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("done")).build();
+    assert!(agent.mcp_health_check_all().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_tools_are_sent_in_name_order() {
+    let presets = [
+        ToolPreset::CodeInterpreter,
+        ToolPreset::WebSearch,
+        ToolPreset::FileSystem(".".into()),
+    ];
+
+    let mut requests = Vec::new();
+    for order in [[0, 1, 2], [2, 0, 1]] {
+        let (model, tools) = recording("done", |request| request.tools.clone());
+        let agent = order
+            .iter()
+            .fold(SwarmsAgentBuilder::new_with_model(model), |builder, &i| {
+                builder.with_prebuilt_tools(presets[i].clone())
+            })
+            .build();
+        agent.run("Use the tools".to_string()).await.unwrap();
+        let names: Vec<_> = tools.lock().unwrap()[0]
+            .iter()
+            .map(|tool| tool.name.clone())
+            .collect();
+        requests.push(names);
+    }
+
+    assert_eq!(requests[0], requests[1]);
+    let mut sorted = requests[0].clone();
+    sorted.sort();
+    assert_eq!(requests[0], sorted);
+    assert!(requests[0].contains(&"task_evaluator".to_string()));
+}
+
+#[tokio::test]
+async fn test_add_tools_from_keeps_custom_descriptions() {
+    let shared = SwarmsAgentBuilder::new_with_model(answering("done"))
+        .add_tool_with_description(EchoTool, "Repeat the arguments back verbatim");
+
+    let (model, tools) = recording("done", |request| request.tools.clone());
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .add_tools_from(&shared)
+        .build();
+    agent.run("Echo something".to_string()).await.unwrap();
+
+    let tools = tools.lock().unwrap();
+    let echo = tools[0].iter().find(|tool| tool.name == "echo").unwrap();
+    assert_eq!(echo.description, "Repeat the arguments back verbatim");
+}
+
+#[tokio::test]
+async fn test_add_tool_with_description_replaces_registered_tool() {
+    let (model, tools) = recording("done", |request| request.tools.clone());
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .add_tool(EchoTool)
+        .add_tool_with_description(EchoTool, "Repeat the arguments back verbatim")
+        .build();
+    agent.run("Echo something".to_string()).await.unwrap();
+
+    let tools = tools.lock().unwrap();
+    let echoes: Vec<_> = tools[0].iter().filter(|tool| tool.name == "echo").collect();
+    assert_eq!(echoes.len(), 1);
+    assert_eq!(echoes[0].description, "Repeat the arguments back verbatim");
+}
+
+// Tool that declares the schema of its output
+struct ReportTool;
+
+impl Tool for ReportTool {
+    type Error = std::io::Error;
+    type Args = serde_json::Value;
+    type Output = serde_json::Value;
+
+    const NAME: &'static str = "report";
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description: "Builds a report".to_string(),
+            parameters: serde_json::json!({"type": "object"}),
+            output_schema: None,
+        }
+    }
+
+    fn output_schema(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "type": "object",
+            "properties": {"summary": {"type": "string"}}
+        }))
+    }
+
+    async fn call(&self, _args: Self::Args) -> Result<Self::Output, Self::Error> {
+        Ok(serde_json::json!({"summary": "ok"}))
+    }
+}
+
+#[tokio::test]
+async fn test_output_schema_is_sent_with_added_tools() {
+    for custom_description in [false, true] {
+        let (model, tools) = recording("done", |request| request.tools.clone());
+        let builder = SwarmsAgentBuilder::new_with_model(model);
+        let builder = if custom_description {
+            builder.add_tool_with_description(ReportTool, "Summarize the findings")
+        } else {
+            builder.add_tool(ReportTool)
+        };
+        builder
+            .build()
+            .run("Write a report".to_string())
+            .await
+            .unwrap();
+
+        let tools = tools.lock().unwrap();
+        let report = tools[0].iter().find(|tool| tool.name == "report").unwrap();
+        assert_eq!(
+            report.output_schema.as_ref().unwrap()["properties"]["summary"]["type"],
+            "string"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_loop_prompts_are_configurable() {
+    let (model, prompts) = recording("done", prompt_text);
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .max_loops(3)
+        .disable_task_complete_tool()
+        .first_loop_prompt("Start on: {task}")
+        .continuation_prompt_template("Loop {loop}/{max_loops} for {task}")
+        .build();
+
+    agent.run("the report".to_string()).await.unwrap();
+    assert_eq!(
+        *prompts.lock().unwrap(),
+        [
+            "Start on: the report",
+            "Loop 2/3 for the report",
+            "Loop 3/3 for the report"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_default_loop_prompts() {
+    let (model, prompts) = recording("done", prompt_text);
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .max_loops(2)
+        .disable_task_complete_tool()
+        .build();
+
+    agent.run("the report".to_string()).await.unwrap();
+    let prompts = prompts.lock().unwrap();
+    assert_eq!(prompts[0], "");
+    assert!(prompts[1].starts_with("Now, you are in loop 2 of 2"));
+    assert!(prompts[1].ends_with("origin task:\nthe report"));
+}
+
+#[tokio::test]
+async fn test_run_with_documents_adds_documents_to_task() {
+    let (model, prompts) = recording("done", prompt_text);
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .disable_task_complete_tool()
+        .first_loop_prompt("{task}")
+        .max_document_tokens(5)
+        .build();
+
+    let documents = vec![
+        Document::new("notes.md", "# Notes", "text/markdown"),
+        Document::new("big.txt", "y".repeat(100), "text/plain"),
+    ];
+    agent
+        .run_with_documents("Summarize the files", documents)
+        .await
+        .unwrap();
+
+    let prompts = prompts.lock().unwrap();
+    assert!(prompts[0].starts_with("Summarize the files\n\n### Documents ###"));
+    assert!(prompts[0].contains("--- notes.md (text/markdown) ---\n# Notes\n"));
+    assert!(prompts[0].contains(&format!(
+        "--- big.txt (text/plain) ---\n{}… [truncated about 20 tokens]",
+        "y".repeat(20)
+    )));
+}
+
+#[tokio::test]
+async fn test_run_with_context_adds_documents_message() {
+    let (model, prompts) = recording("done", prompt_text);
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .disable_task_complete_tool()
+        .first_loop_prompt("{task}")
+        .build();
+
+    let docs = vec![
+        ContextDocument::new("Paper A", "Results of A"),
+        ContextDocument::new("Paper B", "Results of B"),
+    ];
+    agent
+        .run_with_context("Compare the papers".to_string(), docs)
+        .await
+        .unwrap();
+
+    // The task is sent as is, the documents are a separate message
+    assert_eq!(*prompts.lock().unwrap(), ["Compare the papers"]);
+    let stats = agent.conversation_stats();
+    assert_eq!(stats["Documents(User)"].message_count, 1);
+}
+
+#[tokio::test]
+async fn test_pipe_feeds_first_output_to_second() {
+    let first = SwarmsAgentBuilder::new_with_model(answering("draft"))
+        .agent_name("drafter")
+        .disable_task_complete_tool()
+        .build();
+    let (model, prompts) = recording("done", prompt_text);
+    let second = SwarmsAgentBuilder::new_with_model(model)
+        .agent_name("editor")
+        .disable_task_complete_tool()
+        .first_loop_prompt("{task}")
+        .build();
+
+    let piped = pipe(Box::new(first), Box::new(second));
+    assert_eq!(piped.name(), "drafter|editor");
+
+    let result = piped.run("Write a post".to_string()).await.unwrap();
+    assert!(result.contains("done"));
+    assert!(prompts.lock().unwrap()[0].contains("draft"));
+}
+
+#[tokio::test]
+async fn test_run_json_extracts_json_from_prose() {
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Article {
+        title: String,
+        url: String,
+    }
+
+    let response = "Here are the articles:\n```json\n[{\"title\": \"A {b}\", \"url\": \"https://a.dev\"}]\n```\nDone.";
+    let agent = SwarmsAgentBuilder::new_with_model(answering(response))
+        .disable_task_complete_tool()
+        .build();
+
+    let articles: Vec<Article> = agent.run_json("List articles").await.unwrap();
+    assert_eq!(
+        articles,
+        [Article {
+            title: "A {b}".to_string(),
+            url: "https://a.dev".to_string(),
+        }]
+    );
+
+    let agent = SwarmsAgentBuilder::new_with_model(answering("no json {here"))
+        .disable_task_complete_tool()
+        .build();
+    let result = agent.run_json::<Vec<Article>>("List articles").await;
+    assert!(matches!(result, Err(AgentError::JsonExtraction(raw)) if raw == "no json {here"));
+}
+
+#[tokio::test]
+async fn test_run_json_ignores_json_in_the_task() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("Result: {\"total\": 3}"))
+        .disable_task_complete_tool()
+        .build();
+
+    let value: serde_json::Value = agent
+        .run_json("Sum the counts in {\"counts\": [1, 2]}")
+        .await
+        .unwrap();
+    assert_eq!(value, serde_json::json!({"total": 3}));
+}
+
+#[tokio::test]
+async fn test_run_json_applies_after_middlewares() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering(r#"{"note": "darn it"}"#))
+        .disable_task_complete_tool()
+        .add_middleware(ProfanityFilter::new(["darn"]))
+        .build();
+
+    let value: serde_json::Value = agent.run_json("Write a note").await.unwrap();
+    assert_eq!(value, serde_json::json!({"note": "**** it"}));
+}
+
+// Mock model without native tool calling that answers with a JSON tool call, recording
+// the tools and system prompt of every request
+fn no_tools_model() -> (MockModel, Arc<Mutex<Vec<(usize, Option<String>)>>>) {
+    let (model, requests) = recording(
+        r#"Calling: {"name": "task_evaluator", "arguments": {"status": "Complete"}}"#,
+        |request| (request.tools.len(), request.system_prompt.clone()),
+    );
+    (model.set_supports_tools(false), requests)
+}
+
+#[tokio::test]
+async fn test_tools_unsupported_fails_fast() {
+    let (model, requests) = no_tools_model();
+    let agent = SwarmsAgentBuilder::new_with_model(model).build();
+
+    let result = agent.run("Finish the task".to_string()).await;
+    assert!(matches!(result, Err(AgentError::ToolsUnsupported(1))));
+    assert!(requests.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_prompt_tool_fallback_parses_tool_calls_from_text() {
+    let (model, requests) = no_tools_model();
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .system_prompt("You are helpful.")
+        .max_loops(3)
+        .prompt_tool_fallback(true)
+        .build();
+
+    let result = agent.run("Finish the task".to_string()).await.unwrap();
+    assert!(result.contains("[Tool name]: task_evaluator"));
+
+    // The task evaluator completed the task in the first loop
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    let (tool_count, system_prompt) = &requests[0];
+    assert_eq!(*tool_count, 0);
+    let system_prompt = system_prompt.as_deref().unwrap();
+    assert!(system_prompt.starts_with("You are helpful."));
+    assert!(system_prompt.contains("- task_evaluator:"));
+}
+
+#[cfg(feature = "pdf-export")]
+#[tokio::test]
+async fn test_export_pdf_writes_report() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("done"))
+        .agent_name("Reporter")
+        .disable_task_complete_tool()
+        .build();
+    agent.run("Write the report".to_string()).await.unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reports").join("report.pdf");
+    agent.export_pdf("Write the report", &path).await.unwrap();
+
+    let pdf = std::fs::read(&path).unwrap();
+    assert!(pdf.starts_with(b"%PDF"));
+}
+
+#[tokio::test]
+async fn test_system_prompt_refresh_is_deduplicated() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("working"))
+        .agent_name("Refresher")
+        .system_prompt("Always answer in French.")
+        .max_loops(5)
+        .disable_task_complete_tool()
+        .system_prompt_refresh_interval(2)
+        .build();
+
+    let result = agent.run("Long task".to_string()).await.unwrap();
+
+    // Reminders were added in loops 3 and 5, but only the latest one is kept
+    assert_eq!(result.matches("Always answer in French.").count(), 1);
+    let stats = agent.conversation_stats();
+    assert_eq!(stats["Refresher(System)"].message_count, 1);
+}
+
+#[tokio::test]
+async fn test_rate_limited_requests_wait_for_retry_after() {
+    // Rate limited on the first call
+    let calls = AtomicUsize::new(0);
+    let model = MockModel::from_fallible_fn(move |_| {
+        if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+            return Err(CompletionError::RateLimited {
+                retry_after: Some(Duration::from_millis(100)),
+            });
+        }
+        Ok(text_response("done"))
+    });
+    let agent = SwarmsAgentBuilder::new_with_model(model.clone())
+        .retry_attempts(2)
+        .disable_task_complete_tool()
+        .build();
+
+    let start = std::time::Instant::now();
+    let result = agent.run("Try twice".to_string()).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert!(result.contains("done"));
+    assert_eq!(model.call_count(), 2);
+}
+
+#[tokio::test]
+async fn test_failed_attempts_back_off_exponentially() {
+    // The first two calls fail
+    let calls = AtomicUsize::new(0);
+    let model = MockModel::from_fallible_fn(move |_| {
+        if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+            return Err(CompletionError::Provider("unavailable".to_string()));
+        }
+        Ok(text_response("done"))
+    });
+    let agent = SwarmsAgentBuilder::new_with_model(model.clone())
+        .retry_attempts(3)
+        .retry_base_delay(Duration::from_millis(40))
+        .disable_task_complete_tool()
+        .build();
+
+    // At least half of 40ms, then half of 80ms
+    let start = std::time::Instant::now();
+    let result = agent.run("Try thrice".to_string()).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(60));
+    assert!(result.contains("done"));
+    assert_eq!(model.call_count(), 3);
+}
+
+#[tokio::test]
+async fn test_fork_diverges_independently() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("done"))
+        .agent_name("agent")
+        .disable_task_complete_tool()
+        .build();
+    agent.run("first".to_string()).await.unwrap();
+
+    let fork = agent.fork();
+    fork.run("second".to_string()).await.unwrap();
+
+    let user_messages = |agent: &SwarmsAgent<MockModel>| {
+        agent
+            .conversation_stats()
+            .iter()
+            .filter(|(role, _)| role.ends_with("(User)"))
+            .map(|(_, stats)| stats.message_count)
+            .sum::<usize>()
+    };
+    assert_eq!(user_messages(&agent), 1);
+    assert_eq!(user_messages(&fork), 2);
+}
+
+#[tokio::test]
+async fn test_clone_with_new_id_runs_independently() {
+    let agent = SwarmsAgentBuilder::new_with_model(answering("done"))
+        .disable_task_complete_tool()
+        .build();
+    let copies: Vec<_> = (0..3).map(|_| agent.clone_with_new_id()).collect();
+
+    let mut ids: Vec<_> = copies.iter().map(|copy| copy.id()).collect();
+    ids.push(agent.id());
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 4);
+
+    futures::future::try_join_all(
+        copies
+            .iter()
+            .enumerate()
+            .map(|(i, copy)| copy.run(format!("task {i}"))),
+    )
+    .await
+    .unwrap();
+    assert!(copies.iter().all(|copy| copy.metrics().runs == 1));
+    assert_eq!(agent.metrics().runs, 0);
+}
+
+#[tokio::test]
+async fn test_clone_with_new_id_autosaves_separately() {
+    let dir = tempfile::tempdir().unwrap();
+    let agent = SwarmsAgentBuilder::new_with_model(answering("step"))
+        .agent_name("Saver")
+        .max_loops(2)
+        .disable_task_complete_tool()
+        .enable_autosave()
+        .save_state_dir(dir.path().to_string_lossy())
+        .build();
+    let copies = [agent.clone_with_new_id(), agent.clone_with_new_id()];
+
+    futures::future::try_join_all(copies.iter().map(|copy| copy.run("Same task".to_string())))
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    for copy in &copies {
+        let conversation =
+            AgentConversation::from_saved_state(dir.path(), "Saver", &copy.id(), "Same task")
+                .await
+                .unwrap();
+        let entries = conversation.entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].content, "Same task");
+    }
+}
+
+#[tokio::test]
+async fn test_tools_only_reprompts_after_text_response() {
+    let (model, prompts) = recording("done", prompt_text);
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .max_loops(2)
+        .tools_only(true)
+        .build();
+
+    agent.run("the report".to_string()).await.unwrap();
+    assert_eq!(
+        *prompts.lock().unwrap(),
+        [
+            "",
+            "You must use a tool to respond. Please select and call the appropriate tool."
+        ]
+    );
+}
+
+#[test]
+fn test_try_build_reports_invalid_config() {
+    let error = |builder: SwarmsAgentBuilder<MockModel>| builder.try_build().err();
+    let builder = || SwarmsAgentBuilder::new_with_model(answering("done"));
+
+    assert!(builder().try_build().is_ok());
+    assert_eq!(
+        error(builder().agent_name(" ")),
+        Some(AgentBuildError::EmptyName)
+    );
+    assert_eq!(
+        error(builder().max_loops(0)),
+        Some(AgentBuildError::ZeroMaxLoops)
+    );
+    assert_eq!(
+        error(builder().temperature(3.5)),
+        Some(AgentBuildError::InvalidTemperature(3.5))
+    );
+    assert_eq!(
+        error(builder().enable_autosave()),
+        Some(AgentBuildError::AutosaveWithoutSaveStateDir)
+    );
+    assert_eq!(
+        error(builder().tools_only(true).disable_task_complete_tool()),
+        Some(AgentBuildError::ToolsOnlyWithoutTools)
+    );
+    assert_eq!(
+        error(builder().tool_timeout(Duration::ZERO)),
+        Some(AgentBuildError::ZeroToolTimeout)
+    );
+    assert_eq!(
+        error(builder().max_history_messages(0)),
+        Some(AgentBuildError::ZeroMaxHistoryMessages)
+    );
+    assert!(matches!(
+        error(
+            builder()
+                .add_stop_word("(unclosed")
+                .stop_word_matching(StopWordMatching::Regex)
+        ),
+        Some(AgentBuildError::InvalidStopWordPattern { pattern, .. }) if pattern == "(unclosed"
+    ));
+}
+
+// Tool that never finishes
+struct HangingTool;
+
+impl Tool for HangingTool {
+    type Error = std::io::Error;
+    type Args = serde_json::Value;
+    type Output = String;
+
+    const NAME: &'static str = "hanging_tool";
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description: "Never returns".to_string(),
+            parameters: serde_json::json!({"type": "object"}),
+            output_schema: None,
+        }
+    }
+
+    fn call(
+        &self,
+        _args: Self::Args,
+    ) -> impl Future<Output = Result<Self::Output, Self::Error>> + Send + Sync {
+        std::future::pending()
+    }
+}
+
+#[tokio::test]
+async fn test_tool_timeout_reprompts_for_alternative() {
+    for concurrent in [true, false] {
+        // Calls the hanging tool in the first request and answers with text after
+        let prompts = Arc::new(Mutex::new(Vec::new()));
+        let model = MockModel::from_fn({
+            let prompts = Arc::clone(&prompts);
+            move |request| {
+                let mut prompts = prompts.lock().unwrap();
+                let choice = if prompts.is_empty() {
+                    AssistantContent::tool_call("call_1", "hanging_tool", serde_json::json!({}))
+                } else {
+                    AssistantContent::text("done another way")
+                };
+                prompts.push(prompt_text(request));
+                CompletionResponse {
+                    choice: vec![choice],
+                    raw_response: (),
+                }
+            }
+        });
+        let mut builder = SwarmsAgentBuilder::new_with_model(model)
+            .max_loops(2)
+            .disable_task_complete_tool()
+            .add_tool(HangingTool)
+            .tool_timeout(Duration::from_millis(20));
+        if !concurrent {
+            builder = builder.disable_concurrent_tool_call();
+        }
+        let agent = builder.build();
+
+        let result = agent.run("Use the tool".to_string()).await.unwrap();
+
+        assert!(result.contains("done another way"));
+        assert_eq!(agent.timed_out_tool_calls(), 1);
+        assert_eq!(
+            prompts.lock().unwrap()[1],
+            "Tool hanging_tool timed out. Consider an alternative approach."
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_planning_model_is_used_for_the_plan_only() {
+    let (model, prompts) = recording("done", prompt_text);
+    let (planning_model, planning_prompts) = recording("done", prompt_text);
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .planning_model(planning_model)
+        .enable_plan("Plan:".to_string())
+        .max_loops(1)
+        .disable_task_complete_tool()
+        .build();
+
+    agent.run("the report".to_string()).await.unwrap();
+    assert_eq!(*planning_prompts.lock().unwrap(), ["Plan: the report"]);
+    assert_eq!(*prompts.lock().unwrap(), [""]);
+}
+
+#[tokio::test]
+async fn test_run_interactive_until_exit_command() {
+    let (model, prompts) = recording("Hi there", prompt_text);
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .agent_name("Bot")
+        .user_name("Ann")
+        .disable_task_complete_tool()
+        .exit_command("quit")
+        .build();
+
+    let input: &[u8] = b"hello\n\nhow are you?\nquit\nnever read\n";
+    let mut output = Vec::new();
+    let conversation = agent
+        .run_interactive_with(input, &mut output)
+        .await
+        .unwrap();
+
+    assert_eq!(conversation.history.len(), 4);
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.matches("Bot: Hi there\n").count(), 2);
+    // Each message is sent as the prompt, not as an empty prompt after the history
+    assert_eq!(
+        *prompts.lock().unwrap(),
+        ["Ann: hello", "Ann: how are you?"]
+    );
+}
+
+// Tool that echoes its arguments
+struct EchoTool;
+
+impl Tool for EchoTool {
+    type Error = std::io::Error;
+    type Args = serde_json::Value;
+    type Output = String;
+
+    const NAME: &'static str = "echo";
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description: "Echoes its arguments".to_string(),
+            parameters: serde_json::json!({"type": "object"}),
+            output_schema: None,
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        // Later calls finish first, so concurrent results complete out of call order
+        let n = args["n"].as_u64().unwrap_or(1).max(1);
+        tokio::time::sleep(Duration::from_millis(40 / n)).await;
+        Ok(format!("echo {}", args["n"]))
+    }
+}
+
+// Mock model that makes the given tool calls in its first turn and answers with text after
+fn tool_turn(first_turn: Vec<AssistantContent>) -> MockModel {
+    MockModel::from_responses([
+        CompletionResponse {
+            choice: first_turn,
+            raw_response: (),
+        },
+        text_response("all done"),
+    ])
+}
+
+fn echo_call(id: &str, n: u32) -> AssistantContent {
+    AssistantContent::tool_call(id, "echo", serde_json::json!({ "n": n }))
+}
+
+#[tokio::test]
+async fn test_two_tool_turn_reports_both_results() {
+    for concurrent in [true, false] {
+        let model = tool_turn(vec![echo_call("call_1", 1), echo_call("call_2", 2)]);
+        let mut builder = SwarmsAgentBuilder::new_with_model(model)
+            .max_loops(2)
+            .disable_task_complete_tool()
+            .add_tool(EchoTool);
+        if !concurrent {
+            builder = builder.disable_concurrent_tool_call();
+        }
+        let agent = builder.build();
+
+        let responses = Mutex::new(Vec::new());
+        let result = agent
+            .run_until_approved("Echo twice".to_string(), |response| {
+                responses.lock().unwrap().push(response.to_string());
+                false
+            })
+            .await
+            .unwrap();
+
+        let responses = responses.into_inner().unwrap();
+        assert_eq!(responses.len(), 2);
+        let block = |n: u32| {
+            format!(
+                "[Tool name]: echo\n[Tool args]: {{\"n\":{n}}}\n[Tool result]: \"echo {n}\"\n\n"
+            )
+        };
+        // Results are reported in call order, although call 2 finishes first
+        let expected = format!("{}{}", block(1), block(2));
+        assert_eq!(responses[0], expected);
+        assert_eq!(result.matches(&expected).count(), 1);
+        assert_eq!(responses[1], "all done");
+    }
+}
+
+#[tokio::test]
+async fn test_tool_and_task_evaluator_turn_keeps_all_results() {
+    let model = tool_turn(vec![
+        echo_call("call_1", 1),
+        AssistantContent::tool_call(
+            "call_2",
+            "task_evaluator",
+            serde_json::json!({"status": "Complete"}),
+        ),
+    ]);
+    let agent = SwarmsAgentBuilder::new_with_model(model.clone())
+        .max_loops(3)
+        .add_tool(EchoTool)
+        .build();
+
+    let result = agent.run("Echo once".to_string()).await.unwrap();
+
+    // The evaluator completed the task, and the echo result was not dropped from memory
+    assert_eq!(model.call_count(), 1);
+    assert!(result.contains("[Tool result]: \"echo 1\""));
+    assert!(result.contains("[Tool name]: task_evaluator"));
+}
+
+// Mock model that makes the given tool call in its first turn and answers with text after,
+// recording the chat history of every request
+fn failing_tool_turn(call: AssistantContent) -> (MockModel, Arc<Mutex<Vec<String>>>) {
+    let histories = Arc::new(Mutex::new(Vec::new()));
+    let model = MockModel::from_fn({
+        let histories = Arc::clone(&histories);
+        move |request| {
+            let mut histories = histories.lock().unwrap();
+            histories.push(serde_json::to_string(&request.chat_history).unwrap());
+            if histories.len() == 1 {
+                CompletionResponse {
+                    choice: vec![call.clone()],
+                    raw_response: (),
+                }
+            } else {
+                text_response("done another way")
+            }
+        }
+    });
+    (model, histories)
+}
+
+#[tokio::test]
+async fn test_unknown_tool_is_reported_to_the_model() {
+    for concurrent in [true, false] {
+        let (model, histories) = failing_tool_turn(AssistantContent::tool_call(
+            "call_1",
+            "missing_tool",
+            serde_json::json!({}),
+        ));
+        let mut builder = SwarmsAgentBuilder::new_with_model(model)
+            .max_loops(2)
+            .disable_task_complete_tool()
+            .add_tool(EchoTool);
+        if !concurrent {
+            builder = builder.disable_concurrent_tool_call();
+        }
+        let agent = builder.build();
+
+        let result = agent.run("Use the tool".to_string()).await.unwrap();
+
+        assert!(result.contains("done another way"));
+        assert!(histories.lock().unwrap()[1].contains("Tool missing_tool not found"));
+    }
+}
+
+// Tool that always fails
+struct FailingTool;
+
+impl Tool for FailingTool {
+    type Error = std::io::Error;
+    type Args = serde_json::Value;
+    type Output = String;
+
+    const NAME: &'static str = "failing_tool";
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description: "Always fails".to_string(),
+            parameters: serde_json::json!({"type": "object"}),
+            output_schema: None,
+        }
+    }
+
+    async fn call(&self, _args: Self::Args) -> Result<Self::Output, Self::Error> {
+        Err(std::io::Error::other("disk full"))
+    }
+}
+
+#[tokio::test]
+async fn test_sequential_tool_failure_reaches_next_turn() {
+    let (model, histories) = failing_tool_turn(AssistantContent::tool_call(
+        "call_1",
+        "failing_tool",
+        serde_json::json!({}),
+    ));
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .max_loops(2)
+        .disable_task_complete_tool()
+        .disable_concurrent_tool_call()
+        .add_tool(FailingTool)
+        .build();
+
+    let result = agent.run("Use the tool".to_string()).await.unwrap();
+
+    assert!(result.contains("done another way"));
+    assert!(histories.lock().unwrap()[1].contains("Execution failed: disk full"));
+}
+
+#[tokio::test]
+async fn test_metrics_count_runs_loops_and_tool_calls() {
+    let model = tool_turn(vec![echo_call("call_1", 1), echo_call("call_2", 2)]);
+    let agent = SwarmsAgentBuilder::new_with_model(model)
+        .max_loops(2)
+        .disable_task_complete_tool()
+        .add_tool(EchoTool)
+        .build();
+    assert_eq!(agent.metrics(), MetricsSnapshot::default());
+
+    agent.run("Echo twice".to_string()).await.unwrap();
+
+    let metrics = agent.metrics();
+    assert_eq!(metrics.runs, 1);
+    assert_eq!(metrics.loops, 2);
+    assert_eq!(metrics.llm_calls, 2);
+    assert_eq!(metrics.retries, 0);
+    assert_eq!(metrics.tool_calls["echo"], 2);
+}
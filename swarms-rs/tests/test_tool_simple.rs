@@ -3,7 +3,7 @@
 
 use serde::{Deserialize, Serialize};
 use swarms_rs::llm::request::ToolDefinition;
-use swarms_rs::structs::tool::{Tool, ToolDyn, ToolError};
+use swarms_rs::structs::tool::{MCPTool, Tool, ToolDyn, ToolError, ToolOutput, Toolkit};
 
 // Mock tool for testing
 #[derive(Debug, Clone)]
@@ -65,6 +65,7 @@ impl Tool for MockTool {
                 },
                 "required": ["input"]
             }),
+            output_schema: None,
         }
     }
 
@@ -150,7 +151,10 @@ async fn test_tool_dyn_trait_invalid_json() {
     let result = tool.call(invalid_json.to_string()).await;
 
     assert!(result.is_err());
-    assert!(matches!(result.unwrap_err(), ToolError::JsonError(_)));
+    assert!(matches!(
+        result.unwrap_err(),
+        ToolError::InvalidArguments(_)
+    ));
 }
 
 #[tokio::test]
@@ -161,33 +165,41 @@ async fn test_tool_dyn_trait_tool_error() {
     let result = tool.call(args_json.to_string()).await;
 
     assert!(result.is_err());
-    assert!(matches!(result.unwrap_err(), ToolError::ToolCallError(_)));
+    assert!(matches!(result.unwrap_err(), ToolError::ExecutionFailed(_)));
 }
 
 #[test]
 fn test_tool_error_types() {
-    // Test JsonError
-    let json_error = serde_json::from_str::<serde_json::Value>("invalid json").unwrap_err();
-    let tool_error = ToolError::JsonError(json_error);
-    assert!(matches!(tool_error, ToolError::JsonError(_)));
+    let tool_error = ToolError::InvalidArguments("missing field `input`".to_string());
+    assert!(matches!(tool_error, ToolError::InvalidArguments(_)));
 
-    // Test ToolCallError
-    let custom_error = MockToolError::TestError("custom error".to_string());
-    let boxed_error: Box<dyn core::error::Error + Send + Sync> = Box::new(custom_error);
-    let tool_error = ToolError::ToolCallError(boxed_error);
-    assert!(matches!(tool_error, ToolError::ToolCallError(_)));
+    let tool_error = ToolError::ExecutionFailed("custom error".to_string());
+    assert!(matches!(tool_error, ToolError::ExecutionFailed(_)));
+
+    let tool_error = ToolError::NotFound("missing_tool".to_string());
+    assert!(matches!(tool_error, ToolError::NotFound(_)));
+
+    let tool_error = ToolError::from(anyhow::anyhow!("connection reset"));
+    assert!(matches!(tool_error, ToolError::Upstream(_)));
 }
 
 #[test]
 fn test_tool_error_display() {
-    let json_error = serde_json::from_str::<serde_json::Value>("invalid json").unwrap_err();
-    let tool_error = ToolError::JsonError(json_error);
-    assert!(tool_error.to_string().starts_with("JsonError:"));
+    let tool_error = ToolError::InvalidArguments("missing field `input`".to_string());
+    assert_eq!(
+        tool_error.to_string(),
+        "Invalid arguments: missing field `input`"
+    );
 
     let custom_error = MockToolError::TestError("test message".to_string());
-    let boxed_error: Box<dyn core::error::Error + Send + Sync> = Box::new(custom_error);
-    let tool_error = ToolError::ToolCallError(boxed_error);
-    assert!(tool_error.to_string().starts_with("ToolCallError:"));
+    let tool_error = ToolError::ExecutionFailed(custom_error.to_string());
+    assert!(tool_error.to_string().starts_with("Execution failed:"));
+
+    let tool_error = ToolError::NotFound("missing_tool".to_string());
+    assert_eq!(tool_error.to_string(), "Tool missing_tool not found");
+
+    let tool_error = ToolError::from(anyhow::anyhow!("connection reset"));
+    assert_eq!(tool_error.to_string(), "Upstream error: connection reset");
 }
 
 #[test]
@@ -201,9 +213,243 @@ fn test_tool_definition_creation() {
                 "param": {"type": "string"}
             }
         }),
+        output_schema: None,
     };
 
     assert_eq!(definition.name, "test_definition");
     assert!(!definition.description.is_empty());
     assert!(definition.parameters.is_object());
 }
+
+#[tokio::test]
+async fn test_toolkit_bundles_tools() {
+    let toolkit = Toolkit::new("mock_toolkit")
+        .add_tool(MockTool::new("first"))
+        .add_tool(MockTool::new("second"));
+
+    assert_eq!(toolkit.name(), "mock_toolkit");
+    assert_eq!(toolkit.len(), 2);
+    assert!(!toolkit.is_empty());
+
+    let names: Vec<String> = toolkit
+        .tools()
+        .iter()
+        .map(|tool| tool.definition().name)
+        .collect();
+    assert_eq!(names, vec!["first", "second"]);
+
+    let result = toolkit.tools()[0]
+        .call(r#"{"input": "hello"}"#.to_string())
+        .await
+        .unwrap();
+    assert!(result.contains("Processed: hello"));
+}
+
+// Tool that declares the shape of its output
+struct SchemaTool;
+
+impl Tool for SchemaTool {
+    type Error = std::io::Error;
+    type Args = MockArgs;
+    type Output = MockOutput;
+
+    const NAME: &'static str = "schema_tool";
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description: "Echoes its input".to_string(),
+            parameters: serde_json::json!({"type": "object"}),
+            output_schema: None,
+        }
+    }
+
+    fn output_schema(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "type": "object",
+            "properties": {"result": {"type": "string"}}
+        }))
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        Ok(MockOutput { result: args.input })
+    }
+}
+
+#[test]
+fn test_tool_output_schema_in_definition() {
+    let definition = ToolDyn::definition(&SchemaTool);
+    let schema = definition.output_schema.as_ref().unwrap();
+    assert_eq!(schema["properties"]["result"]["type"], "string");
+    assert!(
+        definition
+            .description_with_output_schema()
+            .starts_with("Echoes its input\n\nReturns JSON matching this schema: {")
+    );
+
+    // Tools without an output schema keep their plain description
+    let mock = MockTool::new("plain");
+    let definition = ToolDyn::definition(&mock);
+    assert!(definition.output_schema.is_none());
+    assert_eq!(
+        definition.description_with_output_schema(),
+        definition.description
+    );
+}
+
+fn mcp_tool(schema: serde_json::Value) -> rmcp::model::Tool {
+    let serde_json::Value::Object(schema) = schema else {
+        panic!("schema must be an object");
+    };
+    rmcp::model::Tool::new("search", "Search the web", schema)
+}
+
+#[test]
+fn test_mcp_schema_validation_accepts_well_formed_schema() {
+    let tool = mcp_tool(serde_json::json!({
+        "type": "object",
+        "properties": {
+            "query": { "type": "string" },
+            "filters": { "type": "array", "items": { "type": ["string", "null"] } }
+        },
+        "required": ["query"]
+    }));
+    assert!(MCPTool::validate_schema(&tool).is_ok());
+}
+
+#[test]
+fn test_mcp_schema_validation_rejects_malformed_schemas() {
+    let cases = [
+        (serde_json::json!({}), "missing input schema"),
+        (
+            serde_json::json!({ "type": "string" }),
+            "\"type\": \"object\"",
+        ),
+        (
+            serde_json::json!({ "type": "object", "properties": [] }),
+            "input schema.properties must be an object",
+        ),
+        (
+            serde_json::json!({ "type": "object", "properties": { "q": { "type": "text" } } }),
+            "input schema.properties.q has an invalid type",
+        ),
+        (
+            serde_json::json!({ "type": "object", "properties": {}, "required": ["q"] }),
+            "lists `q`, which is not a property",
+        ),
+    ];
+    for (schema, expected) in cases {
+        let error = MCPTool::validate_schema(&mcp_tool(schema)).unwrap_err();
+        assert!(error.to_string().contains(expected), "{error}");
+    }
+}
+
+#[swarms_rs::swarms_macro::tool(
+    name = "search_web",
+    description = "Search the web",
+    arg(query, description = "The search query"),
+    arg(mode, description = "Search mode", allowed_values = ["fast", "deep"]),
+    arg(lang, description = "Result language", required = false, allowed_values = ["en", "de"])
+)]
+fn search(query: String, mode: String, lang: Option<String>) -> Result<String, std::io::Error> {
+    Ok(format!(
+        "{query} ({mode}, {})",
+        lang.as_deref().unwrap_or("any")
+    ))
+}
+
+#[tokio::test]
+async fn test_tool_macro_custom_name_and_allowed_values() {
+    let tool: Box<dyn ToolDyn> = Box::new(SearchWeb);
+    assert_eq!(ToolDyn::name(tool.as_ref()), "search_web");
+
+    let definition = tool.definition();
+    assert_eq!(
+        definition.parameters["properties"]["mode"]["enum"],
+        serde_json::json!(["fast", "deep"])
+    );
+
+    let result = tool
+        .call(r#"{"query": "rust", "mode": "deep", "lang": null}"#.to_string())
+        .await
+        .unwrap();
+    assert_eq!(result, r#""rust (deep, any)""#);
+
+    for args in [
+        r#"{"query": "rust", "mode": "slow", "lang": null}"#,
+        r#"{"query": "rust", "mode": "fast", "lang": "fr"}"#,
+    ] {
+        let result = tool.call(args.to_string()).await;
+        assert!(matches!(result, Err(ToolError::InvalidArguments(_))));
+    }
+}
+
+#[swarms_rs::swarms_macro::tool(
+    description = "Render a chart",
+    arg(kind, description = "Kind of output")
+)]
+fn render_chart(kind: String) -> Result<ToolOutput, std::io::Error> {
+    Ok(match kind.as_str() {
+        "json" => ToolOutput::Json(serde_json::json!({ "bars": [1, 2] })),
+        _ => ToolOutput::Text("a bar chart".to_string()),
+    })
+}
+
+#[tokio::test]
+async fn test_tool_macro_rich_output() {
+    let tool: Box<dyn ToolDyn> = Box::new(RenderChart);
+
+    let call = |kind: &str| tool.call(format!(r#"{{"kind": "{kind}"}}"#));
+    assert_eq!(call("json").await.unwrap(), r#"{"bars":[1,2]}"#);
+    assert_eq!(call("text").await.unwrap(), "a bar chart");
+}
+
+struct ReadFile;
+
+impl Tool for ReadFile {
+    type Error = std::io::Error;
+    type Args = MockArgs;
+    type Output = String;
+
+    const NAME: &'static str = "read_file";
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description: "Read a file".to_string(),
+            parameters: serde_json::json!({}),
+            output_schema: None,
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        std::fs::read_to_string(args.input)
+    }
+
+    fn describe_failure(&self, error: &ToolError) -> String {
+        format!("{error}\nCheck that the path is within the allowed directories.")
+    }
+}
+
+#[tokio::test]
+async fn test_describe_failure() {
+    let tool: Box<dyn ToolDyn> = Box::new(MockTool::new_with_error("failing_tool"));
+    let error = tool
+        .call(r#"{"input": "test"}"#.to_string())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        tool.describe_failure(&error),
+        format!("{error}\n{}", error.suggestion())
+    );
+
+    let tool: Box<dyn ToolDyn> = Box::new(ReadFile);
+    let error = tool
+        .call(r#"{"input": "/nonexistent/file.txt"}"#.to_string())
+        .await
+        .unwrap_err();
+    assert!(
+        tool.describe_failure(&error)
+            .ends_with("Check that the path is within the allowed directories.")
+    );
+}